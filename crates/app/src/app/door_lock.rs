@@ -0,0 +1,155 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crate::domain::{LockActuator, LockError, LockState, LockStateStore};
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Tracks the lock state on top of a [`LockActuator`].
+///
+/// The state is persisted after every move, so a restart can skip the boot
+/// cycle when the bolt is already where the boot policy wants it. The state is
+/// unknown after a boot move that failed, until the next move succeeds. Unlocks are
+/// rate limited so a stuck button cannot rattle the hardware; locking is
+/// never refused.
+#[derive(Debug)]
+pub struct DoorLockController<A, S>
+where
+    A: LockActuator,
+    S: LockStateStore,
+{
+    actuator: A,
+    store: S,
+    state: Option<LockState>,
+    max_cycles_per_minute: usize,
+    cycles: VecDeque<Instant>,
+}
+
+impl<A, S> DoorLockController<A, S>
+where
+    A: LockActuator,
+    S: LockStateStore,
+{
+    /// Brings the bolt into `boot_state` unless the persisted state says it
+    /// is already there.
+    ///
+    /// A failed move is logged rather than returned, so that a broken
+    /// actuator leaves the terminal running with an unknown state instead of
+    /// crash-looping and driving the bolt again on every restart.
+    pub async fn boot(
+        actuator: A,
+        store: S,
+        boot_state: LockState,
+        max_cycles_per_minute: usize,
+    ) -> Self {
+        let persisted = store.load().unwrap_or_else(|error| {
+            warn!(error = %error, "failed to load persisted lock state");
+            None
+        });
+
+        let mut controller = Self {
+            actuator,
+            store,
+            state: None,
+            max_cycles_per_minute,
+            cycles: VecDeque::new(),
+        };
+
+        if persisted == Some(boot_state) {
+            info!(state = ?boot_state, "restored persisted lock state");
+            controller.state = persisted;
+            return controller;
+        }
+
+        info!(?persisted, state = ?boot_state, "moving lock to boot state");
+        if let Err(error) = controller.move_to(boot_state).await {
+            error!(error = %error, state = ?boot_state, "failed to move lock to boot state");
+        }
+
+        controller
+    }
+
+    /// Where the bolt is, or `None` if a failed boot move left it unknown.
+    #[must_use]
+    pub fn state(&self) -> Option<LockState> {
+        self.state
+    }
+
+    /// Unlocks the door unless it is already unlocked and the actuator holds
+    /// it that way.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rate limit is exceeded or the actuator fails.
+    pub async fn unlock(&mut self) -> Result<(), LockError> {
+        if self.state == Some(LockState::Unlocked) && self.actuator.holds_unlocked() {
+            return Ok(());
+        }
+
+        self.acquire_cycle()?;
+        self.move_to(LockState::Unlocked).await
+    }
+
+    /// Locks the door unless it is already locked. Never rate limited, so the
+    /// door is not left open after a burst of unlocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the actuator fails.
+    pub async fn lock(&mut self) -> Result<(), LockError> {
+        if self.state == Some(LockState::Locked) {
+            return Ok(());
+        }
+
+        self.move_to(LockState::Locked).await
+    }
+
+    async fn move_to(&mut self, state: LockState) -> Result<(), LockError> {
+        match state {
+            LockState::Locked => {
+                info!("locking door");
+                self.actuator.lock().await.map_err(LockError::Actuator)?;
+                info!("door locked");
+            }
+            LockState::Unlocked => {
+                info!("unlocking door");
+                self.actuator.unlock().await.map_err(LockError::Actuator)?;
+                info!("door unlocked");
+            }
+        }
+        self.state = Some(state);
+
+        if let Err(error) = self.store.save(state) {
+            warn!(?state, error = %error, "failed to persist lock state");
+        }
+
+        Ok(())
+    }
+
+    /// Counts an unlock against the per-minute limit. Every unlock is
+    /// followed by at most one lock, so this bounds full cycles as well.
+    fn acquire_cycle(&mut self) -> Result<(), LockError> {
+        let now = Instant::now();
+        while self
+            .cycles
+            .front()
+            .is_some_and(|cycle| now.duration_since(*cycle) >= RATE_LIMIT_WINDOW)
+        {
+            self.cycles.pop_front();
+        }
+
+        if self.cycles.len() >= self.max_cycles_per_minute {
+            warn!(
+                max_cycles = self.max_cycles_per_minute,
+                "lock cycle rate limit exceeded"
+            );
+            return Err(LockError::RateLimited {
+                max_cycles: self.max_cycles_per_minute,
+            });
+        }
+
+        self.cycles.push_back(now);
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use crate::domain::{
+    DoorEvent, DoorEventApi, DoorEventRequest, DoorSensor, DoorState, SoundEvent, SoundPlayer,
+};
+use tokio::time::{self, Instant};
+use tracing::{error, info, warn};
+
+pub struct DoorMonitorUseCase<S, A, P>
+where
+    S: DoorSensor,
+    A: DoorEventApi,
+    P: SoundPlayer,
+{
+    sensor: S,
+    api: A,
+    player: P,
+    left_open_threshold: Duration,
+    report_events: bool,
+}
+
+impl<S, A, P> DoorMonitorUseCase<S, A, P>
+where
+    S: DoorSensor,
+    A: DoorEventApi,
+    P: SoundPlayer,
+{
+    pub fn new(
+        sensor: S,
+        api: A,
+        player: P,
+        left_open_threshold: Duration,
+        report_events: bool,
+    ) -> Self {
+        Self {
+            sensor,
+            api,
+            player,
+            left_open_threshold,
+            report_events,
+        }
+    }
+
+    /// Watches the door sensor, alerting every `left_open_threshold` while the
+    /// door stays open. Open/close events are reported only if `report_events`
+    /// is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the door sensor stops delivering state changes.
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        // 開いた時刻と、次に開けっ放しの警告を出す時刻
+        let mut open: Option<(Instant, Instant)> = None;
+        if self.sensor.state() == DoorState::Open {
+            let now = Instant::now();
+            open = Some((now, now + self.left_open_threshold));
+        }
+
+        loop {
+            let Some((opened_at, alert_at)) = open else {
+                if self.sensor.wait_for_change().await? == DoorState::Open {
+                    info!("door opened");
+                    let now = Instant::now();
+                    open = Some((now, now + self.left_open_threshold));
+                    self.report(DoorEvent::Opened, None).await;
+                }
+                continue;
+            };
+
+            let Ok(state) = time::timeout_at(alert_at, self.sensor.wait_for_change()).await else {
+                let open_secs = opened_at.elapsed().as_secs();
+                warn!(open_secs, "door left open");
+                if let Err(error) = self.player.play(SoundEvent::DoorLeftOpen) {
+                    error!(error = %error, "failed to play door-left-open alert");
+                }
+                self.report(DoorEvent::LeftOpen, Some(open_secs)).await;
+                open = Some((opened_at, alert_at + self.left_open_threshold));
+                continue;
+            };
+
+            if state? == DoorState::Closed {
+                let open_secs = opened_at.elapsed().as_secs();
+                info!(open_secs, "door closed");
+                open = None;
+                self.report(DoorEvent::Closed, Some(open_secs)).await;
+            }
+        }
+    }
+
+    async fn report(&self, event: DoorEvent, open_secs: Option<u64>) {
+        if !self.report_events {
+            return;
+        }
+        let req = DoorEventRequest { event, open_secs };
+        if let Err(error) = self.api.report_door_event(req).await {
+            error!(?event, error = %error, "failed to report door event");
+        }
+    }
+}
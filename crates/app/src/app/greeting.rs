@@ -0,0 +1,221 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike as _, FixedOffset, Local, NaiveDate, NaiveTime, Weekday};
+use serde::Deserialize;
+
+use crate::domain::{Clock, SoundEvent};
+
+/// Daily time range written as `HH:MM-HH:MM`. Ranges ending before they start
+/// wrap past midnight, e.g. `18:00-06:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TimeRange {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeRange {
+    #[must_use]
+    pub fn contains(self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .map_err(|_| format!("invalid time: {time}"))
+        };
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("expected <start>-<end>: {s}"))?;
+        let (start, end) = (parse_time(start)?, parse_time(end)?);
+        if start == end {
+            return Err(format!("time range must not be empty: {s}"));
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+impl TryFrom<String> for TimeRange {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Calendar dates a rule applies to:
+///
+/// - `MM-DD` or `MM-DD..MM-DD` every year, wrapping past the new year
+/// - `YYYY-MM-DD` or `YYYY-MM-DD..YYYY-MM-DD` once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum DateSpec {
+    Yearly { from: (u32, u32), to: (u32, u32) },
+    Once { from: NaiveDate, to: NaiveDate },
+}
+
+impl DateSpec {
+    #[must_use]
+    pub fn contains(self, date: NaiveDate) -> bool {
+        match self {
+            Self::Yearly { from, to } => {
+                let day = (date.month(), date.day());
+                if from <= to {
+                    from <= day && day <= to
+                } else {
+                    from <= day || day <= to
+                }
+            }
+            Self::Once { from, to } => from <= date && date <= to,
+        }
+    }
+}
+
+impl FromStr for DateSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s.split_once("..").unwrap_or((s, s));
+        let (from, to) = (from.trim(), to.trim());
+
+        if let (Ok(from), Ok(to)) = (
+            NaiveDate::parse_from_str(from, "%Y-%m-%d"),
+            NaiveDate::parse_from_str(to, "%Y-%m-%d"),
+        ) {
+            if from > to {
+                return Err(format!("date range must end after it starts: {s}"));
+            }
+            return Ok(Self::Once { from, to });
+        }
+
+        // 閏日も書けるよう閏年で検証する
+        let parse_day = |day: &str| {
+            NaiveDate::parse_from_str(&format!("2000-{day}"), "%Y-%m-%d")
+                .map(|date| (date.month(), date.day()))
+                .map_err(|_| format!("invalid date: {day}"))
+        };
+        Ok(Self::Yearly {
+            from: parse_day(from)?,
+            to: parse_day(to)?,
+        })
+    }
+}
+
+impl TryFrom<String> for DateSpec {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// What the API told us about an entry, besides its status.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Visit {
+    pub first_entry_today: bool,
+    pub last_entry_at: Option<DateTime<FixedOffset>>,
+}
+
+/// Entry greeting played when every condition of the rule holds. Conditions
+/// that are left out always hold.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GreetingRule {
+    pub sound: SoundEvent,
+    /// Said before the user's name when speech is available. Without it the
+    /// sound is played even for users with a display name.
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub time: Option<TimeRange>,
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    #[serde(default)]
+    pub dates: Vec<DateSpec>,
+    /// Only for the first person to enter the room that day.
+    #[serde(default)]
+    pub first_entry_of_day: bool,
+    /// Only for users who have not entered for at least this many days.
+    #[serde(default)]
+    pub away_days: Option<u32>,
+}
+
+impl GreetingRule {
+    fn time_of_day(sound: SoundEvent, text: &str, time: Option<&str>) -> Self {
+        Self {
+            sound,
+            text: Some(text.to_string()),
+            time: time.map(|time| time.parse().expect("built-in time range is valid")),
+            weekdays: Vec::new(),
+            dates: Vec::new(),
+            first_entry_of_day: false,
+            away_days: None,
+        }
+    }
+
+    fn matches(&self, now: DateTime<Local>, visit: &Visit) -> bool {
+        let now = now.naive_local();
+        self.time.is_none_or(|time| time.contains(now.time()))
+            && (self.weekdays.is_empty() || self.weekdays.contains(&now.weekday()))
+            && (self.dates.is_empty() || self.dates.iter().any(|dates| dates.contains(now.date())))
+            && (!self.first_entry_of_day || visit.first_entry_today)
+            && self.away_days.is_none_or(|away_days| {
+                visit.last_entry_at.is_some_and(|last| {
+                    (now.date() - last.with_timezone(&Local).date_naive()).num_days()
+                        >= i64::from(away_days)
+                })
+            })
+    }
+}
+
+/// Picks the entry greeting from configured rules, checked in order, falling
+/// back to the built-in time-of-day greetings.
+#[derive(Debug, Clone)]
+pub struct GreetingSchedule {
+    rules: Vec<GreetingRule>,
+    fallback: GreetingRule,
+}
+
+impl Default for GreetingSchedule {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl GreetingSchedule {
+    #[must_use]
+    pub fn new(mut rules: Vec<GreetingRule>) -> Self {
+        rules.extend([
+            GreetingRule::time_of_day(
+                SoundEvent::GoodMorning,
+                "おはようございます",
+                Some("06:00-12:00"),
+            ),
+            GreetingRule::time_of_day(SoundEvent::Hello, "こんにちは", Some("12:00-18:00")),
+        ]);
+
+        Self {
+            rules,
+            fallback: GreetingRule::time_of_day(SoundEvent::GoodEvening, "こんばんは", None),
+        }
+    }
+
+    /// Returns the first rule matching the current time and `visit`.
+    pub fn select(&self, clock: &impl Clock, visit: &Visit) -> &GreetingRule {
+        let now = clock.now();
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(now, visit))
+            .unwrap_or(&self.fallback)
+    }
+}
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use tokio::time::{self, Instant};
+use tracing::{debug, warn};
+
+use crate::domain::{DoorLock, HeartbeatApi, HeartbeatRequest, SoundPlayer};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+/// First retry after a failed heartbeat. Doubles on every further failure.
+/// Also the shortest interval the server can ask for.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+/// Longest interval the server can ask for, so that a bad value does not stop
+/// heartbeats for days.
+const MAX_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// What only the caller knows about the terminal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerminalHealth {
+    pub readers: usize,
+    pub last_error: Option<String>,
+}
+
+/// Sends a heartbeat every interval, retrying failures with exponential
+/// backoff.
+pub struct HeartbeatUseCase<A, D, P, F>
+where
+    A: HeartbeatApi,
+    D: DoorLock,
+    P: SoundPlayer,
+    F: Fn() -> TerminalHealth,
+{
+    api: A,
+    door_lock: D,
+    player: P,
+    health: F,
+    interval: Duration,
+    started: Instant,
+}
+
+impl<A, D, P, F> HeartbeatUseCase<A, D, P, F>
+where
+    A: HeartbeatApi,
+    D: DoorLock,
+    P: SoundPlayer,
+    F: Fn() -> TerminalHealth,
+{
+    pub fn new(api: A, door_lock: D, player: P, health: F) -> Self {
+        Self {
+            api,
+            door_lock,
+            player,
+            health,
+            interval: DEFAULT_INTERVAL,
+            started: Instant::now(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sends heartbeats forever. Never returns; failures are logged and
+    /// retried.
+    pub async fn run(self) {
+        let mut failures = 0;
+        loop {
+            let delay = match self.api.heartbeat(self.request().await).await {
+                Ok(response) => {
+                    failures = 0;
+                    let delay = response
+                        .next_heartbeat_secs
+                        .map_or(self.interval, Duration::from_secs)
+                        .clamp(RETRY_DELAY, MAX_INTERVAL);
+                    debug!(next_secs = delay.as_secs(), "sent heartbeat");
+                    delay
+                }
+                Err(error) => {
+                    let delay = retry_delay(failures);
+                    failures += 1;
+                    warn!(
+                        error = %error,
+                        failures,
+                        retry_secs = delay.as_secs(),
+                        "failed to send heartbeat"
+                    );
+                    delay
+                }
+            };
+            time::sleep(delay).await;
+        }
+    }
+
+    async fn request(&self) -> HeartbeatRequest {
+        let health = (self.health)();
+        HeartbeatRequest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: self.started.elapsed().as_secs(),
+            readers: health.readers,
+            lock_state: self.door_lock.state().await,
+            queued_sounds: self.player.queue_len(),
+            last_error: health.last_error,
+        }
+    }
+}
+
+fn retry_delay(failures: u32) -> Duration {
+    RETRY_DELAY
+        .saturating_mul(2_u32.saturating_pow(failures))
+        .min(MAX_RETRY_DELAY)
+}
@@ -0,0 +1,67 @@
+use crate::domain::{ButtonPress, DoorEvent, DoorEventApi, DoorEventRequest, DoorLock, LockButton};
+use tracing::{error, info};
+
+pub struct LockButtonUseCase<B, D, A>
+where
+    B: LockButton,
+    D: DoorLock,
+    A: DoorEventApi,
+{
+    button: B,
+    door_lock: D,
+    api: A,
+    report_events: bool,
+}
+
+impl<B, D, A> LockButtonUseCase<B, D, A>
+where
+    B: LockButton,
+    D: DoorLock,
+    A: DoorEventApi,
+{
+    pub fn new(button: B, door_lock: D, api: A, report_events: bool) -> Self {
+        Self {
+            button,
+            door_lock,
+            api,
+            report_events,
+        }
+    }
+
+    /// Handles button presses: a short press unlocks with the normal auto-lock
+    /// timer and a long press locks immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the button stops delivering presses.
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        loop {
+            let press = self.button.next_press().await?;
+            let (event, result) = match press {
+                ButtonPress::Short => {
+                    info!("unlocking door from button");
+                    (DoorEvent::ManualUnlock, self.door_lock.unlock().await)
+                }
+                ButtonPress::Long => {
+                    info!("locking door from button");
+                    (DoorEvent::ManualLock, self.door_lock.lock().await)
+                }
+            };
+
+            if let Err(error) = result {
+                error!(?press, error = %error, "failed to handle button press");
+                continue;
+            }
+
+            if self.report_events {
+                let req = DoorEventRequest {
+                    event,
+                    open_secs: None,
+                };
+                if let Err(error) = self.api.report_door_event(req).await {
+                    error!(?event, error = %error, "failed to report door event");
+                }
+            }
+        }
+    }
+}
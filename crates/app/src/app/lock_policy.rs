@@ -0,0 +1,77 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use chrono::NaiveDate;
+
+use crate::domain::{Clock, LockMode, OpenHours};
+
+/// What a successful touch does to the door.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockDecision {
+    /// The door is held open by the schedule; leave it alone.
+    HoldOpen,
+    /// Unlock and let the auto-lock relock the door.
+    UnlockOnTouch,
+    /// Keep the door locked.
+    Deny,
+}
+
+/// Decides how the door behaves from the lock mode, weekly open hours, and
+/// holidays on which the open hours do not apply.
+#[derive(Debug, Clone, Default)]
+pub struct LockPolicy {
+    mode: LockMode,
+    open_hours: Vec<OpenHours>,
+    holidays: HashSet<NaiveDate>,
+}
+
+impl LockPolicy {
+    #[must_use]
+    pub fn new(mode: LockMode, open_hours: Vec<OpenHours>, holidays: HashSet<NaiveDate>) -> Self {
+        Self {
+            mode,
+            open_hours,
+            holidays,
+        }
+    }
+
+    /// Returns the decision for the current time. `override_mode` (e.g. from
+    /// the API response) replaces the configured mode.
+    ///
+    /// The clock is only consulted when open hours are configured.
+    pub fn decide(&self, clock: &impl Clock, override_mode: Option<LockMode>) -> LockDecision {
+        match override_mode.unwrap_or(self.mode) {
+            LockMode::Lockdown => LockDecision::Deny,
+            LockMode::Normal if self.is_open(clock) => LockDecision::HoldOpen,
+            LockMode::Normal | LockMode::TouchOnly => LockDecision::UnlockOnTouch,
+        }
+    }
+
+    fn is_open(&self, clock: &impl Clock) -> bool {
+        if self.open_hours.is_empty() {
+            return false;
+        }
+
+        let now = clock.now().naive_local();
+        !self.holidays.contains(&now.date())
+            && self.open_hours.iter().any(|hours| hours.contains(now))
+    }
+}
+
+/// The lock mode sent with the latest successful touch, shared so that the
+/// lock schedule follows it between touches.
+#[derive(Debug, Clone, Default)]
+pub struct LockModeOverride(Arc<Mutex<Option<LockMode>>>);
+
+impl LockModeOverride {
+    #[must_use]
+    pub fn get(&self) -> Option<LockMode> {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set(&self, mode: Option<LockMode>) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = mode;
+    }
+}
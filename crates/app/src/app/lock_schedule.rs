@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use tokio::time;
+use tracing::{error, info};
+
+use crate::{
+    app::{LockDecision, LockModeOverride, LockPolicy},
+    domain::{Clock, DoorLock},
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Holds the door open while the lock policy says so, and locks it again when
+/// the open hours end or the API overrides the lock mode, e.g. with
+/// `lockdown`.
+///
+/// Only transitions are acted on, so locking by hand during open hours (e.g.
+/// with a long button press) sticks until the next transition.
+pub struct LockScheduleUseCase<C, D>
+where
+    C: Clock,
+    D: DoorLock,
+{
+    clock: C,
+    door_lock: D,
+    policy: LockPolicy,
+    lock_mode: LockModeOverride,
+}
+
+impl<C, D> LockScheduleUseCase<C, D>
+where
+    C: Clock,
+    D: DoorLock,
+{
+    pub fn new(clock: C, door_lock: D, policy: LockPolicy) -> Self {
+        Self {
+            clock,
+            door_lock,
+            policy,
+            lock_mode: LockModeOverride::default(),
+        }
+    }
+
+    /// Follows the lock mode last sent by the API.
+    #[must_use]
+    pub fn with_lock_mode_override(mut self, lock_mode: LockModeOverride) -> Self {
+        self.lock_mode = lock_mode;
+        self
+    }
+
+    /// Evaluates the policy periodically. Never returns; lock failures are
+    /// logged and retried on the next check.
+    pub async fn run(self) {
+        let mut interval = time::interval(CHECK_INTERVAL);
+        let mut holding_open = false;
+        loop {
+            interval.tick().await;
+
+            let decision = self.policy.decide(&self.clock, self.lock_mode.get());
+            let hold_open = decision == LockDecision::HoldOpen;
+            if hold_open == holding_open {
+                continue;
+            }
+
+            let result = if hold_open {
+                info!("open hours started; holding door open");
+                self.door_lock.hold_open().await
+            } else {
+                info!(?decision, "door no longer held open; locking door");
+                self.door_lock.lock().await
+            };
+            match result {
+                Ok(()) => holding_open = hold_open,
+                Err(error) => error!(error = %error, hold_open, "failed to apply lock schedule"),
+            }
+        }
+    }
+}
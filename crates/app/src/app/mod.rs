@@ -1,3 +1,21 @@
+pub mod door_lock;
+pub mod door_monitor;
+pub mod greeting;
+pub mod heartbeat;
+pub mod lock_button;
+pub mod lock_policy;
+pub mod lock_schedule;
+pub mod sound_scheduler;
 pub mod touch_card;
+pub mod volume;
 
-pub use touch_card::TouchCardUseCase;
+pub use door_lock::DoorLockController;
+pub use door_monitor::DoorMonitorUseCase;
+pub use greeting::{DateSpec, GreetingRule, GreetingSchedule, TimeRange, Visit};
+pub use heartbeat::{HeartbeatUseCase, TerminalHealth};
+pub use lock_button::LockButtonUseCase;
+pub use lock_policy::{LockDecision, LockModeOverride, LockPolicy};
+pub use lock_schedule::LockScheduleUseCase;
+pub use sound_scheduler::SoundScheduler;
+pub use touch_card::{TouchCardError, TouchCardUseCase};
+pub use volume::VolumePolicy;
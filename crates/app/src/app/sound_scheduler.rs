@@ -0,0 +1,181 @@
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use tokio::time;
+use tracing::{debug, error, info};
+
+use crate::{
+    app::VolumePolicy,
+    domain::{
+        AudioOutput, Clock, Metrics, NoopMetrics, SoundError, SoundEvent, SoundPlayer, SoundPolicy,
+        SoundRequest,
+    },
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Plays sounds one at a time according to the priority and policy of each
+/// [`SoundEvent`].
+///
+/// The output only reports whether it is still playing, so [`Self::run`] has
+/// to be polled for queued sounds to start after the current one finishes.
+/// The volume policy is evaluated when a sound starts, not when it is
+/// requested.
+pub struct SoundScheduler<O: AudioOutput, C: Clock, M: Metrics = NoopMetrics> {
+    output: O,
+    clock: C,
+    volume: VolumePolicy,
+    metrics: M,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    current: Option<SoundRequest>,
+    pending: VecDeque<SoundRequest>,
+}
+
+impl<O: AudioOutput, C: Clock> SoundScheduler<O, C> {
+    pub fn new(output: O, clock: C) -> Self {
+        Self {
+            output,
+            clock,
+            volume: VolumePolicy::default(),
+            metrics: NoopMetrics,
+            state: Mutex::default(),
+        }
+    }
+}
+
+impl<O: AudioOutput, C: Clock, M: Metrics> SoundScheduler<O, C, M> {
+    #[must_use]
+    pub fn with_volume(mut self, volume: VolumePolicy) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// Reports the queue depth and sounds that fail to start to `metrics`.
+    pub fn with_metrics<N: Metrics>(self, metrics: N) -> SoundScheduler<O, C, N> {
+        SoundScheduler {
+            output: self.output,
+            clock: self.clock,
+            volume: self.volume,
+            metrics,
+            state: self.state,
+        }
+    }
+
+    /// Schedules `request` according to the policy of its event.
+    ///
+    /// Playback failures are logged instead of returned, since the sound may
+    /// start long after it was requested.
+    pub fn request(&self, request: SoundRequest) {
+        let mut state = self.state();
+        self.advance(&mut state);
+
+        let sound = request.event();
+        let priority = sound.priority();
+        let busy = state.current.is_some() || !state.pending.is_empty();
+        match sound.policy() {
+            SoundPolicy::DropIfBusy if busy => {
+                debug!(?sound, "dropping sound while another one is playing");
+                return;
+            }
+            SoundPolicy::Coalesce
+                if state.current.as_ref() == Some(&request) || state.pending.contains(&request) =>
+            {
+                debug!(?sound, "coalescing duplicate sound");
+                return;
+            }
+            SoundPolicy::Interrupt
+                if state
+                    .current
+                    .as_ref()
+                    .is_none_or(|current| current.event().priority() <= priority) =>
+            {
+                state
+                    .pending
+                    .retain(|queued| queued.event().priority() >= priority);
+                if let Some(current) = state.current.take() {
+                    info!(?sound, interrupted = ?current.event(), "interrupting sound");
+                    self.output.stop();
+                }
+                state.pending.push_front(request);
+            }
+            _ => {
+                // 同じ優先度の中では先着順にする
+                let index = state
+                    .pending
+                    .iter()
+                    .position(|queued| queued.event().priority() < priority)
+                    .unwrap_or(state.pending.len());
+                state.pending.insert(index, request);
+            }
+        }
+
+        self.advance(&mut state);
+        self.metrics.sound_queue_depth(state.pending.len());
+    }
+
+    /// Starts queued sounds as the current ones finish. Never returns.
+    pub async fn run(&self) {
+        let mut interval = time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let mut state = self.state();
+            self.advance(&mut state);
+            self.metrics.sound_queue_depth(state.pending.len());
+        }
+    }
+
+    /// Starts the next queued sound once the current one has finished,
+    /// skipping sounds that fail to start.
+    fn advance(&self, state: &mut State) {
+        if state.current.is_some() && self.output.is_playing() {
+            return;
+        }
+
+        state.current = None;
+        while let Some(next) = state.pending.pop_front() {
+            let (request, gain) = self.volume.apply(&self.clock, &next);
+            let sound = request.event();
+            match self.output.start(&request, gain) {
+                Ok(()) => {
+                    info!(?sound, gain, "playing sound");
+                    state.current = Some(next);
+                    return;
+                }
+                Err(error) => {
+                    error!(?sound, error = %error, "failed to play sound");
+                    self.metrics.sound_error(sound);
+                }
+            }
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<O: AudioOutput, C: Clock, M: Metrics> SoundPlayer for SoundScheduler<O, C, M> {
+    fn play(&self, sound: SoundEvent) -> Result<(), SoundError> {
+        self.request(SoundRequest::Event(sound));
+        Ok(())
+    }
+
+    fn speak(&self, text: &str, fallback: SoundEvent) -> Result<(), SoundError> {
+        self.request(SoundRequest::Speech {
+            text: text.to_string(),
+            fallback,
+        });
+        Ok(())
+    }
+
+    fn queue_len(&self) -> usize {
+        self.state().pending.len()
+    }
+}
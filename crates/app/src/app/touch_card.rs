@@ -1,21 +1,54 @@
+use crate::app::{GreetingSchedule, LockDecision, LockModeOverride, LockPolicy, Visit};
 use crate::domain::{
-    Card, CardApi, Clock, DoorLock, ErrorCode, RoomEntryStatus, SoundEvent, SoundPlayer,
-    TouchCardRequest, TouchCardResponse,
+    AccessLevel, ApiError, Card, CardApi, Clock, DisplayScreen, DoorLock, ErrorCode, Indicator,
+    IndicatorPattern, LockError, Metrics, NoopDisplay, NoopIndicator, NoopMetrics, RoomEntryStatus,
+    SoundEvent, SoundPlayer, StatusDisplay, TouchCardRequest, TouchCardResponse, TouchIntent,
+    TouchOutcome,
 };
-use chrono::Timelike;
-use tracing::{error, info};
+use tracing::{Instrument as _, error, info, info_span, warn};
 
-pub struct TouchCardUseCase<A, P, C, D>
+/// Why a touch could not be handled. Sound failures are only logged, since
+/// the door must open even when the speaker is broken.
+#[derive(Debug, thiserror::Error)]
+pub enum TouchCardError {
+    #[error(transparent)]
+    Api(#[from] ApiError),
+    #[error("failed to unlock the door: {0}")]
+    DoorLock(#[from] LockError),
+}
+
+impl TouchCardError {
+    /// How the touch ended because of this error.
+    #[must_use]
+    pub fn outcome(&self) -> TouchOutcome {
+        match self {
+            Self::Api(error) if error.is_offline() => TouchOutcome::Offline,
+            Self::Api(_) => TouchOutcome::ApiError,
+            Self::DoorLock(_) => TouchOutcome::LockFailed,
+        }
+    }
+}
+
+pub struct TouchCardUseCase<A, P, C, D, I = NoopIndicator, S = NoopDisplay, M = NoopMetrics>
 where
     A: CardApi,
     P: SoundPlayer,
     C: Clock,
     D: DoorLock,
+    I: Indicator,
+    S: StatusDisplay,
+    M: Metrics,
 {
     api: A,
     player: P,
     clock: C,
     door_lock: D,
+    indicator: I,
+    display: S,
+    metrics: M,
+    lock_policy: LockPolicy,
+    lock_mode: LockModeOverride,
+    greetings: GreetingSchedule,
 }
 
 impl<A, P, C, D> TouchCardUseCase<A, P, C, D>
@@ -31,104 +64,445 @@ where
             player,
             clock,
             door_lock,
+            indicator: NoopIndicator,
+            display: NoopDisplay,
+            metrics: NoopMetrics,
+            lock_policy: LockPolicy::default(),
+            lock_mode: LockModeOverride::default(),
+            greetings: GreetingSchedule::default(),
         }
     }
+}
+
+impl<A, P, C, D, I, S, M> TouchCardUseCase<A, P, C, D, I, S, M>
+where
+    A: CardApi,
+    P: SoundPlayer,
+    C: Clock,
+    D: DoorLock,
+    I: Indicator,
+    S: StatusDisplay,
+    M: Metrics,
+{
+    /// Drives `indicator` alongside the sounds.
+    pub fn with_indicator<J: Indicator>(
+        self,
+        indicator: J,
+    ) -> TouchCardUseCase<A, P, C, D, J, S, M> {
+        TouchCardUseCase {
+            api: self.api,
+            player: self.player,
+            clock: self.clock,
+            door_lock: self.door_lock,
+            indicator,
+            display: self.display,
+            metrics: self.metrics,
+            lock_policy: self.lock_policy,
+            lock_mode: self.lock_mode,
+            greetings: self.greetings,
+        }
+    }
+
+    /// Shows occupancy and touch results on `display`.
+    pub fn with_display<T: StatusDisplay>(
+        self,
+        display: T,
+    ) -> TouchCardUseCase<A, P, C, D, I, T, M> {
+        TouchCardUseCase {
+            api: self.api,
+            player: self.player,
+            clock: self.clock,
+            door_lock: self.door_lock,
+            indicator: self.indicator,
+            display,
+            metrics: self.metrics,
+            lock_policy: self.lock_policy,
+            lock_mode: self.lock_mode,
+            greetings: self.greetings,
+        }
+    }
+
+    /// Counts touches by outcome and card kind, and sounds that failed.
+    pub fn with_metrics<N: Metrics>(self, metrics: N) -> TouchCardUseCase<A, P, C, D, I, S, N> {
+        TouchCardUseCase {
+            api: self.api,
+            player: self.player,
+            clock: self.clock,
+            door_lock: self.door_lock,
+            indicator: self.indicator,
+            display: self.display,
+            metrics,
+            lock_policy: self.lock_policy,
+            lock_mode: self.lock_mode,
+            greetings: self.greetings,
+        }
+    }
+
+    #[must_use]
+    pub fn with_lock_policy(mut self, lock_policy: LockPolicy) -> Self {
+        self.lock_policy = lock_policy;
+        self
+    }
+
+    /// Shares the lock mode of each successful response through `lock_mode`,
+    /// e.g. with the lock schedule.
+    #[must_use]
+    pub fn with_lock_mode_override(mut self, lock_mode: LockModeOverride) -> Self {
+        self.lock_mode = lock_mode;
+        self
+    }
+
+    #[must_use]
+    pub fn with_greetings(mut self, greetings: GreetingSchedule) -> Self {
+        self.greetings = greetings;
+        self
+    }
 
     /// Executes the touch-card workflow for a single scanned card.
     ///
+    /// The API refuses touches that contradict the intent of the reader (e.g.
+    /// an exit-only reader and a user not in the room) without changing
+    /// presence. A success response that contradicts it anyway, from a server
+    /// that ignores the intent, plays guidance but never unlocks. Otherwise the lock policy and the access level granted by the API decide
+    /// whether the door is unlocked.
+    ///
+    /// The door is unlocked before any greeting is played, and sounds that fail
+    /// to play are logged and skipped, so a broken speaker never keeps a member
+    /// out. When unlocking fails the success screen and greeting are replaced
+    /// by an error.
+    ///
+    /// Returns how the touch ended, which is also reported to the metrics.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the API request fails, the selected sound cannot be
-    /// queued, or the door lock operation fails.
-    pub async fn execute(&self, card: &Card) -> anyhow::Result<()> {
+    /// Returns an error if the API request fails or the door cannot be
+    /// unlocked, after showing the failure on the indicator and display.
+    pub async fn execute(&self, card: &Card) -> Result<TouchOutcome, TouchCardError> {
+        let result = self.handle(card).await;
+        let outcome = match &result {
+            Ok(outcome) => *outcome,
+            Err(error) => error.outcome(),
+        };
+        self.metrics.touch(card.kind(), outcome);
+        result
+    }
+
+    async fn handle(&self, card: &Card) -> Result<TouchOutcome, TouchCardError> {
         let req: TouchCardRequest = card.clone().into();
         info!(
             idm = %card.idm,
             student_id = ?card.student_id,
             balance = ?card.balance,
+            intent = ?card.intent,
             "starting touch-card workflow"
         );
 
-        self.player.reset();
-        self.player.play(SoundEvent::Touch)?;
-
-        let response = self.api.touch(req).await;
-        let response = match response {
-            Ok(response) => response,
-            Err(error) => {
-                error!(
-                    idm = %card.idm,
-                    student_id = ?card.student_id,
-                    balance = ?card.balance,
-                    error = %error,
-                    "touch-card api call failed"
+        self.indicator.show(IndicatorPattern::Reading);
+        self.display.show(DisplayScreen::Reading);
+        self.play(SoundEvent::Touch);
+
+        let response = self.call_api(card, req).await?;
+
+        let outcome = match response {
+            TouchCardResponse::Success {
+                status, entries, ..
+            } if !card.intent.accepts(status) => {
+                warn!(
+                    intent = ?card.intent,
+                    ?status,
+                    entries,
+                    "touch-card response did not match reader intent"
                 );
-                return Err(error);
+                self.indicator.show(IndicatorPattern::Error);
+                self.display.show(DisplayScreen::Rejected(match status {
+                    RoomEntryStatus::Entry => ErrorCode::AlreadyEntered,
+                    RoomEntryStatus::Exit => ErrorCode::NotEntered,
+                }));
+                self.play_intent_mismatch(card.intent);
+                TouchOutcome::Rejected
             }
-        };
-
-        match response {
-            TouchCardResponse::Success { status, entries } => {
-                info!(?status, entries, "touch-card workflow succeeded");
-                self.play_success(status, entries)?;
-                self.door_lock.unlock().await?;
+            TouchCardResponse::Success {
+                status,
+                entries,
+                access,
+                display_name,
+                first_entry_today,
+                last_entry_at,
+                lock_mode,
+            } => {
+                info!(?status, entries, ?access, "touch-card workflow succeeded");
+                let visit = Visit {
+                    first_entry_today,
+                    last_entry_at,
+                };
+                self.lock_mode.set(lock_mode);
+                let decision = self.lock_policy.decide(&self.clock, lock_mode);
+                if let Err(error) = self.move_door(decision, access).await {
+                    error!(error = %error, "failed to unlock door");
+                    self.indicator.show(IndicatorPattern::Error);
+                    self.display.show(DisplayScreen::LockFailed { entries });
+                    self.play(SoundEvent::Error);
+                    return Err(error.into());
+                }
+                self.show_success(decision, access, status, entries, display_name.as_deref());
+                self.play_success(status, entries, display_name.as_deref(), &visit);
+                self.play_decision(decision, access);
                 info!(?status, entries, "completed touch-card success handling");
+                match status {
+                    RoomEntryStatus::Entry => TouchOutcome::Entered,
+                    RoomEntryStatus::Exit => TouchOutcome::Exited,
+                }
             }
-            TouchCardResponse::Error { error_code, .. } => {
+            TouchCardResponse::Error {
+                error_code,
+                registration_code,
+                ..
+            } => {
                 info!(?error_code, "touch-card workflow returned business error");
-                self.play_error(error_code)?;
+                self.indicator.show(IndicatorPattern::Error);
+                match registration_code {
+                    Some(code) if matches!(error_code, ErrorCode::NfcCardNotRegistered) => {
+                        self.announce_registration_code(code);
+                    }
+                    _ => {
+                        self.display.show(DisplayScreen::Rejected(error_code));
+                        self.play_error(error_code);
+                    }
+                }
+                TouchOutcome::Rejected
             }
+        };
+
+        Ok(outcome)
+    }
+
+    async fn call_api(
+        &self,
+        card: &Card,
+        req: TouchCardRequest,
+    ) -> Result<TouchCardResponse, ApiError> {
+        let response = self
+            .api
+            .touch(req)
+            .instrument(info_span!("touch_card_api"))
+            .await;
+        if let Err(error) = &response {
+            self.show_api_failure(error);
+            error!(
+                idm = %card.idm,
+                student_id = ?card.student_id,
+                balance = ?card.balance,
+                error = %error,
+                "touch-card api call failed"
+            );
         }
+        response
+    }
 
+    /// The only side effect the user waits for, so it runs before any sound.
+    async fn move_door(
+        &self,
+        decision: LockDecision,
+        access: AccessLevel,
+    ) -> Result<(), LockError> {
+        if decision == LockDecision::UnlockOnTouch && access == AccessLevel::Unlock {
+            self.door_lock
+                .unlock()
+                .instrument(info_span!("unlock"))
+                .await?;
+            info!("door unlocked");
+        }
         Ok(())
     }
 
-    fn play_success(&self, status: RoomEntryStatus, entries: u32) -> anyhow::Result<()> {
+    /// Explains after the greeting why the door did not move.
+    fn play_decision(&self, decision: LockDecision, access: AccessLevel) {
+        match decision {
+            LockDecision::UnlockOnTouch if access == AccessLevel::Unlock => {}
+            LockDecision::UnlockOnTouch => {
+                info!("presence recorded without door access");
+                self.play(SoundEvent::RecordOnly);
+            }
+            LockDecision::HoldOpen => info!("door is held open by schedule"),
+            LockDecision::Deny => {
+                warn!("lockdown; not unlocking door");
+                self.play(SoundEvent::Lockdown);
+            }
+        }
+    }
+
+    /// An unreachable API is shown as offline; anything else is a fault the
+    /// user cannot fix by waiting.
+    fn show_api_failure(&self, error: &ApiError) {
+        if error.is_offline() {
+            self.indicator.show(IndicatorPattern::Offline);
+            self.display.show(DisplayScreen::Offline);
+        } else {
+            self.indicator.show(IndicatorPattern::Error);
+            self.display
+                .show(DisplayScreen::Rejected(ErrorCode::Unknown));
+            self.play(SoundEvent::Error);
+        }
+    }
+
+    fn show_success(
+        &self,
+        decision: LockDecision,
+        access: AccessLevel,
+        status: RoomEntryStatus,
+        entries: u32,
+        display_name: Option<&str>,
+    ) {
+        let display_name = display_name.map(ToString::to_string);
+        let (pattern, screen) = match (decision, status) {
+            (LockDecision::Deny, _) => {
+                (IndicatorPattern::Error, DisplayScreen::Lockdown { entries })
+            }
+            (LockDecision::UnlockOnTouch, _) if access != AccessLevel::Unlock => (
+                IndicatorPattern::RecordOnly,
+                DisplayScreen::RecordOnly { entries },
+            ),
+            (_, RoomEntryStatus::Entry) => (
+                IndicatorPattern::SuccessEntry,
+                DisplayScreen::Entered {
+                    display_name,
+                    entries,
+                },
+            ),
+            (_, RoomEntryStatus::Exit) => (
+                IndicatorPattern::SuccessExit,
+                DisplayScreen::Exited {
+                    display_name,
+                    entries,
+                },
+            ),
+        };
+        self.indicator.show(pattern);
+        self.display.show(screen);
+    }
+
+    fn play_success(
+        &self,
+        status: RoomEntryStatus,
+        entries: u32,
+        display_name: Option<&str>,
+        visit: &Visit,
+    ) {
         match status {
             RoomEntryStatus::Entry => {
-                let now = self.clock.now();
-                let hour = now.hour();
-                match hour {
-                    6..=11 => {
-                        info!(hour, "playing morning greeting");
-                        self.player.play(SoundEvent::GoodMorning)?;
-                    }
-                    12..=17 => {
-                        info!(hour, "playing daytime greeting");
-                        self.player.play(SoundEvent::Hello)?;
-                    }
-                    _ => {
-                        info!(hour, "playing evening greeting");
-                        self.player.play(SoundEvent::GoodEvening)?;
-                    }
-                }
+                let rule = self.greetings.select(&self.clock, visit);
+                info!(sound = ?rule.sound, "playing entry greeting");
+                self.greet(rule.sound, rule.text.as_deref(), display_name);
             }
             RoomEntryStatus::Exit => {
-                self.player.play(SoundEvent::GoodBye)?;
+                self.greet(
+                    SoundEvent::GoodBye,
+                    Some("おつかれさまでした"),
+                    display_name,
+                );
                 if entries == 0 {
                     info!("playing last-person exit sound");
-                    self.player.play(SoundEvent::Last)?;
+                    self.play(SoundEvent::Last);
                 }
             }
         }
-        Ok(())
     }
 
-    fn play_error(&self, error_code: ErrorCode) -> anyhow::Result<()> {
+    /// Greets the user by name when the API provided one and the greeting has
+    /// words to say, falling back to the stock sound.
+    fn greet(&self, sound: SoundEvent, greeting: Option<&str>, display_name: Option<&str>) {
+        match (
+            greeting,
+            display_name.map(str::trim).filter(|name| !name.is_empty()),
+        ) {
+            (Some(greeting), Some(name)) => {
+                self.speak(&format!("{greeting}、{name}さん"), sound);
+            }
+            _ => self.play(sound),
+        }
+    }
+
+    fn play_error(&self, error_code: ErrorCode) {
         match error_code {
             ErrorCode::StudentCardNotRegistered => {
                 info!("playing student-card registration guidance");
-                self.player.play(SoundEvent::RegisterStudentCard)?;
+                self.play(SoundEvent::RegisterStudentCard);
             }
             ErrorCode::NfcCardNotRegistered => {
                 info!("playing nfc-card registration guidance");
-                self.player.play(SoundEvent::RegisterNfcCard)?;
+                self.play(SoundEvent::RegisterNfcCard);
+            }
+            ErrorCode::AlreadyEntered => {
+                info!("playing already-entered guidance");
+                self.play(SoundEvent::AlreadyEntered);
+            }
+            ErrorCode::NotEntered => {
+                info!("playing not-entered guidance");
+                self.play(SoundEvent::NotEntered);
             }
             _ => {
                 info!(?error_code, "playing generic error sound");
-                self.player.play(SoundEvent::Error)?;
+                self.play(SoundEvent::Error);
             }
         }
-        Ok(())
     }
+
+    /// Reads the code out digit by digit so that it can be typed into
+    /// `/room register nfc-card`. Without speech the stock guidance plays.
+    fn announce_registration_code(&self, code: String) {
+        info!(%code, "announcing nfc-card registration code");
+        let digits: Vec<_> = code.chars().map(spell_digit).collect();
+        let text = format!("NFCカードの登録コードは、{}、です", digits.join("、"));
+        self.display.show(DisplayScreen::RegisterNfcCard { code });
+        self.speak(&text, SoundEvent::RegisterNfcCard);
+    }
+
+    fn play(&self, sound: SoundEvent) {
+        let _span = info_span!("sound", ?sound).entered();
+        if let Err(error) = self.player.play(sound) {
+            warn!(?sound, error = %error, "failed to play sound; continuing");
+            self.metrics.sound_error(sound);
+        }
+    }
+
+    fn speak(&self, text: &str, fallback: SoundEvent) {
+        let _span = info_span!("speech", ?fallback).entered();
+        if let Err(error) = self.player.speak(text, fallback) {
+            warn!(?fallback, error = %error, "failed to speak; continuing");
+            self.metrics.sound_error(fallback);
+        }
+    }
+
+    fn play_intent_mismatch(&self, intent: TouchIntent) {
+        match intent {
+            TouchIntent::Entry => {
+                info!("playing already-entered guidance for entry-only reader");
+                self.play(SoundEvent::AlreadyEntered);
+            }
+            TouchIntent::Exit => {
+                info!("playing not-entered guidance for exit-only reader");
+                self.play(SoundEvent::NotEntered);
+            }
+            TouchIntent::Toggle => {}
+        }
+    }
+}
+
+/// Reading of a single digit, so that speech does not read the code as a
+/// number.
+fn spell_digit(c: char) -> String {
+    let reading = match c {
+        '0' => "ゼロ",
+        '1' => "イチ",
+        '2' => "ニー",
+        '3' => "サン",
+        '4' => "ヨン",
+        '5' => "ゴー",
+        '6' => "ロク",
+        '7' => "ナナ",
+        '8' => "ハチ",
+        '9' => "キュー",
+        _ => return c.to_string(),
+    };
+    reading.to_string()
 }
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use crate::app::TimeRange;
+use crate::domain::{Clock, SoundEvent, SoundRequest};
+
+/// Gain applied by the sound scheduler on top of the sound pack volume.
+#[derive(Debug, Clone)]
+pub struct VolumePolicy {
+    master: f32,
+    event_gains: HashMap<SoundEvent, f32>,
+    quiet_hours: Vec<TimeRange>,
+    quiet_gain: f32,
+    quiet_chime: bool,
+}
+
+impl Default for VolumePolicy {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            event_gains: HashMap::new(),
+            quiet_hours: Vec::new(),
+            quiet_gain: 1.0,
+            quiet_chime: false,
+        }
+    }
+}
+
+impl VolumePolicy {
+    #[must_use]
+    pub fn new(master: f32, event_gains: HashMap<SoundEvent, f32>) -> Self {
+        Self {
+            master,
+            event_gains,
+            ..Self::default()
+        }
+    }
+
+    /// During `hours`, multiplies the gain by `gain`, and with `chime`
+    /// replaces greetings with [`SoundEvent::Chime`].
+    #[must_use]
+    pub fn with_quiet_hours(mut self, hours: Vec<TimeRange>, gain: f32, chime: bool) -> Self {
+        self.quiet_hours = hours;
+        self.quiet_gain = gain;
+        self.quiet_chime = chime;
+        self
+    }
+
+    /// Returns what to actually play for `request` and its gain.
+    ///
+    /// The clock is only consulted when quiet hours are configured.
+    pub fn apply(&self, clock: &impl Clock, request: &SoundRequest) -> (SoundRequest, f32) {
+        let quiet = self.is_quiet(clock);
+        let request = if quiet && self.quiet_chime && request.event().is_greeting() {
+            SoundRequest::Event(SoundEvent::Chime)
+        } else {
+            request.clone()
+        };
+
+        let mut gain = self.master * self.event_gains.get(&request.event()).unwrap_or(&1.0);
+        if quiet {
+            gain *= self.quiet_gain;
+        }
+
+        (request, gain)
+    }
+
+    fn is_quiet(&self, clock: &impl Clock) -> bool {
+        if self.quiet_hours.is_empty() {
+            return false;
+        }
+
+        let now = clock.now().time();
+        self.quiet_hours.iter().any(|hours| hours.contains(now))
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use tracing::info;
+
+use crate::config::ServoAngles;
+
+const DEFAULT_STEP: i32 = 5;
+
+pub trait CalibrationServo {
+    /// Holds the servo at `angle` until the next move or release.
+    fn set_angle(&mut self, angle: u16) -> anyhow::Result<()>;
+
+    /// Stops driving the servo.
+    fn release(&mut self) -> anyhow::Result<()>;
+}
+
+/// Runs the interactive sweep on stdin/stdout and writes the saved angles to
+/// `env_file`.
+///
+/// # Errors
+///
+/// Returns an error if the servo cannot be moved or the env file cannot be
+/// written.
+pub fn calibrate(
+    mut servo: impl CalibrationServo,
+    initial: ServoAngles,
+    env_file: &Path,
+) -> anyhow::Result<()> {
+    let angles = run(io::stdin().lock(), io::stdout(), initial, |angle| {
+        servo.set_angle(angle)
+    });
+    servo.release()?;
+
+    let Some(angles) = angles? else {
+        info!("servo calibration aborted");
+        return Ok(());
+    };
+
+    write_env_file(env_file, angles)?;
+    info!(?angles, env_file = %env_file.display(), "saved servo calibration");
+
+    Ok(())
+}
+
+/// Interactive servo sweep.
+///
+/// Reads commands from `input` and moves the servo with `move_to` until the
+/// user saves or quits. Returns the recorded angles on save, `None` on quit.
+fn run(
+    mut input: impl BufRead,
+    mut output: impl Write,
+    initial: ServoAngles,
+    mut move_to: impl FnMut(u16) -> anyhow::Result<()>,
+) -> anyhow::Result<Option<ServoAngles>> {
+    let mut angles = initial;
+    let mut current = angles.neutral;
+
+    writeln!(
+        output,
+        "commands: <angle>, +[step], -[step], lock, unlock, neutral, save, quit"
+    )?;
+    move_to(current)?;
+
+    loop {
+        write!(
+            output,
+            "[angle {current}] lock={} unlock={} neutral={} > ",
+            angles.lock, angles.unlock, angles.neutral
+        )?;
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        match parse_command(line.trim()) {
+            Ok(Command::MoveTo(angle)) => {
+                current = angle;
+                move_to(current)?;
+            }
+            Ok(Command::MoveBy(delta)) => {
+                current = clamp_angle(i32::from(current) + delta);
+                move_to(current)?;
+            }
+            Ok(Command::RecordLock) => angles.lock = current,
+            Ok(Command::RecordUnlock) => angles.unlock = current,
+            Ok(Command::RecordNeutral) => angles.neutral = current,
+            Ok(Command::Save) => return Ok(Some(angles)),
+            Ok(Command::Quit) => return Ok(None),
+            Err(message) => writeln!(output, "{message}")?,
+        }
+    }
+}
+
+/// Sets `SERVO_*_ANGLE` in the env file, keeping every other line.
+fn write_env_file(path: &Path, angles: ServoAngles) -> anyhow::Result<()> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error.into()),
+    };
+
+    fs::write(path, update_env(&contents, angles))?;
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    MoveTo(u16),
+    MoveBy(i32),
+    RecordLock,
+    RecordUnlock,
+    RecordNeutral,
+    Save,
+    Quit,
+}
+
+fn parse_command(line: &str) -> Result<Command, String> {
+    match line {
+        "lock" | "l" => return Ok(Command::RecordLock),
+        "unlock" | "u" => return Ok(Command::RecordUnlock),
+        "neutral" | "n" => return Ok(Command::RecordNeutral),
+        "save" | "w" => return Ok(Command::Save),
+        "quit" | "q" => return Ok(Command::Quit),
+        _ => {}
+    }
+
+    if let Some(step) = line.strip_prefix('+') {
+        return parse_step(step).map(Command::MoveBy);
+    }
+    if let Some(step) = line.strip_prefix('-') {
+        return parse_step(step).map(|step| Command::MoveBy(-step));
+    }
+
+    match line.parse::<u16>() {
+        Ok(angle) if angle <= ServoAngles::MAX => Ok(Command::MoveTo(angle)),
+        _ => Err(format!("unknown command: {line}")),
+    }
+}
+
+fn parse_step(step: &str) -> Result<i32, String> {
+    if step.is_empty() {
+        return Ok(DEFAULT_STEP);
+    }
+
+    step.parse::<i32>()
+        .map_err(|_| format!("invalid step: {step}"))
+}
+
+fn clamp_angle(angle: i32) -> u16 {
+    u16::try_from(angle.clamp(0, i32::from(ServoAngles::MAX))).unwrap_or_default()
+}
+
+fn update_env(contents: &str, angles: ServoAngles) -> String {
+    let values = [
+        ("SERVO_LOCK_ANGLE", angles.lock),
+        ("SERVO_UNLOCK_ANGLE", angles.unlock),
+        ("SERVO_NEUTRAL_ANGLE", angles.neutral),
+    ];
+
+    let mut lines = contents
+        .lines()
+        .filter(|line| {
+            !values
+                .iter()
+                .any(|(key, _)| line.trim_start().starts_with(&format!("{key}=")))
+        })
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    lines.extend(values.iter().map(|(key, value)| format!("{key}={value}")));
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{Command, parse_command, run, update_env};
+    use crate::config::ServoAngles;
+
+    const DEFAULT_ANGLES: ServoAngles = ServoAngles {
+        lock: 0,
+        unlock: 180,
+        neutral: 90,
+    };
+
+    #[test]
+    fn parse_command_parses_steps_and_angles() {
+        assert_eq!(parse_command("+"), Ok(Command::MoveBy(5)));
+        assert_eq!(parse_command("-10"), Ok(Command::MoveBy(-10)));
+        assert_eq!(parse_command("45"), Ok(Command::MoveTo(45)));
+        assert_eq!(parse_command("l"), Ok(Command::RecordLock));
+        parse_command("181").unwrap_err();
+        parse_command("turn").unwrap_err();
+    }
+
+    #[test]
+    fn run_records_angles_and_moves_servo() {
+        let input = Cursor::new("10\nlock\n170\n+\nunlock\n-100\nneutral\nsave\n");
+        let mut moves = Vec::new();
+
+        let angles = run(input, Vec::new(), DEFAULT_ANGLES, |angle| {
+            moves.push(angle);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(
+            angles,
+            Some(ServoAngles {
+                lock: 10,
+                unlock: 175,
+                neutral: 75,
+            })
+        );
+        assert_eq!(moves, vec![90, 10, 170, 175, 75]);
+    }
+
+    #[test]
+    fn run_returns_none_on_quit() {
+        let input = Cursor::new("lock\nquit\n");
+
+        let angles = run(input, Vec::new(), DEFAULT_ANGLES, |_| Ok(())).unwrap();
+
+        assert_eq!(angles, None);
+    }
+
+    #[test]
+    fn update_env_replaces_existing_values() {
+        let contents = "API_PATH=https://example.com\nSERVO_LOCK_ANGLE=0\nAPI_TOKEN=secret\n";
+
+        let updated = update_env(
+            contents,
+            ServoAngles {
+                lock: 5,
+                unlock: 170,
+                neutral: 85,
+            },
+        );
+
+        assert_eq!(
+            updated,
+            "API_PATH=https://example.com\nAPI_TOKEN=secret\nSERVO_LOCK_ANGLE=5\nSERVO_UNLOCK_ANGLE=170\nSERVO_NEUTRAL_ANGLE=85\n"
+        );
+    }
+}
@@ -1,10 +1,1023 @@
-use clap::Parser;
+use std::{collections::HashSet, fs, net::SocketAddr, path::PathBuf, str::FromStr};
+
+use anyhow::Context as _;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use room_manager::{
+    app::{GreetingRule, GreetingSchedule, LockPolicy, TimeRange, VolumePolicy},
+    domain::{LockMode, OpenHours, SoundEvent, TouchIntent},
+};
+use serde::Deserialize;
 
 #[derive(Parser, Debug)]
+#[clap(subcommand_negates_reqs = true)]
 pub struct Config {
-    #[clap(long, env, hide_env_values = true)]
-    pub api_path: String,
+    /// Required unless running a subcommand such as `calibrate-servo`.
+    #[clap(long, env, required = true, hide_env_values = true)]
+    pub api_path: Option<String>,
+
+    /// Required unless running a subcommand such as `calibrate-servo`.
+    #[clap(long, env, required = true, hide_env_values = true)]
+    pub api_token: Option<String>,
+
+    /// Role of a reader identified by its USB path, e.g. `1-1.2=entry`.
+    /// Readers without an assignment toggle the entry status.
+    #[clap(long = "reader-role", env = "READER_ROLES", value_delimiter = ',')]
+    pub reader_roles: Vec<ReaderRole>,
+
+    /// Human-readable label of a reader identified by its USB path, e.g.
+    /// `1-1.2=front-door`. Used in logs and sent to the API.
+    #[clap(long = "reader-label", env = "READER_LABELS", value_delimiter = ',')]
+    pub reader_labels: Vec<ReaderLabel>,
+
+    /// Address to serve Prometheus metrics on at `/metrics`, e.g.
+    /// `0.0.0.0:9100`. Not served when omitted.
+    #[clap(long, env)]
+    pub metrics_addr: Option<SocketAddr>,
+
+    /// Seconds between heartbeats sent to the API so it can alert when the
+    /// terminal goes silent. `0`, the default, disables them until the API
+    /// serves `/local-device/heartbeat`.
+    #[clap(long, env, default_value_t = 0)]
+    pub heartbeat_secs: u64,
+
+    #[clap(flatten)]
+    pub admin: AdminConfig,
+
+    #[clap(flatten)]
+    pub tracing: TracingConfig,
+
+    #[clap(flatten)]
+    pub door_lock: DoorLockConfig,
+
+    #[clap(flatten)]
+    pub lock_policy: LockPolicyConfig,
+
+    #[clap(flatten)]
+    pub sound: SoundConfig,
+
+    #[clap(flatten)]
+    pub indicator: IndicatorConfig,
+
+    #[clap(flatten)]
+    pub display: DisplayConfig,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Sweep the servo interactively and write the chosen angles to an env
+    /// file.
+    CalibrateServo {
+        #[clap(long, default_value = ".env")]
+        env_file: PathBuf,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct TracingConfig {
+    /// `EnvFilter` directives choosing what is logged, e.g.
+    /// `info,room_manager=debug`. Can be changed at runtime through the admin
+    /// API.
+    #[clap(long, env = "RUST_LOG", default_value = "info")]
+    pub log_filter: String,
+
+    /// Where console logs go. Use `journald` under systemd to keep the
+    /// fields queryable with `journalctl`.
+    #[clap(long, env, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Also write JSON lines to this file, rotated by size.
+    #[clap(long, env)]
+    pub log_file: Option<PathBuf>,
+
+    /// Rotate the log file once it grows past this many bytes.
+    #[clap(long, env, default_value_t = 10 * 1024 * 1024)]
+    pub log_file_max_bytes: u64,
+
+    /// Rotated log files kept besides the current one, so the logs never
+    /// take more than `(count + 1) * max bytes`.
+    #[clap(long, env, default_value_t = 4)]
+    pub log_file_count: usize,
+
+    /// Base URL of an OTLP/HTTP collector to export spans to, e.g.
+    /// `http://127.0.0.1:4318`. Not exported when omitted.
+    #[clap(long, env)]
+    pub otlp_endpoint: Option<String>,
+}
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines on stdout.
+    Text,
+    /// One JSON object per line on stdout.
+    Json,
+    /// Native journald entries with each field as a journal field.
+    Journald,
+}
+
+#[derive(Args, Debug)]
+pub struct AdminConfig {
+    /// Address to serve `/healthz`, `/readyz`, `/status` and the admin
+    /// actions on, e.g. `127.0.0.1:8080`. Not served when omitted.
+    #[clap(long, env)]
+    pub admin_addr: Option<SocketAddr>,
+
+    /// Bearer token required by the admin actions. They are disabled when
+    /// omitted.
     #[clap(long, env, hide_env_values = true)]
-    pub api_token: String,
+    pub admin_token: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct DoorLockConfig {
+    /// Hardware that moves the bolt.
+    #[clap(long = "door-lock", env = "DOOR_LOCK", value_enum, default_value_t = DoorLockKind::Servo)]
+    pub kind: DoorLockKind,
+
+    /// GPIO pin (BCM) of the servo signal line.
+    #[clap(long, env, default_value_t = 18)]
+    pub servo_pin: u8,
+
+    #[clap(long, env, default_value_t = 0, value_parser = servo_angle_parser())]
+    pub servo_lock_angle: u16,
+
+    #[clap(long, env, default_value_t = 180, value_parser = servo_angle_parser())]
+    pub servo_unlock_angle: u16,
+
+    #[clap(long, env, default_value_t = 90, value_parser = servo_angle_parser())]
+    pub servo_neutral_angle: u16,
+
+    /// Input used to verify that the servo actually moved the bolt.
+    #[clap(long, env, value_enum, default_value_t = LockFeedbackKind::None)]
+    pub lock_feedback: LockFeedbackKind,
+
+    /// GPIO pin (BCM) of a limit switch that reads low while the bolt is
+    /// thrown. Required for `--lock-feedback limit-switch`.
+    #[clap(long, env)]
+    pub lock_feedback_pin: Option<u8>,
+
+    /// GPIO pin (BCM) driving the relay coil. Required for `--door-lock relay`.
+    #[clap(long, env)]
+    pub relay_pin: Option<u8>,
+
+    /// Energize the relay by driving the pin low.
+    #[clap(long, env)]
+    pub relay_active_low: bool,
+
+    /// Energize the relay only for this long on unlock, for strikes that
+    /// relatch by themselves. Without it the relay is held until auto-lock.
+    #[clap(long, env)]
+    pub relay_pulse_ms: Option<u64>,
+
+    /// GPIO pin (BCM) of a reed switch that reads low while the door is
+    /// closed. Auto-lock waits for the door to close, and relay pulses end as
+    /// soon as the door opens.
+    #[clap(long, env)]
+    pub door_sensor_pin: Option<u8>,
+
+    /// Alert when the door stays open for this long.
+    #[clap(long, env, default_value_t = 120)]
+    pub door_left_open_secs: u64,
+
+    /// Report door open/close events to the API. Off by default, because the
+    /// API does not serve `/local-device/door-event` yet.
+    #[clap(long, env)]
+    pub report_door_events: bool,
+
+    /// GPIO pin (BCM) of a push button that reads low while pressed. A short
+    /// press unlocks and a long press locks immediately.
+    #[clap(long, env)]
+    pub button_pin: Option<u8>,
+
+    /// Hold time that turns a button press into a long press.
+    #[clap(long, env, default_value_t = 2000)]
+    pub button_long_press_ms: u64,
+
+    /// Report button lock/unlock events to the API. Off by default, like
+    /// `--report-door-events`.
+    #[clap(long, env)]
+    pub report_button_events: bool,
+
+    /// File that keeps the last known lock state across restarts.
+    #[clap(long, env, default_value = "/var/lib/room-manager/lock-state")]
+    pub lock_state_file: PathBuf,
+
+    /// State the door is brought into on startup. The bolt is only moved if
+    /// the persisted state differs.
+    #[clap(long, env, value_enum, default_value_t = BootLockPolicy::FailSecure)]
+    pub boot_lock_policy: BootLockPolicy,
+
+    /// Refuse to unlock more often than this per minute, so a stuck button
+    /// cannot wear out the hardware. Locking is never refused.
+    #[clap(long, env, default_value_t = 10, value_parser = clap::value_parser!(u16).range(1..))]
+    pub max_lock_cycles_per_minute: u16,
+}
+
+#[derive(Args, Debug)]
+pub struct SoundConfig {
+    /// Directory with a `sound-pack.toml` manifest that replaces the
+    /// embedded sounds. Events it does not cover keep the embedded sound.
+    #[clap(long, env)]
+    pub sound_pack: Option<PathBuf>,
+
+    /// Greet users by the display name from the API, synthesized with
+    /// `open_jtalk`.
+    #[clap(long, env)]
+    pub tts: bool,
+
+    #[clap(long, env, default_value = "open_jtalk")]
+    pub tts_command: PathBuf,
+
+    #[clap(long, env, default_value = "/var/lib/mecab/dic/open-jtalk/naist-jdic")]
+    pub tts_dictionary: PathBuf,
+
+    #[clap(
+        long,
+        env,
+        default_value = "/usr/share/hts-voice/nitech-jp-atr503-m001/nitech_jp_atr503_m001.htsvoice"
+    )]
+    pub tts_voice: PathBuf,
+
+    /// Synthesized greetings are kept here, one file per distinct text.
+    #[clap(long, env, default_value = "/var/cache/room-manager/tts")]
+    pub tts_cache_dir: PathBuf,
+
+    /// TOML file with `[[rule]]` tables choosing the entry greeting, checked
+    /// before the built-in time-of-day greetings.
+    #[clap(long, env)]
+    pub greeting_rules: Option<PathBuf>,
+
+    /// Master volume applied to every sound, from 0 to 4.
+    #[clap(long, env, default_value_t = 1.0, value_parser = parse_gain)]
+    pub volume: f32,
+
+    /// Extra gain for one event on top of the master volume, e.g.
+    /// `touch=0.5`.
+    #[clap(long = "event-gain", env = "EVENT_GAINS", value_delimiter = ',')]
+    pub event_gains: Vec<EventGain>,
+
+    /// Daily time ranges during which sounds are quieter, e.g.
+    /// `22:00-07:00`.
+    #[clap(long = "quiet-hours", env = "QUIET_HOURS", value_delimiter = ',')]
+    pub quiet_hours: Vec<TimeRange>,
+
+    /// Gain applied on top of the other volumes during quiet hours.
+    #[clap(long, env, default_value_t = 0.3, value_parser = parse_gain)]
+    pub quiet_volume: f32,
+
+    /// Play a short chime instead of greetings during quiet hours.
+    #[clap(long, env)]
+    pub quiet_chime: bool,
+}
+
+/// Gain of a single sound event, written as `<event>=<gain>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventGain {
+    pub sound: SoundEvent,
+    pub gain: f32,
+}
+
+impl FromStr for EventGain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sound, gain) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected <event>=<gain>: {s}"))?;
+
+        Ok(Self {
+            sound: sound.trim().parse()?,
+            gain: parse_gain(gain.trim())?,
+        })
+    }
+}
+
+fn parse_gain(s: &str) -> Result<f32, String> {
+    const MAX_GAIN: f32 = 4.0;
+
+    let gain: f32 = s.parse().map_err(|_| format!("invalid gain: {s}"))?;
+    if !(0.0..=MAX_GAIN).contains(&gain) {
+        return Err(format!("gain must be between 0 and {MAX_GAIN}: {s}"));
+    }
+
+    Ok(gain)
+}
+
+impl SoundConfig {
+    /// Builds the greeting schedule from `--greeting-rules`.
+    pub fn load_greetings(&self) -> anyhow::Result<GreetingSchedule> {
+        let Some(path) = &self.greeting_rules else {
+            return Ok(GreetingSchedule::default());
+        };
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let rules = parse_greeting_rules(&content)
+            .with_context(|| format!("invalid greeting rules {}", path.display()))?;
+
+        Ok(GreetingSchedule::new(rules))
+    }
+
+    pub fn volume_policy(&self) -> VolumePolicy {
+        let event_gains = self
+            .event_gains
+            .iter()
+            .map(|event_gain| (event_gain.sound, event_gain.gain))
+            .collect();
+
+        VolumePolicy::new(self.volume, event_gains).with_quiet_hours(
+            self.quiet_hours.clone(),
+            self.quiet_volume,
+            self.quiet_chime,
+        )
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GreetingRulesFile {
+    #[serde(default)]
+    rule: Vec<GreetingRule>,
+}
+
+fn parse_greeting_rules(content: &str) -> anyhow::Result<Vec<GreetingRule>> {
+    Ok(toml::from_str::<GreetingRulesFile>(content)?.rule)
+}
+
+#[derive(Args, Debug)]
+pub struct IndicatorConfig {
+    /// GPIO pins (BCM) of an RGB status LED as `<red>,<green>,<blue>`.
+    #[clap(long, env)]
+    pub led_pins: Option<LedPins>,
+
+    /// The LED is common-anode, so a color is lit by driving its pin low.
+    #[clap(long, env)]
+    pub led_active_low: bool,
+
+    /// GPIO pin (BCM) of a passive piezo buzzer.
+    #[clap(long, env)]
+    pub buzzer_pin: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedPins {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl FromStr for LedPins {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pins = s
+            .split(',')
+            .map(|pin| {
+                pin.trim()
+                    .parse()
+                    .map_err(|_| format!("invalid gpio pin: {pin}"))
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+        let [red, green, blue] = pins[..] else {
+            return Err(format!("expected <red>,<green>,<blue>: {s}"));
+        };
+
+        Ok(Self { red, green, blue })
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct DisplayConfig {
+    /// Screen showing occupancy and touch results.
+    #[clap(
+        id = "status_display",
+        long = "status-display",
+        env = "STATUS_DISPLAY",
+        value_enum,
+        default_value_t = DisplayKind::None
+    )]
+    pub kind: DisplayKind,
+
+    /// I2C bus (`/dev/i2c-<bus>`) of the display.
+    #[clap(long, env, default_value_t = 1)]
+    pub display_i2c_bus: u8,
+
+    /// I2C address of the display, e.g. `0x3c`. Defaults to `0x3c` for
+    /// SSD1306 and `0x27` for HD44780 backpacks.
+    #[clap(long, env, value_parser = parse_i2c_address)]
+    pub display_i2c_address: Option<u16>,
+
+    /// Size of the character LCD, or of the terminal display, as
+    /// `<columns>x<rows>`.
+    #[clap(long, env, default_value = "16x2")]
+    pub lcd_size: LcdSize,
+
+    /// Keep touch results on screen for this long before showing occupancy
+    /// again.
+    #[clap(long, env, default_value_t = 5)]
+    pub display_hold_secs: u64,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayKind {
+    None,
+    /// Print screens to stderr, for development.
+    Terminal,
+    /// 128x64 I2C OLED.
+    Ssd1306,
+    /// Character LCD with a PCF8574 I2C backpack.
+    Hd44780,
+}
+
+fn parse_i2c_address(s: &str) -> Result<u16, String> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|_| format!("invalid i2c address: {s}"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcdSize {
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl FromStr for LcdSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |n: &str| match n.trim().parse() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!("invalid lcd size: {s}")),
+        };
+        let (columns, rows) = s
+            .split_once('x')
+            .ok_or_else(|| format!("expected <columns>x<rows>: {s}"))?;
+
+        Ok(Self {
+            columns: parse(columns)?,
+            rows: parse(rows)?,
+        })
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct LockPolicyConfig {
+    /// `normal`, `touch_only` (ignore open hours) or `lockdown` (never
+    /// unlock on touch), spelled as in the API's `lock_mode`. The API
+    /// response can override it per touch.
+    #[clap(long, env, default_value = "normal")]
+    pub lock_mode: LockMode,
+
+    /// Weekly hours during which the door is kept unlocked, e.g.
+    /// `mon-fri@16:00-19:00`.
+    #[clap(long = "open-hours", env = "OPEN_HOURS", value_delimiter = ',')]
+    pub open_hours: Vec<OpenHours>,
+
+    /// Dates (`YYYY-MM-DD`) on which the open hours do not apply.
+    #[clap(long, env, value_delimiter = ',')]
+    pub holidays: Vec<NaiveDate>,
+
+    /// File with one holiday date per line, in addition to `--holidays`.
+    /// Blank lines and lines starting with `#` are ignored.
+    #[clap(long, env)]
+    pub holiday_file: Option<PathBuf>,
+}
+
+impl LockPolicyConfig {
+    /// Builds the lock policy, reading `--holiday-file`.
+    pub fn policy(&self) -> anyhow::Result<LockPolicy> {
+        Ok(LockPolicy::new(
+            self.lock_mode,
+            self.open_hours.clone(),
+            self.load_holidays()?,
+        ))
+    }
+
+    /// Combines `--holidays` with the dates in `--holiday-file`.
+    pub fn load_holidays(&self) -> anyhow::Result<HashSet<NaiveDate>> {
+        let mut holidays: HashSet<NaiveDate> = self.holidays.iter().copied().collect();
+
+        if let Some(path) = &self.holiday_file {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            holidays.extend(parse_holidays(&content)?);
+        }
+
+        Ok(holidays)
+    }
+}
+
+fn parse_holidays(content: &str) -> anyhow::Result<Vec<NaiveDate>> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            // `2026-01-01 元日` のように日付の後ろに名前を書いてもよい
+            let date = line.split_whitespace().next().unwrap_or(line);
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .with_context(|| format!("invalid holiday: {line}"))
+        })
+        .collect()
+}
+
+impl DoorLockConfig {
+    pub fn servo_angles(&self) -> ServoAngles {
+        ServoAngles {
+            lock: self.servo_lock_angle,
+            unlock: self.servo_unlock_angle,
+            neutral: self.servo_neutral_angle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServoAngles {
+    pub lock: u16,
+    pub unlock: u16,
+    pub neutral: u16,
+}
+
+impl ServoAngles {
+    pub const MAX: u16 = 180;
+}
+
+fn servo_angle_parser() -> clap::builder::RangedI64ValueParser<u16> {
+    clap::value_parser!(u16).range(0..=i64::from(ServoAngles::MAX))
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockFeedbackKind {
+    /// Trust the servo without verification.
+    None,
+    /// Limit switch on the bolt.
+    LimitSwitch,
+    /// Door sensor; only confirms that the door was closed when locking.
+    DoorSensor,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootLockPolicy {
+    /// Lock on startup.
+    FailSecure,
+    /// Unlock on startup, e.g. where the door must stay passable for egress.
+    FailSafe,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoorLockKind {
+    /// Hobby servo pushing the thumb turn.
+    Servo,
+    /// Relay-driven electric strike or solenoid bolt.
+    Relay,
+}
+
+/// Touch intent assigned to the reader at a USB bus/port path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReaderRole {
+    /// `<bus>-<port>[.<port>...]`, the same notation as `/sys/bus/usb/devices`.
+    pub usb_path: String,
+    pub intent: TouchIntent,
+}
+
+/// User-assigned label of the reader at a USB bus/port path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReaderLabel {
+    pub usb_path: String,
+    pub label: String,
+}
+
+impl FromStr for ReaderRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (usb_path, intent) = parse_usb_path_assignment(s, "role")?;
+
+        let intent = match intent {
+            "entry" => TouchIntent::Entry,
+            "exit" => TouchIntent::Exit,
+            "toggle" => TouchIntent::Toggle,
+            other => return Err(format!("unknown reader role: {other}")),
+        };
+
+        Ok(Self { usb_path, intent })
+    }
+}
+
+impl FromStr for ReaderLabel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (usb_path, label) = parse_usb_path_assignment(s, "label")?;
+        if label.is_empty() {
+            return Err(format!("empty reader label: {s}"));
+        }
+
+        Ok(Self {
+            usb_path,
+            label: label.to_string(),
+        })
+    }
+}
+
+fn parse_usb_path_assignment<'a>(s: &'a str, value: &str) -> Result<(String, &'a str), String> {
+    let (usb_path, rest) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected <usb-path>=<{value}>: {s}"))?;
+
+    let usb_path = usb_path.trim();
+    let valid_path = usb_path.split_once('-').is_some_and(|(bus, ports)| {
+        bus.parse::<u8>().is_ok() && ports.split('.').all(|port| port.parse::<u8>().is_ok())
+    });
+    if !valid_path {
+        return Err(format!("invalid usb path: {usb_path}"));
+    }
+
+    Ok((usb_path.to_string(), rest.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveTime, Weekday};
+    use room_manager::domain::{LockMode, SoundEvent, TouchIntent};
+
+    use clap::Parser as _;
+
+    use super::{
+        BootLockPolicy, Command, Config, DisplayKind, DoorLockKind, EventGain, LcdSize, LedPins,
+        ReaderLabel, ReaderRole, ServoAngles, parse_greeting_rules, parse_holidays,
+    };
+
+    #[test]
+    fn reader_role_parses_path_and_intent() {
+        let role: ReaderRole = "1-1.2=exit".parse().unwrap();
+
+        assert_eq!(role.usb_path, "1-1.2");
+        assert_eq!(role.intent, TouchIntent::Exit);
+    }
+
+    #[test]
+    fn reader_role_rejects_invalid_path() {
+        "usb1=entry".parse::<ReaderRole>().unwrap_err();
+        "1-a=entry".parse::<ReaderRole>().unwrap_err();
+    }
+
+    #[test]
+    fn reader_role_rejects_unknown_role() {
+        let err = "1-1=inside".parse::<ReaderRole>().unwrap_err();
+
+        assert!(err.contains("unknown reader role"));
+    }
+
+    #[test]
+    fn reader_label_parses_path_and_label() {
+        let label: ReaderLabel = "1-1.2=front-door".parse().unwrap();
+
+        assert_eq!(label.usb_path, "1-1.2");
+        assert_eq!(label.label, "front-door");
+    }
+
+    #[test]
+    fn reader_label_rejects_empty_label() {
+        "1-1.2=".parse::<ReaderLabel>().unwrap_err();
+    }
+
+    #[test]
+    fn door_lock_defaults_to_servo_on_gpio18() {
+        let config =
+            Config::try_parse_from(["room-manager", "--api-path", "x", "--api-token", "t"])
+                .unwrap();
+
+        assert_eq!(config.door_lock.kind, DoorLockKind::Servo);
+        assert_eq!(config.door_lock.servo_pin, 18);
+        assert_eq!(
+            config.door_lock.servo_angles(),
+            ServoAngles {
+                lock: 0,
+                unlock: 180,
+                neutral: 90,
+            }
+        );
+        assert!(config.command.is_none());
+    }
+
+    #[test]
+    fn door_lock_boots_fail_secure_by_default() {
+        let config =
+            Config::try_parse_from(["room-manager", "--api-path", "x", "--api-token", "t"])
+                .unwrap();
+
+        assert_eq!(
+            config.door_lock.boot_lock_policy,
+            BootLockPolicy::FailSecure
+        );
+        assert_eq!(config.door_lock.max_lock_cycles_per_minute, 10);
+    }
+
+    #[test]
+    fn door_lock_parses_fail_safe_policy() {
+        let config = Config::try_parse_from([
+            "room-manager",
+            "--api-path",
+            "x",
+            "--api-token",
+            "t",
+            "--boot-lock-policy",
+            "fail-safe",
+        ])
+        .unwrap();
+
+        assert_eq!(config.door_lock.boot_lock_policy, BootLockPolicy::FailSafe);
+    }
+
+    #[test]
+    fn servo_angle_rejects_out_of_range() {
+        Config::try_parse_from([
+            "room-manager",
+            "--api-path",
+            "x",
+            "--api-token",
+            "t",
+            "--servo-lock-angle",
+            "181",
+        ])
+        .unwrap_err();
+    }
+
+    #[test]
+    fn calibrate_servo_subcommand_parses_env_file() {
+        // API の設定なしで起動できる
+        let config = Config::try_parse_from([
+            "room-manager",
+            "calibrate-servo",
+            "--env-file",
+            "/etc/room-manager.env",
+        ])
+        .unwrap();
+
+        assert!(matches!(
+            config.command,
+            Some(Command::CalibrateServo { env_file }) if env_file.to_str() == Some("/etc/room-manager.env")
+        ));
+        assert_eq!(config.api_path, None);
+    }
+
+    #[test]
+    fn api_settings_are_required_without_subcommand() {
+        Config::try_parse_from(["room-manager", "--api-token", "t"]).unwrap_err();
+        Config::try_parse_from(["room-manager", "--api-path", "x"]).unwrap_err();
+    }
+
+    #[test]
+    fn door_lock_parses_relay_options() {
+        let config = Config::try_parse_from([
+            "room-manager",
+            "--api-path",
+            "x",
+            "--api-token",
+            "t",
+            "--door-lock",
+            "relay",
+            "--relay-pin",
+            "17",
+            "--relay-active-low",
+            "--relay-pulse-ms",
+            "500",
+        ])
+        .unwrap();
+
+        assert_eq!(config.door_lock.kind, DoorLockKind::Relay);
+        assert_eq!(config.door_lock.relay_pin, Some(17));
+        assert!(config.door_lock.relay_active_low);
+        assert_eq!(config.door_lock.relay_pulse_ms, Some(500));
+    }
+
+    #[test]
+    fn lock_policy_parses_mode_hours_and_holidays() {
+        let config = Config::try_parse_from([
+            "room-manager",
+            "--api-path",
+            "x",
+            "--api-token",
+            "t",
+            "--lock-mode",
+            "lockdown",
+            "--open-hours",
+            "mon-fri@16:00-19:00,sat@13:00-17:00",
+            "--holidays",
+            "2026-12-29,2026-12-30",
+        ])
+        .unwrap();
+
+        assert_eq!(config.lock_policy.lock_mode, LockMode::Lockdown);
+        assert_eq!(config.lock_policy.open_hours.len(), 2);
+        assert_eq!(config.lock_policy.open_hours[0].weekdays.len(), 5);
+        assert_eq!(config.lock_policy.load_holidays().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn lock_policy_rejects_inverted_open_hours() {
+        Config::try_parse_from([
+            "room-manager",
+            "--api-path",
+            "x",
+            "--api-token",
+            "t",
+            "--open-hours",
+            "mon@19:00-16:00",
+        ])
+        .unwrap_err();
+    }
+
+    #[test]
+    fn holiday_file_skips_comments_and_names() {
+        let holidays = parse_holidays("# 年末年始\n2026-12-29\n\n2027-01-01 元日\n").unwrap();
+
+        assert_eq!(
+            holidays,
+            vec![
+                NaiveDate::from_ymd_opt(2026, 12, 29).unwrap(),
+                NaiveDate::from_ymd_opt(2027, 1, 1).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn greeting_rules_parse_in_order() {
+        let rules = parse_greeting_rules(
+            r#"
+            [[rule]]
+            sound = "new_year"
+            text = "あけましておめでとうございます"
+            dates = ["12-31..01-03"]
+
+            [[rule]]
+            sound = "exam_week"
+            dates = ["2026-07-27..2026-08-07"]
+            weekdays = ["mon", "tue", "wed", "thu", "fri"]
+            time = "09:00-18:00"
+
+            [[rule]]
+            sound = "welcome_back"
+            away_days = 30
+            "#,
+        )
+        .unwrap();
+
+        let sounds: Vec<_> = rules.iter().map(|rule| rule.sound).collect();
+        assert_eq!(
+            sounds,
+            vec![
+                SoundEvent::NewYear,
+                SoundEvent::ExamWeek,
+                SoundEvent::WelcomeBack
+            ]
+        );
+        assert_eq!(rules[1].weekdays.len(), 5);
+        assert_eq!(rules[1].weekdays[0], Weekday::Mon);
+        assert_eq!(
+            rules[1].time.unwrap().start,
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap()
+        );
+        assert_eq!(rules[2].away_days, Some(30));
+    }
+
+    #[test]
+    fn greeting_rules_reject_typos() {
+        parse_greeting_rules("[[rule]]\nsound = \"hello\"\nweekday = [\"mon\"]\n").unwrap_err();
+        parse_greeting_rules("[[rule]]\nsound = \"good_night\"\n").unwrap_err();
+        parse_greeting_rules("[[rule]]\nsound = \"hello\"\ntime = \"9-18\"\n").unwrap_err();
+    }
+
+    #[test]
+    fn sound_volume_options_parse() {
+        let config = Config::try_parse_from([
+            "room-manager",
+            "--api-path",
+            "x",
+            "--api-token",
+            "t",
+            "--volume",
+            "0.8",
+            "--event-gain",
+            "touch=0.5,door_left_open=2",
+            "--quiet-hours",
+            "22:00-07:00",
+            "--quiet-chime",
+        ])
+        .unwrap();
+
+        assert!((config.sound.volume - 0.8).abs() < f32::EPSILON);
+        assert_eq!(
+            config.sound.event_gains,
+            vec![
+                EventGain {
+                    sound: SoundEvent::Touch,
+                    gain: 0.5
+                },
+                EventGain {
+                    sound: SoundEvent::DoorLeftOpen,
+                    gain: 2.0
+                },
+            ]
+        );
+        assert_eq!(config.sound.quiet_hours.len(), 1);
+        assert!((config.sound.quiet_volume - 0.3).abs() < f32::EPSILON);
+        assert!(config.sound.quiet_chime);
+    }
+
+    #[test]
+    fn sound_volume_rejects_invalid_gains() {
+        "touch".parse::<EventGain>().unwrap_err();
+        "good_night=1".parse::<EventGain>().unwrap_err();
+        "touch=5".parse::<EventGain>().unwrap_err();
+        "touch=-0.1".parse::<EventGain>().unwrap_err();
+    }
+
+    #[test]
+    fn indicator_parses_led_and_buzzer_pins() {
+        let config = Config::try_parse_from([
+            "room-manager",
+            "--api-path",
+            "x",
+            "--api-token",
+            "t",
+            "--led-pins",
+            "5,6,13",
+            "--buzzer-pin",
+            "12",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            config.indicator.led_pins,
+            Some(LedPins {
+                red: 5,
+                green: 6,
+                blue: 13
+            })
+        );
+        assert_eq!(config.indicator.buzzer_pin, Some(12));
+        assert!(!config.indicator.led_active_low);
+    }
+
+    #[test]
+    fn indicator_requires_three_led_pins() {
+        Config::try_parse_from([
+            "room-manager",
+            "--api-path",
+            "x",
+            "--api-token",
+            "t",
+            "--led-pins",
+            "5,6",
+        ])
+        .unwrap_err();
+    }
+
+    #[test]
+    fn display_parses_kind_address_and_size() {
+        let config = Config::try_parse_from([
+            "room-manager",
+            "--api-path",
+            "x",
+            "--api-token",
+            "t",
+            "--status-display",
+            "hd44780",
+            "--display-i2c-address",
+            "0x3f",
+            "--lcd-size",
+            "20x4",
+        ])
+        .unwrap();
+
+        assert_eq!(config.display.kind, DisplayKind::Hd44780);
+        assert_eq!(config.display.display_i2c_address, Some(0x3f));
+        assert_eq!(
+            config.display.lcd_size,
+            LcdSize {
+                columns: 20,
+                rows: 4
+            }
+        );
+        assert_eq!(config.display.display_i2c_bus, 1);
+    }
+
+    #[test]
+    fn display_defaults_to_none() {
+        let config =
+            Config::try_parse_from(["room-manager", "--api-path", "x", "--api-token", "t"])
+                .unwrap();
+
+        assert_eq!(config.display.kind, DisplayKind::None);
+        assert_eq!(config.display.display_i2c_address, None);
+        assert!("16x0".parse::<LcdSize>().is_err());
+        assert!("16".parse::<LcdSize>().is_err());
+    }
 }
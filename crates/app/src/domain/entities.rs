@@ -1,3 +1,6 @@
+use std::{fmt, str::FromStr, time::Instant};
+
+use chrono::{DateTime, Datelike as _, FixedOffset, NaiveDateTime, NaiveTime, Weekday};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -5,22 +8,107 @@ pub struct Card {
     pub idm: String,
     pub student_id: Option<u32>,
     pub balance: Option<u32>,
+    pub intent: TouchIntent,
+    pub reader: ReaderId,
+    /// When the reader detected the card, before reading its data.
+    pub detected_at: Instant,
+}
+
+impl Card {
+    /// What the reader could tell about the card, judged by the data it read.
+    #[must_use]
+    pub fn kind(&self) -> CardKind {
+        match (self.student_id, self.balance) {
+            (Some(_), _) => CardKind::StudentCard,
+            (None, Some(_)) => CardKind::Transit,
+            (None, None) => CardKind::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CardKind {
+    StudentCard,
+    /// Suica and other transit cards with a readable balance.
+    Transit,
+    /// Any other NFC card, identified only by its `IDm`.
+    Other,
+}
+
+impl CardKind {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::StudentCard => "student_card",
+            Self::Transit => "transit",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Stable identity of a card reader.
+///
+/// `usb_path` is derived from the bus number and port chain, so it stays the
+/// same across reboots as long as the reader is plugged into the same port.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReaderId {
+    pub usb_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl fmt::Display for ReaderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{label}@{}", self.usb_path),
+            None => f.write_str(&self.usb_path),
+        }
+    }
+}
+
+/// Direction requested by the reader that scanned the card.
+///
+/// Entry-only and exit-only readers let the terminal decide the direction
+/// instead of relying on the server toggling the current state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TouchIntent {
+    Entry,
+    Exit,
+    #[default]
+    Toggle,
+}
+
+impl TouchIntent {
+    /// Returns whether the status decided by the API is consistent with this
+    /// intent.
+    #[must_use]
+    pub fn accepts(self, status: RoomEntryStatus) -> bool {
+        match self {
+            Self::Entry => status == RoomEntryStatus::Entry,
+            Self::Exit => status == RoomEntryStatus::Exit,
+            Self::Toggle => true,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RoomEntryStatus {
     Entry,
     Exit,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ErrorCode {
     StudentCardAlreadyRegistered,
     NfcCardAlreadyRegistered,
     StudentCardNotRegistered,
     NfcCardNotRegistered,
+    AlreadyEntered,
+    NotEntered,
+    #[serde(other)]
     Unknown,
 }
 
@@ -29,6 +117,8 @@ pub struct TouchCardRequest {
     pub idm: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub student_id: Option<u32>,
+    pub intent: TouchIntent,
+    pub reader: ReaderId,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,13 +127,44 @@ pub enum TouchCardResponse {
     Success {
         status: RoomEntryStatus,
         entries: u32,
+        /// Whether this user may open the door. Servers that predate the
+        /// field always grant unlock.
+        #[serde(default)]
+        access: AccessLevel,
+        /// Name to greet the user with, if the server provides one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        display_name: Option<String>,
+        /// This user is the first to enter the room today.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        first_entry_today: bool,
+        /// When this user last entered the room before this touch.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_entry_at: Option<DateTime<FixedOffset>>,
+        /// Overrides the configured lock mode for this touch.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lock_mode: Option<LockMode>,
     },
     Error {
         error: String,
         error_code: ErrorCode,
+        /// Code to register an unknown NFC card with via Discord.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        registration_code: Option<String>,
     },
 }
 
+/// What a successful touch grants besides recording presence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessLevel {
+    #[default]
+    Unlock,
+    /// Presence is recorded but the door stays locked, e.g. alumni after
+    /// hours. Unknown levels from newer servers fall back to this.
+    #[serde(other)]
+    RecordOnly,
+}
+
 #[cfg(test)]
 impl TouchCardResponse {
     #[must_use]
@@ -51,6 +172,11 @@ impl TouchCardResponse {
         Self::Success {
             status: RoomEntryStatus::Entry,
             entries,
+            access: AccessLevel::Unlock,
+            display_name: None,
+            first_entry_today: false,
+            last_entry_at: None,
+            lock_mode: None,
         }
     }
 
@@ -59,6 +185,11 @@ impl TouchCardResponse {
         Self::Success {
             status: RoomEntryStatus::Exit,
             entries,
+            access: AccessLevel::Unlock,
+            display_name: None,
+            first_entry_today: false,
+            last_entry_at: None,
+            lock_mode: None,
         }
     }
 
@@ -66,6 +197,7 @@ impl TouchCardResponse {
         Self::Error {
             error: message.into(),
             error_code: code,
+            registration_code: None,
         }
     }
 }
@@ -75,11 +207,14 @@ impl From<Card> for TouchCardRequest {
         Self {
             idm: card.idm,
             student_id: card.student_id,
+            intent: card.intent,
+            reader: card.reader,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SoundEvent {
     Boot,
     Touch,
@@ -91,4 +226,387 @@ pub enum SoundEvent {
     Error,
     RegisterStudentCard,
     RegisterNfcCard,
+    AlreadyEntered,
+    NotEntered,
+    DoorLeftOpen,
+    Lockdown,
+    RecordOnly,
+    FirstEntry,
+    WelcomeBack,
+    NewYear,
+    Anniversary,
+    ExamWeek,
+    Chime,
+}
+
+impl SoundEvent {
+    pub const ALL: [Self; 21] = [
+        Self::Boot,
+        Self::Touch,
+        Self::GoodMorning,
+        Self::Hello,
+        Self::GoodEvening,
+        Self::GoodBye,
+        Self::Last,
+        Self::Error,
+        Self::RegisterStudentCard,
+        Self::RegisterNfcCard,
+        Self::AlreadyEntered,
+        Self::NotEntered,
+        Self::DoorLeftOpen,
+        Self::Lockdown,
+        Self::RecordOnly,
+        Self::FirstEntry,
+        Self::WelcomeBack,
+        Self::NewYear,
+        Self::Anniversary,
+        Self::ExamWeek,
+        Self::Chime,
+    ];
+
+    /// Name used for the event in sound pack manifests and metrics labels.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Boot => "boot",
+            Self::Touch => "touch",
+            Self::GoodMorning => "good_morning",
+            Self::Hello => "hello",
+            Self::GoodEvening => "good_evening",
+            Self::GoodBye => "good_bye",
+            Self::Last => "last",
+            Self::Error => "error",
+            Self::RegisterStudentCard => "register_student_card",
+            Self::RegisterNfcCard => "register_nfc_card",
+            Self::AlreadyEntered => "already_entered",
+            Self::NotEntered => "not_entered",
+            Self::DoorLeftOpen => "door_left_open",
+            Self::Lockdown => "lockdown",
+            Self::RecordOnly => "record_only",
+            Self::FirstEntry => "first_entry",
+            Self::WelcomeBack => "welcome_back",
+            Self::NewYear => "new_year",
+            Self::Anniversary => "anniversary",
+            Self::ExamWeek => "exam_week",
+            Self::Chime => "chime",
+        }
+    }
+
+    /// Greets someone entering or leaving, as opposed to feedback and
+    /// guidance that must always be heard.
+    #[must_use]
+    pub fn is_greeting(self) -> bool {
+        matches!(
+            self,
+            Self::GoodMorning
+                | Self::Hello
+                | Self::GoodEvening
+                | Self::GoodBye
+                | Self::FirstEntry
+                | Self::WelcomeBack
+                | Self::NewYear
+                | Self::Anniversary
+                | Self::ExamWeek
+        )
+    }
+
+    /// Priority used to order queued sounds and to decide what may be
+    /// interrupted.
+    #[must_use]
+    pub fn priority(self) -> SoundPriority {
+        match self {
+            Self::Boot => SoundPriority::Urgent,
+            Self::Touch | Self::Lockdown => SoundPriority::High,
+            Self::DoorLeftOpen => SoundPriority::Low,
+            _ => SoundPriority::Normal,
+        }
+    }
+
+    /// What happens when this sound is requested while another one is
+    /// playing.
+    #[must_use]
+    pub fn policy(self) -> SoundPolicy {
+        match self {
+            Self::Boot => SoundPolicy::Interrupt,
+            Self::Touch => SoundPolicy::Coalesce,
+            Self::DoorLeftOpen => SoundPolicy::DropIfBusy,
+            _ => SoundPolicy::Queue,
+        }
+    }
+}
+
+impl FromStr for SoundEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::deserialize(serde::de::value::StrDeserializer::<serde::de::value::Error>::new(s))
+            .map_err(|_| format!("unknown sound event: {s}"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SoundPriority {
+    Low,
+    Normal,
+    High,
+    Urgent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundPolicy {
+    /// Stops the current sound unless it has a higher priority, and discards
+    /// queued sounds with a lower priority.
+    Interrupt,
+    /// Waits for the sounds ahead of it, jumping those with a lower priority.
+    Queue,
+    /// Skipped if anything is playing or queued.
+    DropIfBusy,
+    /// Skipped if the same sound is already playing or queued; queued
+    /// otherwise.
+    Coalesce,
+}
+
+/// One item handled by the sound scheduler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SoundRequest {
+    Event(SoundEvent),
+    /// Synthesized speech, which borrows the priority and policy of
+    /// `fallback` and is replaced by it when speech is unavailable.
+    Speech {
+        text: String,
+        fallback: SoundEvent,
+    },
+}
+
+impl SoundRequest {
+    #[must_use]
+    pub fn event(&self) -> SoundEvent {
+        match self {
+            Self::Event(event)
+            | Self::Speech {
+                fallback: event, ..
+            } => *event,
+        }
+    }
+}
+
+/// Feedback shown by status LEDs and buzzers alongside sounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndicatorPattern {
+    /// A card was read and the API is being asked.
+    Reading,
+    SuccessEntry,
+    SuccessExit,
+    /// Presence was recorded, but the card does not open the door.
+    RecordOnly,
+    /// The touch was rejected or needs attention, e.g. an unregistered card.
+    Error,
+    /// The API could not be reached.
+    Offline,
+}
+
+/// What the status display shows after a touch, until it returns to the
+/// occupancy screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayScreen {
+    /// A card was read and the API is being asked.
+    Reading,
+    Entered {
+        display_name: Option<String>,
+        entries: u32,
+    },
+    Exited {
+        display_name: Option<String>,
+        entries: u32,
+    },
+    /// The touch was recorded but the door stays locked for lockdown.
+    Lockdown { entries: u32 },
+    /// The touch was recorded but the card does not open the door.
+    RecordOnly { entries: u32 },
+    /// The touch was recorded but the door could not be unlocked.
+    LockFailed { entries: u32 },
+    /// The API or the reader intent rejected the touch.
+    Rejected(ErrorCode),
+    /// An unknown NFC card, with the code to register it with.
+    RegisterNfcCard { code: String },
+    /// The API could not be reached.
+    Offline,
+}
+
+/// How a touch ended, as counted by [`Metrics`](super::Metrics).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TouchOutcome {
+    Entered,
+    Exited,
+    /// The API or the reader intent rejected the touch.
+    Rejected,
+    /// The API could not be reached.
+    Offline,
+    /// The API answered with something other than a touch result.
+    ApiError,
+    /// The touch was accepted but the door could not be unlocked.
+    LockFailed,
+}
+
+impl TouchOutcome {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Entered => "entered",
+            Self::Exited => "exited",
+            Self::Rejected => "rejected",
+            Self::Offline => "offline",
+            Self::ApiError => "api_error",
+            Self::LockFailed => "lock_failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DoorState {
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DoorEvent {
+    Opened,
+    Closed,
+    LeftOpen,
+    ManualUnlock,
+    ManualLock,
+}
+
+/// How the door reacts to successful touches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockMode {
+    /// Unlock on touch, and keep the door unlocked during open hours.
+    #[default]
+    Normal,
+    /// Unlock on touch only, ignoring open hours.
+    TouchOnly,
+    /// Never unlock on touch, even for valid cards.
+    Lockdown,
+}
+
+impl FromStr for LockMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "touch_only" => Ok(Self::TouchOnly),
+            "lockdown" => Ok(Self::Lockdown),
+            other => Err(format!("unknown lock mode: {other}")),
+        }
+    }
+}
+
+/// Weekly time range during which the door is kept unlocked, written as
+/// `<day>[-<day>]@<HH:MM>-<HH:MM>`, e.g. `mon-fri@16:00-19:00`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenHours {
+    pub weekdays: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl OpenHours {
+    #[must_use]
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        self.weekdays.contains(&at.weekday()) && self.start <= at.time() && at.time() < self.end
+    }
+}
+
+impl FromStr for OpenHours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (days, times) = s
+            .split_once('@')
+            .ok_or_else(|| format!("expected <days>@<start>-<end>: {s}"))?;
+
+        let parse_day = |day: &str| {
+            Weekday::from_str(day.trim()).map_err(|_| format!("invalid weekday: {day}"))
+        };
+        let weekdays = match days.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (parse_day(first)?, parse_day(last)?);
+                let mut weekdays = vec![first];
+                let mut day = first;
+                while day != last {
+                    day = day.succ();
+                    weekdays.push(day);
+                }
+                weekdays
+            }
+            None => vec![parse_day(days)?],
+        };
+
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .map_err(|_| format!("invalid time: {time}"))
+        };
+        let (start, end) = times
+            .split_once('-')
+            .ok_or_else(|| format!("expected <start>-<end>: {times}"))?;
+        let (start, end) = (parse_time(start)?, parse_time(end)?);
+        if start >= end {
+            return Err(format!("open hours must end after they start: {times}"));
+        }
+
+        Ok(Self {
+            weekdays,
+            start,
+            end,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockState {
+    Locked,
+    Unlocked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonPress {
+    Short,
+    Long,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoorEventRequest {
+    pub event: DoorEvent,
+    /// How long the door has been open, for `closed` and `left_open` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_secs: Option<u64>,
+}
+
+/// Periodic sign of life, so the server can tell a dead terminal from a quiet
+/// room.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeartbeatRequest {
+    pub version: String,
+    pub uptime_secs: u64,
+    pub readers: usize,
+    /// `None` if the lock backend cannot tell.
+    pub lock_state: Option<LockState>,
+    /// Sounds waiting behind the one being played.
+    pub queued_sounds: usize,
+    /// The most recent failure the terminal ran into, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeartbeatResponse {
+    /// Interval the server asks for until the next heartbeat. The terminal's
+    /// own interval is used when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_heartbeat_secs: Option<u64>,
 }
@@ -0,0 +1,53 @@
+//! Errors of the domain traits, classified so that use cases can decide
+//! whether to carry on. Underlying causes are kept in the message.
+
+/// Failure talking to the upstream API.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    /// No response arrived within the client timeout.
+    #[error("api request timed out")]
+    Timeout,
+    /// The API could not be reached at all.
+    #[error("could not connect to the api: {0:#}")]
+    Connection(anyhow::Error),
+    /// The API answered with a non-success HTTP status.
+    #[error("api returned status {0}")]
+    Status(u16),
+    /// The response did not match the contract.
+    #[error("could not parse the api response: {0:#}")]
+    InvalidResponse(anyhow::Error),
+}
+
+impl ApiError {
+    /// Whether the API is unreachable or down, as opposed to misbehaving.
+    #[must_use]
+    pub fn is_offline(&self) -> bool {
+        match self {
+            Self::Timeout | Self::Connection(_) => true,
+            Self::Status(status) => *status >= 500,
+            Self::InvalidResponse(_) => false,
+        }
+    }
+}
+
+/// Failure producing a sound.
+#[derive(Debug, thiserror::Error)]
+pub enum SoundError {
+    /// The sound data could not be decoded.
+    #[error("failed to decode sound: {0:#}")]
+    Decode(anyhow::Error),
+}
+
+/// Failure moving the door lock.
+#[derive(Debug, thiserror::Error)]
+pub enum LockError {
+    /// The door was unlocked too often in the last minute.
+    #[error("refusing to cycle the lock more than {max_cycles} times per minute")]
+    RateLimited { max_cycles: usize },
+    /// The hardware could not be driven or the bolt did not move.
+    #[error("lock actuator failed: {0:#}")]
+    Actuator(anyhow::Error),
+    /// The task driving the lock is no longer running.
+    #[error("door lock has stopped")]
+    Stopped,
+}
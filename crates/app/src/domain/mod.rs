@@ -1,8 +1,10 @@
 #![allow(async_fn_in_trait)]
 
 pub mod entities;
+pub mod error;
 
 pub use entities::*;
+pub use error::*;
 
 pub trait CardApi {
     /// Sends a card touch event to the upstream API.
@@ -11,7 +13,44 @@ pub trait CardApi {
     ///
     /// Returns an error if the request cannot be completed or the response
     /// cannot be interpreted.
-    async fn touch(&self, req: TouchCardRequest) -> anyhow::Result<TouchCardResponse>;
+    async fn touch(&self, req: TouchCardRequest) -> Result<TouchCardResponse, ApiError>;
+}
+
+impl<T: CardApi> CardApi for &T {
+    async fn touch(&self, req: TouchCardRequest) -> Result<TouchCardResponse, ApiError> {
+        (**self).touch(req).await
+    }
+}
+
+pub trait DoorEventApi {
+    /// Reports a door sensor event to the upstream API.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request cannot be completed.
+    async fn report_door_event(&self, req: DoorEventRequest) -> Result<(), ApiError>;
+}
+
+impl<T: DoorEventApi> DoorEventApi for &T {
+    async fn report_door_event(&self, req: DoorEventRequest) -> Result<(), ApiError> {
+        (**self).report_door_event(req).await
+    }
+}
+
+pub trait HeartbeatApi {
+    /// Tells the upstream API that the terminal is alive.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request cannot be completed or the response
+    /// cannot be interpreted.
+    async fn heartbeat(&self, req: HeartbeatRequest) -> Result<HeartbeatResponse, ApiError>;
+}
+
+impl<T: HeartbeatApi> HeartbeatApi for &T {
+    async fn heartbeat(&self, req: HeartbeatRequest) -> Result<HeartbeatResponse, ApiError> {
+        (**self).heartbeat(req).await
+    }
 }
 
 pub trait SoundPlayer {
@@ -21,14 +60,153 @@ pub trait SoundPlayer {
     ///
     /// Returns an error if the sound backend cannot accept or decode the
     /// requested audio.
-    fn play(&self, sound: SoundEvent) -> anyhow::Result<()>;
-    fn reset(&self);
+    fn play(&self, sound: SoundEvent) -> Result<(), SoundError>;
+
+    /// Speaks `text`, or plays `fallback` if the backend cannot synthesize
+    /// speech.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither the utterance nor the fallback can be
+    /// queued.
+    fn speak(&self, text: &str, fallback: SoundEvent) -> Result<(), SoundError> {
+        let _ = text;
+        self.play(fallback)
+    }
+
+    /// Number of sounds waiting to be played, for status reports.
+    fn queue_len(&self) -> usize {
+        0
+    }
+}
+
+impl<T: SoundPlayer> SoundPlayer for &T {
+    fn play(&self, sound: SoundEvent) -> Result<(), SoundError> {
+        (**self).play(sound)
+    }
+
+    fn speak(&self, text: &str, fallback: SoundEvent) -> Result<(), SoundError> {
+        (**self).speak(text, fallback)
+    }
+
+    fn queue_len(&self) -> usize {
+        (**self).queue_len()
+    }
+}
+
+/// Audio backend that plays one sound at a time. What plays next is decided
+/// by [`crate::app::SoundScheduler`].
+pub trait AudioOutput {
+    /// Starts playing `request` with its volume multiplied by `gain`,
+    /// replacing whatever is playing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the audio cannot be decoded or played.
+    fn start(&self, request: &SoundRequest, gain: f32) -> Result<(), SoundError>;
+    fn stop(&self);
+    fn is_playing(&self) -> bool;
+}
+
+/// Status LEDs, buzzers and similar feedback besides sound.
+pub trait Indicator {
+    /// Shows `pattern`, replacing the one being shown. Never blocks; backends
+    /// log their own hardware failures.
+    fn show(&self, pattern: IndicatorPattern);
+}
+
+impl<T: Indicator> Indicator for &T {
+    fn show(&self, pattern: IndicatorPattern) {
+        (**self).show(pattern);
+    }
+}
+
+/// Indicator for terminals without status LEDs or buzzers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopIndicator;
+
+impl Indicator for NoopIndicator {
+    fn show(&self, _pattern: IndicatorPattern) {}
+}
+
+/// Small screen next to the reader showing occupancy and touch results.
+pub trait StatusDisplay {
+    /// Shows `screen`, replacing the one being shown. Never blocks; backends
+    /// log their own hardware failures.
+    fn show(&self, screen: DisplayScreen);
+}
+
+impl<T: StatusDisplay> StatusDisplay for &T {
+    fn show(&self, screen: DisplayScreen) {
+        (**self).show(screen);
+    }
+}
+
+/// Shows nothing when no display is configured.
+impl<T: StatusDisplay> StatusDisplay for Option<T> {
+    fn show(&self, screen: DisplayScreen) {
+        if let Some(display) = self {
+            display.show(screen);
+        }
+    }
+}
+
+/// Status display for terminals without a screen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopDisplay;
+
+impl StatusDisplay for NoopDisplay {
+    fn show(&self, _screen: DisplayScreen) {}
+}
+
+/// Counters and gauges the use cases report to. Recording never fails and
+/// never blocks.
+pub trait Metrics {
+    fn touch(&self, kind: CardKind, outcome: TouchOutcome);
+
+    /// A sound could not be played and was skipped.
+    fn sound_error(&self, sound: SoundEvent);
+
+    /// Number of sounds waiting behind the one being played.
+    fn sound_queue_depth(&self, depth: usize);
+}
+
+impl<T: Metrics> Metrics for &T {
+    fn touch(&self, kind: CardKind, outcome: TouchOutcome) {
+        (**self).touch(kind, outcome);
+    }
+
+    fn sound_error(&self, sound: SoundEvent) {
+        (**self).sound_error(sound);
+    }
+
+    fn sound_queue_depth(&self, depth: usize) {
+        (**self).sound_queue_depth(depth);
+    }
+}
+
+/// Metrics for terminals that do not export any.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {
+    fn touch(&self, _kind: CardKind, _outcome: TouchOutcome) {}
+
+    fn sound_error(&self, _sound: SoundEvent) {}
+
+    fn sound_queue_depth(&self, _depth: usize) {}
 }
 
 pub trait Clock {
     fn now(&self) -> chrono::DateTime<chrono::Local>;
 }
 
+impl<T: Clock> Clock for &T {
+    fn now(&self) -> chrono::DateTime<chrono::Local> {
+        (**self).now()
+    }
+}
+
 pub trait DoorLock {
     /// Unlocks the door.
     ///
@@ -36,5 +214,109 @@ pub trait DoorLock {
     ///
     /// Returns an error if the door lock backend cannot perform the unlock
     /// operation.
-    async fn unlock(&self) -> anyhow::Result<()>;
+    async fn unlock(&self) -> Result<(), LockError>;
+
+    /// Locks the door immediately and cancels any pending auto-lock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the door lock backend cannot perform the lock
+    /// operation.
+    async fn lock(&self) -> Result<(), LockError>;
+
+    /// Unlocks the door without scheduling an auto-lock, e.g. during open
+    /// hours. The door stays unlocked until [`DoorLock::lock`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the door lock backend cannot perform the unlock
+    /// operation.
+    async fn hold_open(&self) -> Result<(), LockError>;
+
+    /// Returns the current state, or `None` if the backend cannot tell.
+    async fn state(&self) -> Option<LockState> {
+        None
+    }
+}
+
+impl<T: DoorLock> DoorLock for &T {
+    async fn unlock(&self) -> Result<(), LockError> {
+        (**self).unlock().await
+    }
+
+    async fn hold_open(&self) -> Result<(), LockError> {
+        (**self).hold_open().await
+    }
+
+    async fn lock(&self) -> Result<(), LockError> {
+        (**self).lock().await
+    }
+
+    async fn state(&self) -> Option<LockState> {
+        (**self).state().await
+    }
+}
+
+/// Hardware that physically moves the bolt, without any state tracking.
+pub trait LockActuator {
+    /// Moves the bolt to the locked position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hardware cannot be driven or the bolt did not
+    /// move.
+    async fn lock(&mut self) -> anyhow::Result<()>;
+
+    /// Moves the bolt to the unlocked position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hardware cannot be driven or the bolt did not
+    /// move.
+    async fn unlock(&mut self) -> anyhow::Result<()>;
+
+    /// Whether the bolt stays unlocked after [`Self::unlock`] returns. A
+    /// pulsed strike relatches by itself and has to be driven on every
+    /// unlock.
+    fn holds_unlocked(&self) -> bool {
+        true
+    }
+}
+
+/// Persists the last known lock state across restarts.
+pub trait LockStateStore {
+    /// Loads the persisted state, or `None` if nothing has been saved yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored state cannot be read or parsed.
+    fn load(&self) -> anyhow::Result<Option<LockState>>;
+
+    /// Saves the current state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state cannot be written.
+    fn save(&self, state: LockState) -> anyhow::Result<()>;
+}
+
+pub trait DoorSensor {
+    /// Returns the last observed door state.
+    fn state(&self) -> DoorState;
+
+    /// Waits until the door state changes and returns the new state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sensor backend has stopped.
+    async fn wait_for_change(&mut self) -> anyhow::Result<DoorState>;
+}
+
+pub trait LockButton {
+    /// Waits for the next debounced button press.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the button backend has stopped.
+    async fn next_press(&mut self) -> anyhow::Result<ButtonPress>;
 }
@@ -0,0 +1,697 @@
+//! Local HTTP endpoints for checking and operating the terminal without SSH.
+//!
+//! The domain traits are not `Send`, so the server only forwards requests as
+//! [`Command`]s and [`AdminService`] answers them on the main task.
+
+use std::{
+    fmt,
+    net::SocketAddr,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use chrono::{DateTime, Local};
+use room_manager::domain::{
+    Card, DoorLock, LockState, ReaderId, SoundEvent, SoundPlayer, TouchOutcome,
+};
+use serde::Serialize;
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc, oneshot},
+};
+use tracing::{error, info, warn};
+
+use super::{HttpCardApi, ReaderPool};
+use crate::telemetry::LogFilter;
+
+/// How the last card touch ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TouchRecord {
+    pub at: DateTime<Local>,
+    pub reader: ReaderId,
+    pub kind: &'static str,
+    pub result: &'static str,
+}
+
+impl TouchRecord {
+    pub fn new(card: &Card, outcome: TouchOutcome, at: DateTime<Local>) -> Self {
+        Self {
+            at,
+            reader: card.reader.clone(),
+            kind: card.kind().as_str(),
+            result: outcome.as_str(),
+        }
+    }
+}
+
+/// The last touch and failure seen by the card loop.
+#[derive(Debug, Default)]
+pub struct TerminalLog {
+    last_touch: Mutex<Option<TouchRecord>>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl TerminalLog {
+    pub fn record_touch(&self, touch: TouchRecord) {
+        *self.last_touch.lock().unwrap() = Some(touch);
+    }
+
+    pub fn record_error(&self, error: &impl fmt::Display) {
+        *self.last_error.lock().unwrap() = Some(error.to_string());
+    }
+
+    pub fn last_touch(&self) -> Option<TouchRecord> {
+        self.last_touch.lock().unwrap().clone()
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Status {
+    pub version: &'static str,
+    pub uptime_secs: u64,
+    pub readers: Vec<ReaderId>,
+    pub lock_state: Option<LockState>,
+    pub sound_queue: usize,
+    pub last_touch: Option<TouchRecord>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub readers: usize,
+    /// Why the API could not be reached, or `None` if it answered.
+    pub api_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Unlock,
+    Lock,
+    TestSound,
+    RescanReaders,
+}
+
+impl FromStr for Action {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unlock" => Ok(Self::Unlock),
+            "lock" => Ok(Self::Lock),
+            "test-sound" => Ok(Self::TestSound),
+            "rescan-readers" => Ok(Self::RescanReaders),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Readers added by an action. Only a rescan adds any.
+pub type ActionResult = Result<Vec<ReaderId>, String>;
+
+#[derive(Debug)]
+pub enum Command {
+    Status(oneshot::Sender<Status>),
+    Ready(oneshot::Sender<Readiness>),
+    Admin(Action, oneshot::Sender<ActionResult>),
+}
+
+/// Answers the commands of the admin server with the terminal's drivers.
+pub struct AdminService<'a, D, P> {
+    api: &'a HttpCardApi,
+    door_lock: D,
+    player: P,
+    readers: &'a ReaderPool<'a>,
+    log: &'a TerminalLog,
+    started: Instant,
+}
+
+impl<'a, D: DoorLock, P: SoundPlayer> AdminService<'a, D, P> {
+    pub fn new(
+        api: &'a HttpCardApi,
+        door_lock: D,
+        player: P,
+        readers: &'a ReaderPool<'a>,
+        log: &'a TerminalLog,
+    ) -> Self {
+        Self {
+            api,
+            door_lock,
+            player,
+            readers,
+            log,
+            started: Instant::now(),
+        }
+    }
+
+    pub async fn run(self, mut commands: mpsc::Receiver<Command>) {
+        while let Some(command) = commands.recv().await {
+            // 応答を待たずに切断されたリクエストへの送信失敗は無視する
+            match command {
+                Command::Status(reply) => {
+                    let _ = reply.send(self.status().await);
+                }
+                Command::Ready(reply) => {
+                    let _ = reply.send(self.readiness().await);
+                }
+                Command::Admin(action, reply) => {
+                    let _ = reply.send(self.act(action).await);
+                }
+            }
+        }
+    }
+
+    async fn status(&self) -> Status {
+        Status {
+            version: env!("CARGO_PKG_VERSION"),
+            uptime_secs: self.started.elapsed().as_secs(),
+            readers: self.readers.readers(),
+            lock_state: self.door_lock.state().await,
+            sound_queue: self.player.queue_len(),
+            last_touch: self.log.last_touch(),
+            last_error: self.log.last_error(),
+        }
+    }
+
+    async fn readiness(&self) -> Readiness {
+        let readers = self.readers.readers().len();
+        let api_error = self.api.ping().await.err().map(|error| error.to_string());
+
+        Readiness {
+            ready: readers > 0 && api_error.is_none(),
+            readers,
+            api_error,
+        }
+    }
+
+    async fn act(&self, action: Action) -> ActionResult {
+        info!(?action, "running admin action");
+        let result = match action {
+            Action::Unlock => self.door_lock.unlock().await.map_err(anyhow::Error::from),
+            Action::Lock => self.door_lock.lock().await.map_err(anyhow::Error::from),
+            Action::TestSound => self
+                .player
+                .play(SoundEvent::Chime)
+                .map_err(anyhow::Error::from),
+            Action::RescanReaders => return self.readers.rescan().map_err(|e| format!("{e:#}")),
+        };
+
+        result.map(|()| Vec::new()).map_err(|error| {
+            error!(?action, error = %format!("{error:#}"), "admin action failed");
+            format!("{error:#}")
+        })
+    }
+}
+
+#[derive(Clone)]
+struct ServerState {
+    commands: mpsc::Sender<Command>,
+    token: Option<Arc<str>>,
+    log_filter: LogFilter,
+}
+
+pub fn router(
+    commands: mpsc::Sender<Command>,
+    token: Option<String>,
+    log_filter: LogFilter,
+) -> Router {
+    let state = ServerState {
+        commands,
+        token: token.map(Arc::from),
+        log_filter,
+    };
+
+    Router::new()
+        .route("/healthz", get(|| async { "ok" }))
+        .route("/readyz", get(readyz))
+        .route("/status", get(status))
+        .route("/admin/log-level", get(log_level).put(set_log_level))
+        .route("/admin/{action}", post(admin))
+        .with_state(state)
+}
+
+async fn request<T>(
+    state: &ServerState,
+    command: impl FnOnce(oneshot::Sender<T>) -> Command,
+) -> Result<T, Response> {
+    let (tx, rx) = oneshot::channel();
+    let unavailable = || (StatusCode::SERVICE_UNAVAILABLE, "terminal is stopping").into_response();
+
+    state
+        .commands
+        .send(command(tx))
+        .await
+        .map_err(|_| unavailable())?;
+    rx.await.map_err(|_| unavailable())
+}
+
+async fn readyz(State(state): State<ServerState>) -> Response {
+    match request(&state, Command::Ready).await {
+        Ok(readiness) if readiness.ready => Json(readiness).into_response(),
+        Ok(readiness) => (StatusCode::SERVICE_UNAVAILABLE, Json(readiness)).into_response(),
+        Err(response) => response,
+    }
+}
+
+async fn status(State(state): State<ServerState>) -> Response {
+    match request(&state, Command::Status).await {
+        Ok(status) => Json(status).into_response(),
+        Err(response) => response,
+    }
+}
+
+async fn admin(
+    State(state): State<ServerState>,
+    Path(action): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let Ok(action) = action.parse::<Action>() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if let Err(rejection) = authorize(&state, &headers) {
+        warn!(?action, "rejected unauthorized admin request");
+        return rejection.into_response();
+    }
+
+    match request(&state, |reply| Command::Admin(action, reply)).await {
+        Ok(Ok(added)) => Json(serde_json::json!({ "added_readers": added })).into_response(),
+        Ok(Err(error)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": error })),
+        )
+            .into_response(),
+        Err(response) => response,
+    }
+}
+
+async fn log_level(State(state): State<ServerState>, headers: HeaderMap) -> Response {
+    if let Err(rejection) = authorize(&state, &headers) {
+        return rejection.into_response();
+    }
+    state.log_filter.current().into_response()
+}
+
+async fn set_log_level(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    directives: String,
+) -> Response {
+    if let Err(rejection) = authorize(&state, &headers) {
+        warn!("rejected unauthorized log level change");
+        return rejection.into_response();
+    }
+    let directives = directives.trim();
+    match state.log_filter.set(directives) {
+        Ok(()) => {
+            info!(filter = directives, "changed log filter");
+            state.log_filter.current().into_response()
+        }
+        Err(error) => (StatusCode::BAD_REQUEST, format!("{error:#}")).into_response(),
+    }
+}
+
+/// Admin endpoints need `Authorization: Bearer <ADMIN_TOKEN>`, and are
+/// disabled entirely when no token is configured.
+fn authorize(state: &ServerState, headers: &HeaderMap) -> Result<(), (StatusCode, &'static str)> {
+    let Some(token) = &state.token else {
+        return Err((StatusCode::FORBIDDEN, "admin actions are disabled"));
+    };
+    let authorized = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()));
+    if authorized {
+        Ok(())
+    } else {
+        Err((StatusCode::UNAUTHORIZED, ""))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Serves the admin endpoints on `addr` in the background and returns the
+/// commands to answer with [`AdminService::run`]. Admin actions and log
+/// level changes are refused unless `token` is set.
+///
+/// # Errors
+///
+/// Returns an error if `addr` cannot be bound.
+pub async fn spawn_server(
+    addr: SocketAddr,
+    token: Option<String>,
+    log_filter: LogFilter,
+) -> anyhow::Result<mpsc::Receiver<Command>> {
+    let listener = TcpListener::bind(addr).await?;
+    let (tx, rx) = mpsc::channel(8);
+    if token.is_none() {
+        warn!("ADMIN_TOKEN is not set; admin actions are disabled");
+    }
+    info!(addr = %listener.local_addr()?, "serving admin api");
+    tokio::spawn(async move {
+        if let Err(error) = axum::serve(listener, router(tx, token, log_filter)).await {
+            error!(error = %error, "admin server stopped");
+        }
+    });
+
+    Ok(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, future::Future};
+
+    use futures_util::stream;
+    use reqwest::Client;
+    use room_manager::domain::{LockError, SoundError, TouchIntent};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        infra::{
+            PrometheusMetrics,
+            fixtures::{test_card, test_reader},
+        },
+        runtime::CardStream,
+        telemetry::LogFilter,
+    };
+
+    const TOKEN: &str = "admin-secret";
+
+    #[derive(Default)]
+    struct FakeDoorLock {
+        state: RefCell<Option<LockState>>,
+    }
+
+    impl DoorLock for &FakeDoorLock {
+        async fn unlock(&self) -> Result<(), LockError> {
+            self.state.replace(Some(LockState::Unlocked));
+            Ok(())
+        }
+
+        async fn lock(&self) -> Result<(), LockError> {
+            self.state.replace(Some(LockState::Locked));
+            Ok(())
+        }
+
+        async fn hold_open(&self) -> Result<(), LockError> {
+            self.unlock().await
+        }
+
+        async fn state(&self) -> Option<LockState> {
+            *self.state.borrow()
+        }
+    }
+
+    #[derive(Default)]
+    struct FakePlayer {
+        played: RefCell<Vec<SoundEvent>>,
+    }
+
+    impl SoundPlayer for &FakePlayer {
+        fn play(&self, sound: SoundEvent) -> Result<(), SoundError> {
+            self.played.borrow_mut().push(sound);
+            Ok(())
+        }
+
+        fn queue_len(&self) -> usize {
+            self.played.borrow().len()
+        }
+    }
+
+    async fn serve(router: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    /// `/local-device` に `api_status` を返す API を立てる
+    async fn fake_api(api_status: StatusCode) -> HttpCardApi {
+        let router = Router::new().route("/local-device", get(move || async move { api_status }));
+        HttpCardApi::new(serve(router).await, "token", PrometheusMetrics::new()).unwrap()
+    }
+
+    #[derive(Default)]
+    struct Terminal {
+        door_lock: FakeDoorLock,
+        player: FakePlayer,
+        log: TerminalLog,
+    }
+
+    impl Terminal {
+        /// 管理サーバーとサービスを同じタスクで動かし、`check` にサーバーの URL を渡す
+        async fn check<F: Future<Output = ()>>(
+            &self,
+            api_status: StatusCode,
+            token: Option<&str>,
+            readers_plugged: bool,
+            check: impl FnOnce(String) -> F,
+        ) {
+            let api = fake_api(api_status).await;
+            let (pool, _streams) = ReaderPool::new(PrometheusMetrics::new(), |running| {
+                if !readers_plugged || !running.is_empty() {
+                    return Ok(Vec::new());
+                }
+                let stream: CardStream = Box::pin(stream::pending());
+                Ok(vec![(test_reader(), stream)])
+            });
+            let service = AdminService::new(&api, &self.door_lock, &self.player, &pool, &self.log);
+
+            let (_filter, log_filter) = LogFilter::new("info").unwrap();
+            let (tx, rx) = mpsc::channel(8);
+            let url = serve(router(tx, token.map(str::to_string), log_filter)).await;
+
+            tokio::select! {
+                () = service.run(rx) => unreachable!("admin service never completes"),
+                () = check(url) => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn health_and_readiness() {
+        let terminal = Terminal::default();
+
+        terminal
+            .check(StatusCode::OK, None, false, |url| async move {
+                let client = Client::new();
+                let health = client.get(format!("{url}/healthz")).send().await.unwrap();
+                assert_eq!(health.status(), StatusCode::OK);
+
+                // リーダーが無ければ準備未完了
+                let ready = client.get(format!("{url}/readyz")).send().await.unwrap();
+                assert_eq!(ready.status(), StatusCode::SERVICE_UNAVAILABLE);
+                let body: Value = ready.json().await.unwrap();
+                assert_eq!(body["readers"], 0);
+                assert_eq!(body["api_error"], Value::Null);
+            })
+            .await;
+
+        terminal
+            .check(
+                StatusCode::UNAUTHORIZED,
+                Some(TOKEN),
+                true,
+                |url| async move {
+                    let client = Client::new();
+                    let rescan = client
+                        .post(format!("{url}/admin/rescan-readers"))
+                        .bearer_auth(TOKEN)
+                        .send()
+                        .await
+                        .unwrap();
+                    assert_eq!(rescan.status(), StatusCode::OK);
+
+                    // API がトークンを拒否している
+                    let ready = client.get(format!("{url}/readyz")).send().await.unwrap();
+                    assert_eq!(ready.status(), StatusCode::SERVICE_UNAVAILABLE);
+                    let body: Value = ready.json().await.unwrap();
+                    assert_eq!(body["readers"], 1);
+                    assert!(body["api_error"].as_str().unwrap().contains("401"));
+                },
+            )
+            .await;
+
+        terminal
+            .check(StatusCode::OK, Some(TOKEN), true, |url| async move {
+                let client = Client::new();
+                client
+                    .post(format!("{url}/admin/rescan-readers"))
+                    .bearer_auth(TOKEN)
+                    .send()
+                    .await
+                    .unwrap();
+                let ready = client.get(format!("{url}/readyz")).send().await.unwrap();
+                assert_eq!(ready.status(), StatusCode::OK);
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn status_reports_terminal_state() {
+        let card = test_card(TouchIntent::Toggle);
+        let terminal = Terminal::default();
+        terminal
+            .log
+            .record_touch(TouchRecord::new(&card, TouchOutcome::Entered, Local::now()));
+        terminal.log.record_error(&"door lock actuator failed");
+
+        terminal
+            .check(StatusCode::OK, Some(TOKEN), true, |url| async move {
+                let client = Client::new();
+                for action in ["rescan-readers", "lock", "test-sound"] {
+                    let response = client
+                        .post(format!("{url}/admin/{action}"))
+                        .bearer_auth(TOKEN)
+                        .send()
+                        .await
+                        .unwrap();
+                    assert_eq!(response.status(), StatusCode::OK, "{action}");
+                }
+
+                let status: Value = client
+                    .get(format!("{url}/status"))
+                    .send()
+                    .await
+                    .unwrap()
+                    .json()
+                    .await
+                    .unwrap();
+                assert_eq!(status["version"], env!("CARGO_PKG_VERSION"));
+                assert_eq!(status["readers"][0]["usb_path"], "1-1.2");
+                assert_eq!(status["readers"][0]["label"], "front-door");
+                assert_eq!(status["lock_state"], "locked");
+                assert_eq!(status["sound_queue"], 1);
+                assert_eq!(status["last_touch"]["kind"], "student_card");
+                assert_eq!(status["last_touch"]["result"], "entered");
+                assert_eq!(status["last_touch"]["reader"]["usb_path"], "1-1.2");
+                assert_eq!(status["last_error"], "door lock actuator failed");
+            })
+            .await;
+
+        assert_eq!(*terminal.player.played.borrow(), vec![SoundEvent::Chime]);
+    }
+
+    #[tokio::test]
+    async fn admin_actions_require_the_token() {
+        let terminal = Terminal::default();
+
+        terminal
+            .check(StatusCode::OK, Some(TOKEN), true, |url| async move {
+                let client = Client::new();
+                let unlock = format!("{url}/admin/unlock");
+
+                let response = client.post(&unlock).send().await.unwrap();
+                assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+                let response = client
+                    .post(&unlock)
+                    .bearer_auth("wrong")
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+                let response = client
+                    .post(format!("{url}/admin/explode"))
+                    .bearer_auth(TOKEN)
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+                let response = client
+                    .post(&unlock)
+                    .bearer_auth(TOKEN)
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+            })
+            .await;
+        assert_eq!(
+            *terminal.door_lock.state.borrow(),
+            Some(LockState::Unlocked)
+        );
+
+        // トークン未設定なら管理操作は無効
+        let terminal = Terminal::default();
+        terminal
+            .check(StatusCode::OK, None, true, |url| async move {
+                let response = Client::new()
+                    .post(format!("{url}/admin/unlock"))
+                    .bearer_auth(TOKEN)
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            })
+            .await;
+        assert_eq!(*terminal.door_lock.state.borrow(), None);
+    }
+
+    #[tokio::test]
+    async fn log_level_can_be_changed() {
+        let terminal = Terminal::default();
+
+        terminal
+            .check(StatusCode::OK, Some(TOKEN), false, |url| async move {
+                let client = Client::new();
+                let log_level = format!("{url}/admin/log-level");
+
+                let response = client.get(&log_level).send().await.unwrap();
+                assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+                let response = client.put(&log_level).body("debug").send().await.unwrap();
+                assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+                let response = client
+                    .get(&log_level)
+                    .bearer_auth(TOKEN)
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(response.text().await.unwrap(), "info");
+
+                let response = client
+                    .put(&log_level)
+                    .bearer_auth(TOKEN)
+                    .body("room_manager=debug\n")
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                assert_eq!(response.text().await.unwrap(), "room_manager=debug");
+
+                // 不正な指定は 400 で、フィルタは変わらない
+                let response = client
+                    .put(&log_level)
+                    .bearer_auth(TOKEN)
+                    .body("room_manager=loud")
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+                let response = client
+                    .get(&log_level)
+                    .bearer_auth(TOKEN)
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(response.text().await.unwrap(), "room_manager=debug");
+            })
+            .await;
+    }
+}
@@ -1,18 +1,33 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use reqwest::{Client, header::HeaderMap};
-use room_manager::domain::{CardApi, TouchCardRequest, TouchCardResponse};
-use tracing::{error, info};
+use opentelemetry::{global, propagation::Injector};
+use reqwest::{
+    Client,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use room_manager::domain::{
+    ApiError, CardApi, DoorEventApi, DoorEventRequest, HeartbeatApi, HeartbeatRequest,
+    HeartbeatResponse, TouchCardRequest, TouchCardResponse,
+};
+use tracing::{Instrument as _, Span, error, info, info_span};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+use super::PrometheusMetrics;
 
 const API_TIMEOUT_SECS: u64 = 5;
 
 pub struct HttpCardApi {
     client: Client,
     api_path: String,
+    metrics: PrometheusMetrics,
 }
 
 impl HttpCardApi {
-    pub fn new(api_path: impl Into<String>, api_token: impl Into<String>) -> anyhow::Result<Self> {
+    pub fn new(
+        api_path: impl Into<String>,
+        api_token: impl Into<String>,
+        metrics: PrometheusMetrics,
+    ) -> anyhow::Result<Self> {
         let mut headers = HeaderMap::new();
         headers.insert(
             "Authorization",
@@ -27,23 +42,62 @@ impl HttpCardApi {
         Ok(Self {
             client,
             api_path: api_path.into(),
+            metrics,
         })
     }
-}
 
-impl CardApi for HttpCardApi {
-    async fn touch(&self, req: TouchCardRequest) -> anyhow::Result<TouchCardResponse> {
-        let start = std::time::Instant::now();
+    /// Checks that the API is reachable and accepts the token.
+    pub async fn ping(&self) -> Result<(), ApiError> {
+        self.observe("ping", self.send_ping()).await
+    }
+
+    /// Runs `request` in a client span and records its latency. Requests
+    /// built inside carry the span as `traceparent`.
+    async fn observe<T>(
+        &self,
+        endpoint: &'static str,
+        request: impl Future<Output = Result<T, ApiError>>,
+    ) -> Result<T, ApiError> {
+        let start = Instant::now();
+        let span = info_span!("api_request", endpoint, otel.kind = "client");
+        let result = request.instrument(span).await;
+        self.metrics
+            .observe_api(endpoint, start.elapsed(), result.as_ref().err());
+        result
+    }
+
+    async fn send_ping(&self) -> Result<(), ApiError> {
+        let response = self
+            .client
+            .get(format!("{}/local-device", self.api_path))
+            .headers(trace_headers())
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiError::Status(status.as_u16()));
+        }
+
+        Ok(())
+    }
+
+    async fn send_touch(&self, req: TouchCardRequest) -> Result<TouchCardResponse, ApiError> {
+        let start = Instant::now();
+
         info!(
             api_path = %self.api_path,
             idm = %req.idm,
             student_id = ?req.student_id,
+            reader = %req.reader,
             "sending touch-card api request"
         );
 
         let response = self
             .client
             .post(format!("{}/local-device/touch-card", self.api_path))
+            .headers(trace_headers())
             .json(&req)
             .timeout(Duration::from_secs(API_TIMEOUT_SECS))
             .send()
@@ -53,10 +107,11 @@ impl CardApi for HttpCardApi {
                     api_path = %self.api_path,
                     idm = %req.idm,
                     student_id = ?req.student_id,
+                    reader = %req.reader,
                     error = %e,
                     "touch-card api request failed"
                 );
-                anyhow::anyhow!("API request failed: {e}")
+                request_error(e)
             })?;
 
         let status = response.status();
@@ -69,10 +124,7 @@ impl CardApi for HttpCardApi {
                 elapsed_ms = elapsed,
                 "touch-card api request returned non-success status"
             );
-            return Err(anyhow::anyhow!(
-                "API request failed with status: {}",
-                response.status()
-            ));
+            return Err(ApiError::Status(status.as_u16()));
         }
 
         let response = response.json::<TouchCardResponse>().await.map_err(|e| {
@@ -82,7 +134,7 @@ impl CardApi for HttpCardApi {
                 error = %e,
                 "failed to parse touch-card api response"
             );
-            anyhow::anyhow!("Failed to parse API response: {e}")
+            ApiError::InvalidResponse(e.into())
         })?;
 
         info!(
@@ -93,4 +145,183 @@ impl CardApi for HttpCardApi {
 
         Ok(response)
     }
+
+    async fn send_door_event(&self, req: DoorEventRequest) -> Result<(), ApiError> {
+        let start = Instant::now();
+        info!(
+            api_path = %self.api_path,
+            event = ?req.event,
+            open_secs = ?req.open_secs,
+            "sending door-event api request"
+        );
+
+        let response = self
+            .client
+            .post(format!("{}/local-device/door-event", self.api_path))
+            .headers(trace_headers())
+            .json(&req)
+            .timeout(Duration::from_secs(API_TIMEOUT_SECS))
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        let status = response.status();
+        info!(
+            %status,
+            elapsed_ms = start.elapsed().as_millis(),
+            "received door-event api response"
+        );
+
+        if !status.is_success() {
+            return Err(ApiError::Status(status.as_u16()));
+        }
+
+        Ok(())
+    }
+
+    async fn send_heartbeat(&self, req: HeartbeatRequest) -> Result<HeartbeatResponse, ApiError> {
+        let response = self
+            .client
+            .post(format!("{}/local-device/heartbeat", self.api_path))
+            .headers(trace_headers())
+            .json(&req)
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiError::Status(status.as_u16()));
+        }
+
+        response
+            .json::<HeartbeatResponse>()
+            .await
+            .map_err(|e| ApiError::InvalidResponse(e.into()))
+    }
+}
+
+impl CardApi for HttpCardApi {
+    async fn touch(&self, req: TouchCardRequest) -> Result<TouchCardResponse, ApiError> {
+        self.observe("touch-card", self.send_touch(req)).await
+    }
+}
+
+impl DoorEventApi for HttpCardApi {
+    async fn report_door_event(&self, req: DoorEventRequest) -> Result<(), ApiError> {
+        self.observe("door-event", self.send_door_event(req)).await
+    }
+}
+
+impl HeartbeatApi for HttpCardApi {
+    async fn heartbeat(&self, req: HeartbeatRequest) -> Result<HeartbeatResponse, ApiError> {
+        self.observe("heartbeat", self.send_heartbeat(req)).await
+    }
+}
+
+fn request_error(error: reqwest::Error) -> ApiError {
+    if error.is_timeout() {
+        ApiError::Timeout
+    } else {
+        ApiError::Connection(error.into())
+    }
+}
+
+/// W3C trace context of the current span, empty unless spans are exported.
+fn trace_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers));
+    });
+    headers
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Json, Router, routing::post};
+    use opentelemetry::trace::{TraceContextExt as _, TracerProvider as _};
+    use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider};
+    use room_manager::domain::TouchIntent;
+    use tokio::{net::TcpListener, sync::mpsc};
+    use tracing_subscriber::layer::SubscriberExt as _;
+
+    use super::*;
+    use crate::infra::fixtures::test_card;
+
+    /// touch-card に届いた `traceparent` を流す API を立てる
+    async fn fake_api() -> (HttpCardApi, mpsc::UnboundedReceiver<Option<String>>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let router = Router::new().route(
+            "/local-device/touch-card",
+            post(move |headers: HeaderMap| async move {
+                let traceparent = headers
+                    .get("traceparent")
+                    .map(|value| value.to_str().unwrap().to_string());
+                tx.send(traceparent).unwrap();
+                Json(serde_json::json!({ "success": true, "status": "entry", "entries": 1 }))
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let api = HttpCardApi::new(url, "token", PrometheusMetrics::new()).unwrap();
+        (api, rx)
+    }
+
+    fn request() -> TouchCardRequest {
+        test_card(TouchIntent::Toggle).into()
+    }
+
+    #[tokio::test]
+    async fn requests_carry_the_touch_trace() {
+        let (api, mut traceparents) = fake_api().await;
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let touch = info_span!("touch");
+        let touch_context = touch.context();
+        let touch_context = touch_context.span().span_context().clone();
+        api.touch(request()).instrument(touch).await.unwrap();
+
+        let traceparent = traceparents.recv().await.unwrap().unwrap();
+        let [version, trace_id, parent_id, flags] = traceparent.split('-').collect::<Vec<_>>()[..]
+        else {
+            panic!("malformed traceparent: {traceparent}");
+        };
+        assert_eq!(version, "00");
+        assert_eq!(trace_id, touch_context.trace_id().to_string());
+        // 親はタッチのスパンではなく API リクエストのスパン
+        assert_ne!(parent_id, touch_context.span_id().to_string());
+        assert_eq!(flags, "01");
+    }
+
+    #[tokio::test]
+    async fn no_trace_context_without_export() {
+        let (api, mut traceparents) = fake_api().await;
+
+        api.touch(request())
+            .instrument(info_span!("touch"))
+            .await
+            .unwrap();
+
+        assert_eq!(traceparents.recv().await.unwrap(), None);
+    }
 }
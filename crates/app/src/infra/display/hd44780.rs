@@ -0,0 +1,174 @@
+use std::{thread, time::Duration};
+
+use super::Panel;
+use crate::infra::i2c::I2cDevice;
+
+// PCF8574 バックパックのビット割り当て (P4-P7 がデータ線)
+const REGISTER_SELECT: u8 = 0x01;
+const ENABLE: u8 = 0x04;
+const BACKLIGHT: u8 = 0x08;
+
+const FUNCTION_SET_4BIT_2LINE: u8 = 0x28;
+const DISPLAY_ON: u8 = 0x0c;
+const ENTRY_MODE_INCREMENT: u8 = 0x06;
+const CLEAR: u8 = 0x01;
+const SET_DDRAM_ADDRESS: u8 = 0x80;
+
+/// HD44780 character LCD behind a PCF8574 I2C backpack, driven in 4-bit mode.
+#[derive(Debug)]
+pub struct Hd44780 {
+    device: Box<dyn I2cDevice>,
+    columns: usize,
+    rows: usize,
+}
+
+impl Hd44780 {
+    /// Resets the controller into 4-bit mode and clears it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backpack does not acknowledge the writes.
+    pub fn new(device: Box<dyn I2cDevice>, columns: usize, rows: usize) -> anyhow::Result<Self> {
+        let mut lcd = Self {
+            device,
+            columns,
+            rows,
+        };
+
+        // 電源投入直後はどのモードか分からないので、8 ビットモードを 3 回指定してから切り替える
+        thread::sleep(Duration::from_millis(50));
+        for _ in 0..3 {
+            lcd.write_nibble(0x03, 0)?;
+            thread::sleep(Duration::from_millis(5));
+        }
+        lcd.write_nibble(0x02, 0)?;
+        for command in [
+            FUNCTION_SET_4BIT_2LINE,
+            DISPLAY_ON,
+            ENTRY_MODE_INCREMENT,
+            CLEAR,
+        ] {
+            lcd.send(command, 0)?;
+        }
+        thread::sleep(Duration::from_millis(2));
+
+        Ok(lcd)
+    }
+
+    fn write_nibble(&mut self, nibble: u8, mode: u8) -> anyhow::Result<()> {
+        let byte = (nibble << 4) | mode | BACKLIGHT;
+        // E の立ち下がりで取り込まれる
+        self.device.write(&[byte | ENABLE, byte])
+    }
+
+    fn send(&mut self, byte: u8, mode: u8) -> anyhow::Result<()> {
+        self.write_nibble(byte >> 4, mode)?;
+        self.write_nibble(byte & 0x0f, mode)
+    }
+
+    /// DDRAM address of the first character of `row`. Rows 2 and 3 continue
+    /// rows 0 and 1.
+    fn row_address(&self, row: usize) -> u8 {
+        let offset = if row >= 2 { self.columns } else { 0 };
+        let base = if row % 2 == 1 { 0x40 } else { 0x00 };
+        base + u8::try_from(offset).unwrap_or(0)
+    }
+}
+
+impl Panel for Hd44780 {
+    fn columns(&self) -> usize {
+        self.columns
+    }
+
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn draw(&mut self, lines: &[String]) -> anyhow::Result<()> {
+        // 消去コマンドは遅いので、各行を空白で埋めて上書きする
+        for row in 0..self.rows {
+            self.send(SET_DDRAM_ADDRESS | self.row_address(row), 0)?;
+            let line = lines.get(row).map_or("", String::as_str);
+            let bytes = line.bytes().chain(std::iter::repeat(b' '));
+            for byte in bytes.take(self.columns) {
+                self.send(byte, REGISTER_SELECT)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::i2c::recording::RecordingI2cDevice;
+
+    /// 書き込みから 1 バイト単位の送信内容とモードを復元する
+    fn decode(writes: &[Vec<u8>]) -> Vec<(u8, bool)> {
+        let nibbles: Vec<_> = writes
+            .iter()
+            .map(|write| (write[1] >> 4, write[1] & REGISTER_SELECT != 0))
+            .collect();
+        nibbles
+            .chunks(2)
+            .map(|pair| ((pair[0].0 << 4) | pair[1].0, pair[0].1))
+            .collect()
+    }
+
+    #[test]
+    fn initializes_in_four_bit_mode() {
+        let device = RecordingI2cDevice::default();
+        Hd44780::new(device.boxed(), 16, 2).unwrap();
+
+        let writes = device.writes();
+        let first: Vec<_> = writes[..4].iter().map(|write| write[1] >> 4).collect();
+        assert_eq!(first, vec![0x03, 0x03, 0x03, 0x02]);
+        assert!(writes.iter().all(|write| write[0] & ENABLE != 0));
+        assert!(writes.iter().all(|write| write[1] & BACKLIGHT != 0));
+        assert_eq!(
+            decode(&writes[4..]),
+            vec![
+                (FUNCTION_SET_4BIT_2LINE, false),
+                (DISPLAY_ON, false),
+                (ENTRY_MODE_INCREMENT, false),
+                (CLEAR, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_pads_every_row() {
+        let device = RecordingI2cDevice::default();
+        let mut lcd = Hd44780::new(device.boxed(), 4, 2).unwrap();
+        device.clear();
+
+        lcd.draw(&["Hi".to_string()]).unwrap();
+
+        assert_eq!(
+            decode(&device.writes()),
+            vec![
+                (SET_DDRAM_ADDRESS, false),
+                (b'H', true),
+                (b'i', true),
+                (b' ', true),
+                (b' ', true),
+                (SET_DDRAM_ADDRESS | 0x40, false),
+                (b' ', true),
+                (b' ', true),
+                (b' ', true),
+                (b' ', true),
+            ]
+        );
+    }
+
+    #[test]
+    fn four_row_addresses() {
+        let lcd = Hd44780 {
+            device: RecordingI2cDevice::default().boxed(),
+            columns: 20,
+            rows: 4,
+        };
+        let addresses: Vec<_> = (0..4).map(|row| lcd.row_address(row)).collect();
+        assert_eq!(addresses, vec![0x00, 0x40, 0x14, 0x54]);
+    }
+}
@@ -0,0 +1,349 @@
+//! Status display driver and its text panels.
+//!
+//! Screens are rendered to a few lines of text, so every panel only has to
+//! know how to draw lines of characters.
+
+use std::time::Duration;
+
+use room_manager::domain::{DisplayScreen, ErrorCode, StatusDisplay};
+use tokio::{sync::watch, time};
+use tracing::{info, warn};
+
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod hd44780;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod ssd1306;
+pub mod terminal;
+
+/// Text output the screens are drawn on.
+pub trait Panel: Send + 'static {
+    /// Characters per line.
+    fn columns(&self) -> usize;
+
+    fn rows(&self) -> usize;
+
+    /// Whether characters outside ASCII, e.g. Japanese names, can be drawn.
+    fn unicode(&self) -> bool {
+        false
+    }
+
+    /// Replaces the whole panel with `lines`, which already fit its size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the panel cannot be written.
+    fn draw(&mut self, lines: &[String]) -> anyhow::Result<()>;
+}
+
+/// Draws screens on a [`Panel`] in a background task. Touch results stay on
+/// the panel for `hold`, then the occupancy screen comes back.
+#[derive(Debug, Clone)]
+pub struct TextDisplay {
+    tx: watch::Sender<Option<DisplayScreen>>,
+}
+
+impl TextDisplay {
+    pub fn spawn(panel: impl Panel, hold: Duration) -> Self {
+        let (tx, rx) = watch::channel(None);
+        info!(
+            columns = panel.columns(),
+            rows = panel.rows(),
+            "initialized status display"
+        );
+        tokio::spawn(run(panel, hold, rx));
+
+        Self { tx }
+    }
+}
+
+impl StatusDisplay for TextDisplay {
+    fn show(&self, screen: DisplayScreen) {
+        self.tx.send_replace(Some(screen));
+    }
+}
+
+async fn run(
+    mut panel: impl Panel,
+    hold: Duration,
+    mut rx: watch::Receiver<Option<DisplayScreen>>,
+) {
+    let mut occupancy = Occupancy::default();
+    draw(&mut panel, occupancy.lines());
+
+    let mut holding = false;
+    loop {
+        let changed = if holding {
+            let Ok(changed) = time::timeout(hold, rx.changed()).await else {
+                draw(&mut panel, occupancy.lines());
+                holding = false;
+                continue;
+            };
+            changed
+        } else {
+            rx.changed().await
+        };
+        if changed.is_err() {
+            return;
+        }
+
+        let Some(screen) = rx.borrow_and_update().clone() else {
+            continue;
+        };
+        occupancy.observe(&screen);
+        let lines = render(&screen, panel.unicode());
+        draw(&mut panel, lines);
+        holding = true;
+    }
+}
+
+fn draw(panel: &mut impl Panel, lines: Vec<String>) {
+    let (columns, unicode) = (panel.columns(), panel.unicode());
+    let lines: Vec<_> = lines
+        .into_iter()
+        .take(panel.rows())
+        .map(|line| {
+            line.chars()
+                .filter(|c| unicode || c.is_ascii())
+                .take(columns)
+                .collect()
+        })
+        .collect();
+    if let Err(error) = panel.draw(&lines) {
+        warn!(error = %format!("{error:#}"), "failed to draw status display");
+    }
+}
+
+/// What the panel shows between touches, learned from the touch results.
+#[derive(Debug, Default)]
+struct Occupancy {
+    entries: Option<u32>,
+    offline: bool,
+}
+
+impl Occupancy {
+    fn observe(&mut self, screen: &DisplayScreen) {
+        match screen {
+            DisplayScreen::Reading => {}
+            DisplayScreen::Entered { entries, .. }
+            | DisplayScreen::Exited { entries, .. }
+            | DisplayScreen::Lockdown { entries }
+            | DisplayScreen::RecordOnly { entries }
+            | DisplayScreen::LockFailed { entries } => {
+                self.entries = Some(*entries);
+                self.offline = false;
+            }
+            DisplayScreen::Rejected(_) | DisplayScreen::RegisterNfcCard { .. } => {
+                self.offline = false;
+            }
+            DisplayScreen::Offline => self.offline = true,
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let title = if self.offline {
+            "OFFLINE"
+        } else {
+            "Touch your card"
+        };
+        let mut lines = vec![title.to_string()];
+        lines.extend(self.entries.map(in_room));
+        lines
+    }
+}
+
+fn render(screen: &DisplayScreen, unicode: bool) -> Vec<String> {
+    match screen {
+        DisplayScreen::Reading => vec!["Reading...".to_string()],
+        DisplayScreen::Entered {
+            display_name,
+            entries,
+        } => vec![
+            salute("Welcome", display_name.as_deref(), unicode),
+            in_room(*entries),
+        ],
+        DisplayScreen::Exited {
+            display_name,
+            entries,
+        } => vec![
+            salute("Goodbye", display_name.as_deref(), unicode),
+            in_room(*entries),
+        ],
+        DisplayScreen::Lockdown { entries } => {
+            vec!["Locked down".to_string(), in_room(*entries)]
+        }
+        DisplayScreen::RecordOnly { entries } => {
+            vec!["Recorded only".to_string(), in_room(*entries)]
+        }
+        DisplayScreen::LockFailed { entries } => {
+            vec!["Door error".to_string(), in_room(*entries)]
+        }
+        DisplayScreen::Rejected(error_code) => {
+            let lines: &[&str] = match error_code {
+                ErrorCode::StudentCardNotRegistered => &["Card not registered", "/room register"],
+                ErrorCode::NfcCardNotRegistered => &["NFC not registered", "/room register"],
+                ErrorCode::StudentCardAlreadyRegistered | ErrorCode::NfcCardAlreadyRegistered => {
+                    &["Already registered"]
+                }
+                ErrorCode::AlreadyEntered => &["Already entered"],
+                ErrorCode::NotEntered => &["Not entered"],
+                ErrorCode::Unknown => &["Error", "Try again"],
+            };
+            lines.iter().map(ToString::to_string).collect()
+        }
+        DisplayScreen::RegisterNfcCard { code } => {
+            vec![format!("NFC code: {code}"), "/room register".to_string()]
+        }
+        DisplayScreen::Offline => vec!["Server offline".to_string(), "Try again".to_string()],
+    }
+}
+
+/// Adds the name only if the panel can draw all of it.
+fn salute(greeting: &str, display_name: Option<&str>, unicode: bool) -> String {
+    match display_name
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && (unicode || name.is_ascii()))
+    {
+        Some(name) => format!("{greeting}, {name}"),
+        None => format!("{greeting}!"),
+    }
+}
+
+fn in_room(entries: u32) -> String {
+    format!("In room: {entries}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Debug, Clone, Default)]
+    struct RecordingPanel {
+        frames: Arc<Mutex<Vec<Vec<String>>>>,
+    }
+
+    impl RecordingPanel {
+        fn last(&self) -> Vec<String> {
+            self.frames
+                .lock()
+                .unwrap()
+                .last()
+                .cloned()
+                .unwrap_or_default()
+        }
+    }
+
+    impl Panel for RecordingPanel {
+        fn columns(&self) -> usize {
+            16
+        }
+
+        fn rows(&self) -> usize {
+            2
+        }
+
+        fn draw(&mut self, lines: &[String]) -> anyhow::Result<()> {
+            self.frames.lock().unwrap().push(lines.to_vec());
+            Ok(())
+        }
+    }
+
+    fn entered(display_name: Option<&str>, entries: u32) -> DisplayScreen {
+        DisplayScreen::Entered {
+            display_name: display_name.map(ToString::to_string),
+            entries,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn result_returns_to_occupancy_after_hold() {
+        let panel = RecordingPanel::default();
+        let display = TextDisplay::spawn(panel.clone(), Duration::from_secs(5));
+        time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(panel.last(), vec!["Touch your card"]);
+
+        display.show(entered(Some("Taro"), 3));
+        time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(panel.last(), vec!["Welcome, Taro", "In room: 3"]);
+
+        time::sleep(Duration::from_secs(5)).await;
+        assert_eq!(panel.last(), vec!["Touch your card", "In room: 3"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn new_screen_restarts_hold() {
+        let panel = RecordingPanel::default();
+        let display = TextDisplay::spawn(panel.clone(), Duration::from_secs(5));
+
+        display.show(DisplayScreen::Reading);
+        time::sleep(Duration::from_secs(4)).await;
+        display.show(DisplayScreen::Offline);
+        time::sleep(Duration::from_secs(4)).await;
+        assert_eq!(panel.last(), vec!["Server offline", "Try again"]);
+
+        time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(panel.last(), vec!["OFFLINE"]);
+    }
+
+    #[test]
+    fn lines_are_cut_to_the_panel() {
+        let mut panel = RecordingPanel::default();
+        draw(
+            &mut panel,
+            vec![
+                "Card not registered".to_string(),
+                "/room register".to_string(),
+                "extra".to_string(),
+            ],
+        );
+        assert_eq!(panel.last(), vec!["Card not registe", "/room register"]);
+    }
+
+    #[test]
+    fn registration_code_is_shown_with_the_command() {
+        let screen = DisplayScreen::RegisterNfcCard {
+            code: "0420".to_string(),
+        };
+        assert_eq!(
+            render(&screen, false),
+            vec!["NFC code: 0420", "/room register"]
+        );
+    }
+
+    #[test]
+    fn names_the_panel_cannot_draw_are_left_out() {
+        assert_eq!(
+            render(&entered(Some("山田"), 1), false),
+            vec!["Welcome!", "In room: 1"]
+        );
+        assert_eq!(
+            render(&entered(Some("山田"), 1), true),
+            vec!["Welcome, 山田", "In room: 1"]
+        );
+        assert_eq!(
+            render(
+                &DisplayScreen::Exited {
+                    display_name: Some(" ".to_string()),
+                    entries: 0
+                },
+                false
+            ),
+            vec!["Goodbye!", "In room: 0"]
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::convert::Infallible;
+
+use embedded_graphics::{
+    Drawable as _, Pixel,
+    mono_font::{MonoTextStyle, ascii::FONT_6X10},
+    pixelcolor::BinaryColor,
+    prelude::{DrawTarget, OriginDimensions, Point, Size},
+    text::{Baseline, Text},
+};
+
+use super::Panel;
+use crate::infra::i2c::I2cDevice;
+
+const WIDTH: usize = 128;
+const HEIGHT: usize = 64;
+const LINE_HEIGHT: i32 = 10;
+const CHAR_WIDTH: usize = 6;
+
+const COMMAND: u8 = 0x00;
+const DATA: u8 = 0x40;
+
+// 128x64、チャージポンプ有効、水平アドレッシング
+const INIT: &[u8] = &[
+    0xae, // display off
+    0xd5, 0x80, // clock divide
+    0xa8, 0x3f, // multiplex 64
+    0xd3, 0x00, // display offset
+    0x40, // start line 0
+    0x8d, 0x14, // charge pump on
+    0x20, 0x00, // horizontal addressing
+    0xa1, // segment remap
+    0xc8, // COM scan descending
+    0xda, 0x12, // COM pins
+    0x81, 0xcf, // contrast
+    0xd9, 0xf1, // precharge
+    0xdb, 0x40, // VCOMH deselect
+    0xa4, // resume from RAM
+    0xa6, // normal (not inverted)
+    0xaf, // display on
+];
+const FULL_WINDOW: &[u8] = &[
+    0x21, 0x00, 0x7f, // columns 0-127
+    0x22, 0x00, 0x07, // pages 0-7
+];
+
+/// 128x64 SSD1306 OLED over I2C, drawn with a 6x10 font.
+#[derive(Debug)]
+pub struct Ssd1306 {
+    device: Box<dyn I2cDevice>,
+    frame: Frame,
+}
+
+impl Ssd1306 {
+    /// Turns the panel on with a blank frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the panel does not acknowledge the writes.
+    pub fn new(device: Box<dyn I2cDevice>) -> anyhow::Result<Self> {
+        let mut oled = Self {
+            device,
+            frame: Frame::default(),
+        };
+        oled.command(INIT)?;
+        oled.flush()?;
+
+        Ok(oled)
+    }
+
+    fn command(&mut self, commands: &[u8]) -> anyhow::Result<()> {
+        let mut bytes = vec![COMMAND];
+        bytes.extend_from_slice(commands);
+        self.device.write(&bytes)
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        self.command(FULL_WINDOW)?;
+        let mut bytes = vec![DATA];
+        bytes.extend_from_slice(&self.frame.0);
+        self.device.write(&bytes)
+    }
+}
+
+impl Panel for Ssd1306 {
+    fn columns(&self) -> usize {
+        WIDTH / CHAR_WIDTH
+    }
+
+    fn rows(&self) -> usize {
+        HEIGHT / LINE_HEIGHT as usize
+    }
+
+    fn draw(&mut self, lines: &[String]) -> anyhow::Result<()> {
+        self.frame = Frame::default();
+        let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+        for (row, line) in (0_i32..).zip(lines) {
+            let position = Point::new(0, row * LINE_HEIGHT);
+            let Ok(_) =
+                Text::with_baseline(line, position, style, Baseline::Top).draw(&mut self.frame);
+        }
+        self.flush()
+    }
+}
+
+/// Frame buffer in the controller's page layout: each byte is a column of
+/// 8 pixels, least significant bit on top.
+#[derive(Debug)]
+struct Frame([u8; WIDTH * HEIGHT / 8]);
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self([0; WIDTH * HEIGHT / 8])
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+impl OriginDimensions for Frame {
+    fn size(&self) -> Size {
+        Size::new(WIDTH as u32, HEIGHT as u32)
+    }
+}
+
+impl DrawTarget for Frame {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) else {
+                continue;
+            };
+            if x >= WIDTH || y >= HEIGHT {
+                continue;
+            }
+            let byte = &mut self.0[x + (y / 8) * WIDTH];
+            let bit = 1 << (y % 8);
+            if color.is_on() {
+                *byte |= bit;
+            } else {
+                *byte &= !bit;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::i2c::recording::RecordingI2cDevice;
+
+    #[test]
+    fn initializes_with_a_blank_frame() {
+        let device = RecordingI2cDevice::default();
+        Ssd1306::new(device.boxed()).unwrap();
+
+        let writes = device.writes();
+        assert_eq!(writes[0][0], COMMAND);
+        assert_eq!(&writes[0][1..], INIT);
+        assert_eq!(&writes[1][1..], FULL_WINDOW);
+        assert_eq!(writes[2][0], DATA);
+        assert_eq!(writes[2].len(), 1 + WIDTH * HEIGHT / 8);
+        assert!(writes[2][1..].iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn draw_lights_only_the_used_rows() {
+        let device = RecordingI2cDevice::default();
+        let mut oled = Ssd1306::new(device.boxed()).unwrap();
+        assert_eq!((oled.columns(), oled.rows()), (21, 6));
+        device.clear();
+
+        oled.draw(&["In room: 3".to_string()]).unwrap();
+
+        let data = &device.writes()[1][1..];
+        // 1 行目は 0-9 px なので 0, 1 ページにだけ描かれる
+        assert!(data[..2 * WIDTH].iter().any(|&byte| byte != 0));
+        assert!(data[2 * WIDTH..].iter().all(|&byte| byte == 0));
+        // 10 文字分より右は空白
+        assert!(data[10 * CHAR_WIDTH..WIDTH].iter().all(|&byte| byte == 0));
+    }
+}
@@ -0,0 +1,43 @@
+use std::io::Write as _;
+
+use super::Panel;
+
+/// Prints each frame to stderr, for trying out screens without hardware.
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalPanel {
+    columns: usize,
+    rows: usize,
+}
+
+impl TerminalPanel {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self { columns, rows }
+    }
+}
+
+impl Panel for TerminalPanel {
+    fn columns(&self) -> usize {
+        self.columns
+    }
+
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn unicode(&self) -> bool {
+        true
+    }
+
+    fn draw(&mut self, lines: &[String]) -> anyhow::Result<()> {
+        let border = format!("+{}+", "-".repeat(self.columns));
+        let mut frame = vec![border.clone()];
+        for row in 0..self.rows {
+            let line = lines.get(row).map_or("", String::as_str);
+            frame.push(format!("|{line:<width$}|", width = self.columns));
+        }
+        frame.push(border);
+
+        writeln!(std::io::stderr().lock(), "{}", frame.join("\n"))?;
+        Ok(())
+    }
+}
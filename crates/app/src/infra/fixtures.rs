@@ -0,0 +1,66 @@
+//! Fixtures for the in-module tests. The binary cannot see the library's
+//! `tests` module, so the cards mirror `test_card()` from there.
+
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use room_manager::domain::{Card, ReaderId, TouchIntent};
+
+pub fn test_reader() -> ReaderId {
+    ReaderId {
+        usb_path: "1-1.2".to_string(),
+        label: Some("front-door".to_string()),
+    }
+}
+
+pub fn test_card(intent: TouchIntent) -> Card {
+    Card {
+        idm: "0123456789abcdef".to_string(),
+        student_id: Some(12_345_678),
+        balance: None,
+        intent,
+        reader: test_reader(),
+        detected_at: Instant::now(),
+    }
+}
+
+/// A file or directory under the temp dir, unique to this process, that is
+/// removed when the test ends, even on failure.
+pub struct TempPath(PathBuf);
+
+impl TempPath {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("room-manager-{}-{name}", std::process::id()));
+        let path = Self(path);
+        path.remove();
+        path
+    }
+
+    fn remove(&self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+impl Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
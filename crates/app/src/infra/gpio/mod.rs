@@ -0,0 +1,48 @@
+//! Minimal GPIO abstraction.
+//!
+//! Drivers take boxed pins instead of rppal types, so the door logic can run
+//! against [`recording`] pins off the Pi.
+
+use std::{fmt::Debug, time::Duration};
+
+#[cfg(test)]
+pub mod recording;
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+mod rppal_backend;
+
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use rppal_backend::{input_pullup, output};
+
+/// Digital output that can also be driven with software PWM.
+pub trait OutputPin: Debug + Send {
+    fn set_high(&mut self);
+
+    fn set_low(&mut self);
+
+    /// Starts software PWM with the given period and pulse width.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend cannot start PWM on this pin.
+    fn set_pwm(&mut self, period: Duration, pulse_width: Duration) -> anyhow::Result<()>;
+
+    /// Stops software PWM.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend cannot stop PWM on this pin.
+    fn clear_pwm(&mut self) -> anyhow::Result<()>;
+}
+
+/// Digital input.
+pub trait InputPin: Debug + Send {
+    fn is_low(&self) -> bool;
+}
@@ -0,0 +1,107 @@
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use super::{InputPin, OutputPin};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinEvent {
+    High,
+    Low,
+    Pwm {
+        period: Duration,
+        pulse_width: Duration,
+    },
+    ClearPwm,
+}
+
+/// Output pin that records every write with the (tokio) time it happened.
+/// Clones share the same record.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingOutputPin {
+    events: Arc<Mutex<Vec<(Instant, PinEvent)>>>,
+}
+
+impl RecordingOutputPin {
+    pub fn boxed(&self) -> Box<dyn OutputPin> {
+        Box::new(self.clone())
+    }
+
+    pub fn events(&self) -> Vec<PinEvent> {
+        self.timed_events()
+            .into_iter()
+            .map(|(_, event)| event)
+            .collect()
+    }
+
+    pub fn timed_events(&self) -> Vec<(Instant, PinEvent)> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    fn record(&self, event: PinEvent) {
+        self.events.lock().unwrap().push((Instant::now(), event));
+    }
+}
+
+impl OutputPin for RecordingOutputPin {
+    fn set_high(&mut self) {
+        self.record(PinEvent::High);
+    }
+
+    fn set_low(&mut self) {
+        self.record(PinEvent::Low);
+    }
+
+    fn set_pwm(&mut self, period: Duration, pulse_width: Duration) -> anyhow::Result<()> {
+        self.record(PinEvent::Pwm {
+            period,
+            pulse_width,
+        });
+
+        Ok(())
+    }
+
+    fn clear_pwm(&mut self) -> anyhow::Result<()> {
+        self.record(PinEvent::ClearPwm);
+
+        Ok(())
+    }
+}
+
+/// Input pin whose level is set by the test. Clones share the same level.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingInputPin {
+    low: Arc<AtomicBool>,
+}
+
+impl RecordingInputPin {
+    pub fn new(low: bool) -> Self {
+        Self {
+            low: Arc::new(AtomicBool::new(low)),
+        }
+    }
+
+    pub fn boxed(&self) -> Box<dyn InputPin> {
+        Box::new(self.clone())
+    }
+
+    pub fn set_low(&self, low: bool) {
+        self.low.store(low, Ordering::SeqCst);
+    }
+}
+
+impl InputPin for RecordingInputPin {
+    fn is_low(&self) -> bool {
+        self.low.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use rppal::gpio::Gpio;
+
+use super::{InputPin, OutputPin};
+
+/// Opens `pin` as an output, starting at the given level so that active-low
+/// loads are not energized while the pin is being set up.
+pub fn output(pin: u8, initial_high: bool) -> anyhow::Result<Box<dyn OutputPin>> {
+    let pin = Gpio::new()?.get(pin)?;
+    let output_pin = if initial_high {
+        pin.into_output_high()
+    } else {
+        pin.into_output_low()
+    };
+
+    Ok(Box::new(output_pin))
+}
+
+/// Opens `pin` as an input with the internal pull-up enabled.
+pub fn input_pullup(pin: u8) -> anyhow::Result<Box<dyn InputPin>> {
+    Ok(Box::new(Gpio::new()?.get(pin)?.into_input_pullup()))
+}
+
+impl OutputPin for rppal::gpio::OutputPin {
+    fn set_high(&mut self) {
+        rppal::gpio::OutputPin::set_high(self);
+    }
+
+    fn set_low(&mut self) {
+        rppal::gpio::OutputPin::set_low(self);
+    }
+
+    fn set_pwm(&mut self, period: Duration, pulse_width: Duration) -> anyhow::Result<()> {
+        rppal::gpio::OutputPin::set_pwm(self, period, pulse_width)?;
+
+        Ok(())
+    }
+
+    fn clear_pwm(&mut self) -> anyhow::Result<()> {
+        rppal::gpio::OutputPin::clear_pwm(self)?;
+
+        Ok(())
+    }
+}
+
+impl InputPin for rppal::gpio::InputPin {
+    fn is_low(&self) -> bool {
+        rppal::gpio::InputPin::is_low(self)
+    }
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use room_manager::domain::{ButtonPress, LockButton};
+use tokio::{
+    sync::mpsc,
+    time::{self, Instant},
+};
+use tracing::{debug, info};
+
+use super::gpio::InputPin;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+const DEBOUNCE: Duration = Duration::from_millis(30);
+
+/// Push button wired to ground with the internal pull-up, so the input reads
+/// low while pressed.
+///
+/// A press is reported as [`ButtonPress::Long`] as soon as it has been held for
+/// `long_press`, otherwise as [`ButtonPress::Short`] on release.
+#[derive(Debug)]
+pub struct GpioButton {
+    rx: mpsc::Receiver<ButtonPress>,
+}
+
+impl GpioButton {
+    pub fn spawn(input_pin: Box<dyn InputPin>, long_press: Duration) -> Self {
+        let (tx, rx) = mpsc::channel(4);
+        info!(
+            long_press_ms = long_press.as_millis(),
+            "initialized gpio button"
+        );
+
+        tokio::spawn(async move {
+            let mut interval = time::interval(POLL_INTERVAL);
+            // 押下が確定した時刻と、長押しを通知済みかどうか
+            let mut pressed_since: Option<Instant> = None;
+            let mut long_press_sent = false;
+            let mut raw_changed_at = Instant::now();
+            let mut raw_pressed = false;
+            loop {
+                interval.tick().await;
+                if tx.is_closed() {
+                    break;
+                }
+
+                let now = Instant::now();
+                let pressed = input_pin.is_low();
+                if pressed != raw_pressed {
+                    raw_pressed = pressed;
+                    raw_changed_at = now;
+                    continue;
+                }
+                if now - raw_changed_at < DEBOUNCE {
+                    continue;
+                }
+
+                let press = match (pressed, pressed_since) {
+                    (true, None) => {
+                        debug!("button pressed");
+                        pressed_since = Some(now);
+                        long_press_sent = false;
+                        None
+                    }
+                    (true, Some(since)) if !long_press_sent && now - since >= long_press => {
+                        long_press_sent = true;
+                        Some(ButtonPress::Long)
+                    }
+                    (false, Some(_)) => {
+                        debug!("button released");
+                        pressed_since = None;
+                        (!long_press_sent).then_some(ButtonPress::Short)
+                    }
+                    _ => None,
+                };
+
+                if let Some(press) = press
+                    && tx.send(press).await.is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Self { rx }
+    }
+}
+
+impl LockButton for GpioButton {
+    async fn next_press(&mut self) -> anyhow::Result<ButtonPress> {
+        self.rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("gpio button task stopped"))
+    }
+}
@@ -1,125 +1,122 @@
-use std::{pin::Pin, sync::Arc, time::Duration};
+use std::{
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
-use room_manager::domain::DoorLock;
-use rppal::gpio::{Gpio, OutputPin};
+use room_manager::{
+    app::DoorLockController,
+    domain::{DoorLock, DoorSensor, DoorState, LockActuator, LockError, LockState},
+};
 use tokio::{
     sync::{Mutex, mpsc},
     time::{self, Sleep},
 };
-use tracing::{error, info};
-
-const SERVO_PIN: u8 = 18;
-const SERVO_PERIOD: Duration = Duration::from_millis(20);
-
-// 0.5ms ~ 2.5ms
-const SERVO_MIN_DUTY_CYCLE_US: u64 = 500;
-const SERVO_MAX_DUTY_CYCLE_US: u64 = 2500;
+use tracing::{error, info, warn};
 
-const SERVO_MIN_ANGLE: u16 = 0;
-const SERVO_MAX_ANGLE: u16 = 180;
-
-const LOCK_ANGLE: u16 = 0;
-const UNLOCK_ANGLE: u16 = 180;
-const NEUTRAL_ANGLE: u16 = 90;
+use super::{
+    PrometheusMetrics, gpio_door_sensor::GpioDoorSensor, gpio_relay::RelayActuator,
+    gpio_servo::ServoActuator, lock_state_file::FileLockStateStore,
+};
 
-const SERVO_MOVE_WAIT_TIME: Duration = Duration::from_secs(1);
 const AUTO_LOCK_DELAY: Duration = Duration::from_secs(30);
 
+/// Hardware that physically moves the bolt.
 #[derive(Debug)]
-struct DoorLockInternal {
-    is_unlocked: bool,
-    output_pin: OutputPin,
+pub enum GpioActuator {
+    Servo(ServoActuator),
+    Relay(RelayActuator),
 }
 
-impl DoorLockInternal {
-    async fn new() -> anyhow::Result<Self> {
-        let output_pin = Gpio::new()?.get(SERVO_PIN)?.into_output();
-
-        let mut door_lock = Self {
-            is_unlocked: true,
-            output_pin,
-        };
-        door_lock.lock().await?;
-        info!("initialized gpio door lock");
-
-        Ok(door_lock)
-    }
-
+impl LockActuator for GpioActuator {
     async fn unlock(&mut self) -> anyhow::Result<()> {
-        info!("unlocking door");
-        self.set_unlock_angle()?;
-        time::sleep(SERVO_MOVE_WAIT_TIME).await;
-        self.set_neutral_angle()?;
-        time::sleep(SERVO_MOVE_WAIT_TIME).await;
-        self.output_pin.clear_pwm()?;
-        self.is_unlocked = true;
-        info!("door unlocked");
-
-        Ok(())
+        match self {
+            Self::Servo(servo) => servo.unlock().await,
+            Self::Relay(relay) => relay.unlock().await,
+        }
     }
 
     async fn lock(&mut self) -> anyhow::Result<()> {
-        if !self.is_unlocked {
-            return Ok(());
+        match self {
+            Self::Servo(servo) => servo.lock().await,
+            Self::Relay(relay) => relay.lock().await,
         }
-
-        info!("locking door");
-        self.set_lock_angle()?;
-        time::sleep(SERVO_MOVE_WAIT_TIME).await;
-        self.set_neutral_angle()?;
-        time::sleep(SERVO_MOVE_WAIT_TIME).await;
-        self.output_pin.clear_pwm()?;
-        self.is_unlocked = false;
-        info!("door locked");
-
-        Ok(())
     }
 
-    fn set_angle(&mut self, angle: u16) -> anyhow::Result<()> {
-        anyhow::ensure!(
-            angle <= SERVO_MAX_ANGLE,
-            "servo angle must be between {SERVO_MIN_ANGLE} and {SERVO_MAX_ANGLE}: {angle}"
-        );
-
-        let duty_cycle_us = SERVO_MIN_DUTY_CYCLE_US
-            + (SERVO_MAX_DUTY_CYCLE_US - SERVO_MIN_DUTY_CYCLE_US) * u64::from(angle)
-                / u64::from(SERVO_MAX_ANGLE);
+    fn holds_unlocked(&self) -> bool {
+        match self {
+            Self::Servo(_) => true,
+            Self::Relay(relay) => relay.holds_unlocked(),
+        }
+    }
+}
 
-        self.output_pin
-            .set_pwm(SERVO_PERIOD, Duration::from_micros(duty_cycle_us))?;
+/// Counts every movement of the bolt, including the one at boot.
+#[derive(Debug)]
+struct MeteredActuator {
+    actuator: GpioActuator,
+    metrics: PrometheusMetrics,
+}
 
-        Ok(())
+impl LockActuator for MeteredActuator {
+    async fn unlock(&mut self) -> anyhow::Result<()> {
+        let result = self.actuator.unlock().await;
+        self.metrics.lock_cycle(LockState::Unlocked, result.is_ok());
+        result
     }
 
-    fn set_lock_angle(&mut self) -> anyhow::Result<()> {
-        self.set_angle(LOCK_ANGLE)
+    async fn lock(&mut self) -> anyhow::Result<()> {
+        let result = self.actuator.lock().await;
+        self.metrics.lock_cycle(LockState::Locked, result.is_ok());
+        result
     }
 
-    fn set_unlock_angle(&mut self) -> anyhow::Result<()> {
-        self.set_angle(UNLOCK_ANGLE)
+    fn holds_unlocked(&self) -> bool {
+        self.actuator.holds_unlocked()
     }
+}
 
-    fn set_neutral_angle(&mut self) -> anyhow::Result<()> {
-        self.set_angle(NEUTRAL_ANGLE)
-    }
+type DoorLockInternal = DoorLockController<MeteredActuator, FileLockStateStore>;
+
+#[derive(Debug)]
+enum AutoLockCommand {
+    Schedule,
+    Cancel,
 }
 
 #[derive(Debug)]
 pub struct GpioDoorLock {
     internal: Arc<Mutex<DoorLockInternal>>,
-    tx_unlock: mpsc::Sender<()>,
+    tx_auto_lock: mpsc::Sender<AutoLockCommand>,
+    /// Set by `hold_open` until the next `lock`, so that unlocking in the
+    /// meantime does not re-arm the auto-lock.
+    held_open: AtomicBool,
 }
 
 impl GpioDoorLock {
-    pub async fn spawn() -> anyhow::Result<Self> {
-        let internal = DoorLockInternal::new().await?;
+    pub async fn spawn(
+        actuator: GpioActuator,
+        store: FileLockStateStore,
+        boot_state: LockState,
+        max_cycles_per_minute: usize,
+        mut door_sensor: Option<GpioDoorSensor>,
+        metrics: PrometheusMetrics,
+    ) -> Self {
+        let actuator = MeteredActuator { actuator, metrics };
+        let internal =
+            DoorLockController::boot(actuator, store, boot_state, max_cycles_per_minute).await;
+        info!(state = ?internal.state(), "initialized gpio door lock");
         let internal = Arc::new(Mutex::new(internal));
 
-        let (tx_unlock, mut rx_unlock) = mpsc::channel(1);
+        let (tx_auto_lock, mut rx_auto_lock) = mpsc::channel(1);
 
         let lock = Self {
             internal: Arc::clone(&internal),
-            tx_unlock,
+            tx_auto_lock,
+            held_open: AtomicBool::new(false),
         };
 
         {
@@ -127,14 +124,23 @@ impl GpioDoorLock {
             tokio::spawn(async move {
                 // unlockされたら30秒後にlockする
                 // ただし、30秒以内に別のメッセージが来たらその30秒後にlockをする。
+                // ドアが開いている間は施錠せず、閉まった時点で施錠する。
+                // 施錠に失敗したら同じ間隔で再試行する。
                 let mut timer: Option<Pin<Box<Sleep>>> = None;
+                let mut waiting_for_close = false;
                 loop {
                     tokio::select! {
-                        msg = rx_unlock.recv() => {
+                        msg = rx_auto_lock.recv() => {
                             match msg {
-                                Some(()) => {
+                                Some(AutoLockCommand::Schedule) => {
                                     info!("scheduled auto-lock");
                                     timer = Some(Box::pin(time::sleep(AUTO_LOCK_DELAY)));
+                                    waiting_for_close = false;
+                                },
+                                Some(AutoLockCommand::Cancel) => {
+                                    info!("cancelled auto-lock");
+                                    timer = None;
+                                    waiting_for_close = false;
                                 },
                                 None => break,
                             }
@@ -144,27 +150,352 @@ impl GpioDoorLock {
                                 timer.await;
                             }
                         }, if timer.is_some() => {
-                            let mut door_lock = internal.lock().await;
-                            if let Err(error) = door_lock.lock().await {
-                                error!(error = %error, "failed to auto-lock door");
+                            timer = None;
+                            if door_sensor.as_ref().is_some_and(|sensor| sensor.state() == DoorState::Open) {
+                                info!("door is open; deferring auto-lock until it closes");
+                                waiting_for_close = true;
                             } else {
-                                info!("auto-lock completed");
+                                timer = auto_lock(&internal).await;
+                            }
+                        },
+                        state = async {
+                            match door_sensor.as_mut() {
+                                Some(sensor) => sensor.wait_for_change().await,
+                                None => std::future::pending().await,
+                            }
+                        }, if waiting_for_close => {
+                            match state {
+                                Ok(DoorState::Open) => {}
+                                Ok(DoorState::Closed) => {
+                                    waiting_for_close = false;
+                                    timer = auto_lock(&internal).await;
+                                }
+                                Err(error) => {
+                                    warn!(error = %error, "door sensor stopped; locking without waiting for close");
+                                    door_sensor = None;
+                                    waiting_for_close = false;
+                                    timer = auto_lock(&internal).await;
+                                }
                             }
-                            timer = None;
                         }
                     }
                 }
             });
         }
 
-        Ok(lock)
+        lock
+    }
+}
+
+/// Locks the door, returning a timer for the retry if that fails.
+async fn auto_lock(internal: &Mutex<DoorLockInternal>) -> Option<Pin<Box<Sleep>>> {
+    let mut door_lock = internal.lock().await;
+    if let Err(error) = door_lock.lock().await {
+        error!(
+            error = %error,
+            retry_secs = AUTO_LOCK_DELAY.as_secs(),
+            "failed to auto-lock door"
+        );
+        return Some(Box::pin(time::sleep(AUTO_LOCK_DELAY)));
     }
+
+    info!("auto-lock completed");
+    None
 }
 
 impl DoorLock for GpioDoorLock {
-    async fn unlock(&self) -> anyhow::Result<()> {
+    async fn unlock(&self) -> Result<(), LockError> {
         info!("received unlock request");
-        self.tx_unlock.send(()).await?;
+        if self.held_open.load(Ordering::Relaxed) {
+            info!("door is held open; not scheduling auto-lock");
+        } else {
+            self.tx_auto_lock
+                .send(AutoLockCommand::Schedule)
+                .await
+                .map_err(|_| LockError::Stopped)?;
+        }
         self.internal.lock().await.unlock().await
     }
+
+    async fn lock(&self) -> Result<(), LockError> {
+        info!("received lock request");
+        self.held_open.store(false, Ordering::Relaxed);
+        self.tx_auto_lock
+            .send(AutoLockCommand::Cancel)
+            .await
+            .map_err(|_| LockError::Stopped)?;
+        self.internal.lock().await.lock().await
+    }
+
+    async fn hold_open(&self) -> Result<(), LockError> {
+        info!("received hold-open request");
+        self.held_open.store(true, Ordering::Relaxed);
+        self.tx_auto_lock
+            .send(AutoLockCommand::Cancel)
+            .await
+            .map_err(|_| LockError::Stopped)?;
+        self.internal.lock().await.unlock().await
+    }
+
+    async fn state(&self) -> Option<LockState> {
+        self.internal.lock().await.state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use room_manager::domain::{DoorLock, LockState, LockStateStore};
+    use tokio::time;
+
+    use super::*;
+    use crate::{
+        config::ServoAngles,
+        infra::{
+            fixtures::TempPath,
+            gpio::recording::{PinEvent, RecordingInputPin, RecordingOutputPin},
+        },
+    };
+
+    // 状態ファイルはテストの終わりに消す
+    fn state_file(name: &str) -> (FileLockStateStore, TempPath) {
+        let path = TempPath::new(&format!("{name}-lock-state"));
+        (FileLockStateStore::new(path.to_path_buf()), path)
+    }
+
+    async fn spawn_relay_lock(
+        name: &str,
+        pin: &RecordingOutputPin,
+        door_sensor: Option<GpioDoorSensor>,
+    ) -> (GpioDoorLock, TempPath) {
+        let relay = RelayActuator::new(pin.boxed(), false, None, door_sensor.clone());
+        let (store, path) = state_file(name);
+        let door_lock = GpioDoorLock::spawn(
+            GpioActuator::Relay(relay),
+            store,
+            LockState::Locked,
+            10,
+            door_sensor,
+            PrometheusMetrics::default(),
+        )
+        .await;
+        // 起動時の施錠は検証対象から外す
+        assert_eq!(pin.events(), vec![PinEvent::Low]);
+        pin.clear();
+
+        (door_lock, path)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn auto_locks_after_delay() {
+        let pin = RecordingOutputPin::default();
+        let (door_lock, _state) = spawn_relay_lock("auto-lock", &pin, None).await;
+
+        door_lock.unlock().await.unwrap();
+        time::sleep(Duration::from_secs(29)).await;
+        assert_eq!(pin.events(), vec![PinEvent::High]);
+
+        time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(pin.events(), vec![PinEvent::High, PinEvent::Low]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unlock_restarts_auto_lock_timer() {
+        let pin = RecordingOutputPin::default();
+        let (door_lock, _state) = spawn_relay_lock("restart", &pin, None).await;
+
+        door_lock.unlock().await.unwrap();
+        time::sleep(Duration::from_secs(20)).await;
+        door_lock.unlock().await.unwrap();
+        time::sleep(Duration::from_secs(20)).await;
+        // 解錠中の再解錠ではリレーを動かさない
+        assert_eq!(pin.events(), vec![PinEvent::High]);
+
+        time::sleep(Duration::from_secs(11)).await;
+        assert_eq!(pin.events(), vec![PinEvent::High, PinEvent::Low]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn burst_of_touches_still_auto_locks() {
+        let pin = RecordingOutputPin::default();
+        let (door_lock, _state) = spawn_relay_lock("burst", &pin, None).await;
+
+        // 上限の 10 回を超えるタッチが 1 分以内に続いても断らない
+        for _ in 0..15 {
+            door_lock.unlock().await.unwrap();
+            time::sleep(Duration::from_secs(2)).await;
+        }
+        assert_eq!(pin.events(), vec![PinEvent::High]);
+
+        time::sleep(AUTO_LOCK_DELAY).await;
+        assert_eq!(pin.events(), vec![PinEvent::High, PinEvent::Low]);
+
+        door_lock.unlock().await.unwrap();
+        assert_eq!(
+            pin.events(),
+            vec![PinEvent::High, PinEvent::Low, PinEvent::High]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pulsed_relay_is_driven_on_every_unlock() {
+        let pin = RecordingOutputPin::default();
+        let relay = RelayActuator::new(pin.boxed(), false, Some(Duration::from_millis(500)), None);
+        let (store, _state) = state_file("pulse");
+        let door_lock = GpioDoorLock::spawn(
+            GpioActuator::Relay(relay),
+            store,
+            LockState::Locked,
+            10,
+            None,
+            PrometheusMetrics::default(),
+        )
+        .await;
+        pin.clear();
+
+        door_lock.unlock().await.unwrap();
+        door_lock.unlock().await.unwrap();
+
+        assert_eq!(
+            pin.events(),
+            vec![PinEvent::High, PinEvent::Low, PinEvent::High, PinEvent::Low]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lock_cancels_pending_auto_lock() {
+        let pin = RecordingOutputPin::default();
+        let (door_lock, _state) = spawn_relay_lock("cancel", &pin, None).await;
+
+        door_lock.unlock().await.unwrap();
+        door_lock.lock().await.unwrap();
+        time::sleep(AUTO_LOCK_DELAY * 2).await;
+
+        assert_eq!(pin.events(), vec![PinEvent::High, PinEvent::Low]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hold_open_cancels_auto_lock() {
+        let pin = RecordingOutputPin::default();
+        let (door_lock, _state) = spawn_relay_lock("hold-open", &pin, None).await;
+
+        door_lock.unlock().await.unwrap();
+        door_lock.hold_open().await.unwrap();
+        time::sleep(AUTO_LOCK_DELAY * 2).await;
+        assert_eq!(pin.events(), vec![PinEvent::High]);
+
+        door_lock.lock().await.unwrap();
+        assert_eq!(pin.events(), vec![PinEvent::High, PinEvent::Low]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unlock_while_held_open_does_not_auto_lock() {
+        let pin = RecordingOutputPin::default();
+        let (door_lock, _state) = spawn_relay_lock("held-open-unlock", &pin, None).await;
+
+        door_lock.hold_open().await.unwrap();
+        // 開放中のボタンや管理 API からの解錠で自動施錠を仕掛け直さない
+        door_lock.unlock().await.unwrap();
+        time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(pin.events(), vec![PinEvent::High]);
+        assert_eq!(door_lock.state().await, Some(LockState::Unlocked));
+
+        // 施錠後の解錠は通常どおり自動施錠する
+        door_lock.lock().await.unwrap();
+        door_lock.unlock().await.unwrap();
+        time::sleep(AUTO_LOCK_DELAY + Duration::from_secs(1)).await;
+        assert_eq!(
+            pin.events(),
+            vec![PinEvent::High, PinEvent::Low, PinEvent::High, PinEvent::Low]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn auto_lock_waits_for_door_to_close() {
+        let pin = RecordingOutputPin::default();
+        let reed_switch = RecordingInputPin::new(true);
+        let door_sensor = GpioDoorSensor::spawn(reed_switch.boxed());
+        let (door_lock, _state) = spawn_relay_lock("door-open", &pin, Some(door_sensor)).await;
+
+        door_lock.unlock().await.unwrap();
+        reed_switch.set_low(false);
+        time::sleep(AUTO_LOCK_DELAY * 2).await;
+        assert_eq!(pin.events(), vec![PinEvent::High]);
+
+        reed_switch.set_low(true);
+        time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(pin.events(), vec![PinEvent::High, PinEvent::Low]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bolt_movements_are_counted() {
+        let pin = RecordingOutputPin::default();
+        let metrics = PrometheusMetrics::default();
+        let relay = RelayActuator::new(pin.boxed(), false, None, None);
+        let (store, _state) = state_file("metrics");
+        let door_lock = GpioDoorLock::spawn(
+            GpioActuator::Relay(relay),
+            store,
+            LockState::Locked,
+            10,
+            None,
+            metrics.clone(),
+        )
+        .await;
+
+        door_lock.unlock().await.unwrap();
+        time::sleep(AUTO_LOCK_DELAY * 2).await;
+
+        // 起動時と自動施錠の2回
+        let body = metrics.encode();
+        assert!(
+            body.contains(r#"room_manager_door_lock_cycles_total{direction="lock",result="ok"} 2"#),
+            "{body}"
+        );
+        assert!(
+            body.contains(
+                r#"room_manager_door_lock_cycles_total{direction="unlock",result="ok"} 1"#
+            )
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn servo_skips_boot_cycle_for_persisted_state() {
+        let pin = RecordingOutputPin::default();
+        let (store, _state) = state_file("servo");
+        store.save(LockState::Locked).unwrap();
+        let angles = ServoAngles {
+            lock: 0,
+            unlock: 180,
+            neutral: 90,
+        };
+        let servo = ServoActuator::new(pin.boxed(), angles, None);
+        let door_lock = GpioDoorLock::spawn(
+            GpioActuator::Servo(servo),
+            store,
+            LockState::Locked,
+            10,
+            None,
+            PrometheusMetrics::default(),
+        )
+        .await;
+        assert!(pin.events().is_empty());
+
+        door_lock.unlock().await.unwrap();
+
+        let period = Duration::from_millis(20);
+        assert_eq!(
+            pin.events(),
+            vec![
+                PinEvent::Pwm {
+                    period,
+                    pulse_width: Duration::from_micros(2500),
+                },
+                PinEvent::Pwm {
+                    period,
+                    pulse_width: Duration::from_micros(1500),
+                },
+                PinEvent::ClearPwm,
+            ]
+        );
+    }
 }
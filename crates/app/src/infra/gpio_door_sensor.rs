@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use room_manager::domain::{DoorSensor, DoorState};
+use tokio::{sync::watch, time};
+use tracing::{debug, info};
+
+use super::gpio::InputPin;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+// チャタリング対策として、同じ値が続いた回数で状態を確定する
+const DEBOUNCE_SAMPLES: u32 = 5;
+
+/// Reed switch wired to ground with the internal pull-up, so the input reads
+/// low while the door is closed.
+#[derive(Debug, Clone)]
+pub struct GpioDoorSensor {
+    rx: watch::Receiver<DoorState>,
+}
+
+impl GpioDoorSensor {
+    pub fn spawn(input_pin: Box<dyn InputPin>) -> Self {
+        let initial = read_state(input_pin.as_ref());
+        let (tx, rx) = watch::channel(initial);
+        info!(state = ?initial, "initialized gpio door sensor");
+
+        tokio::spawn(async move {
+            let mut interval = time::interval(POLL_INTERVAL);
+            let mut candidate = initial;
+            let mut samples = 0;
+            loop {
+                interval.tick().await;
+                if tx.is_closed() {
+                    break;
+                }
+
+                let state = read_state(input_pin.as_ref());
+                if state == *tx.borrow() {
+                    samples = 0;
+                    continue;
+                }
+                if state != candidate {
+                    candidate = state;
+                    samples = 0;
+                }
+                samples += 1;
+                if samples >= DEBOUNCE_SAMPLES {
+                    debug!(?state, "door sensor state changed");
+                    tx.send_replace(state);
+                    samples = 0;
+                }
+            }
+        });
+
+        Self { rx }
+    }
+}
+
+impl DoorSensor for GpioDoorSensor {
+    fn state(&self) -> DoorState {
+        *self.rx.borrow()
+    }
+
+    async fn wait_for_change(&mut self) -> anyhow::Result<DoorState> {
+        self.rx.changed().await?;
+        Ok(*self.rx.borrow_and_update())
+    }
+}
+
+fn read_state(input_pin: &dyn InputPin) -> DoorState {
+    if input_pin.is_low() {
+        DoorState::Closed
+    } else {
+        DoorState::Open
+    }
+}
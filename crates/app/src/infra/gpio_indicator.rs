@@ -0,0 +1,311 @@
+use std::time::Duration;
+
+use room_manager::domain::{Indicator, IndicatorPattern};
+use tokio::{sync::watch, time};
+use tracing::{info, warn};
+
+use super::gpio::OutputPin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    Off,
+    Red,
+    Green,
+    Blue,
+    Yellow,
+    Cyan,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    color: Color,
+    tone_hz: Option<u32>,
+    duration: Duration,
+}
+
+const fn step(color: Color, tone_hz: Option<u32>, millis: u64) -> Step {
+    Step {
+        color,
+        tone_hz,
+        duration: Duration::from_millis(millis),
+    }
+}
+
+const READING: &[Step] = &[
+    step(Color::Blue, Some(2000), 50),
+    // API の応答を待つ間は青を点けておく
+    step(Color::Blue, None, 5000),
+];
+const SUCCESS_ENTRY: &[Step] = &[
+    step(Color::Green, Some(1500), 100),
+    step(Color::Green, None, 1400),
+];
+const SUCCESS_EXIT: &[Step] = &[
+    step(Color::Green, Some(1500), 80),
+    step(Color::Green, None, 120),
+    step(Color::Green, Some(1200), 80),
+    step(Color::Green, None, 1200),
+];
+const RECORD_ONLY: &[Step] = &[
+    step(Color::Cyan, Some(1000), 100),
+    step(Color::Off, None, 100),
+    step(Color::Cyan, None, 1300),
+];
+const ERROR: &[Step] = &[
+    step(Color::Red, Some(400), 200),
+    step(Color::Off, None, 150),
+    step(Color::Red, Some(400), 200),
+    step(Color::Off, None, 150),
+    step(Color::Red, Some(400), 200),
+];
+const OFFLINE: &[Step] = &[
+    step(Color::Yellow, Some(300), 300),
+    step(Color::Off, None, 300),
+    step(Color::Yellow, None, 300),
+    step(Color::Off, None, 300),
+    step(Color::Yellow, Some(300), 300),
+];
+
+fn steps(pattern: IndicatorPattern) -> &'static [Step] {
+    match pattern {
+        IndicatorPattern::Reading => READING,
+        IndicatorPattern::SuccessEntry => SUCCESS_ENTRY,
+        IndicatorPattern::SuccessExit => SUCCESS_EXIT,
+        IndicatorPattern::RecordOnly => RECORD_ONLY,
+        IndicatorPattern::Error => ERROR,
+        IndicatorPattern::Offline => OFFLINE,
+    }
+}
+
+/// Common-cathode RGB LED, or common-anode with `active_low`.
+#[derive(Debug)]
+pub struct RgbLed {
+    pub red: Box<dyn OutputPin>,
+    pub green: Box<dyn OutputPin>,
+    pub blue: Box<dyn OutputPin>,
+    pub active_low: bool,
+}
+
+impl RgbLed {
+    fn set(&mut self, color: Color) {
+        let (red, green, blue) = match color {
+            Color::Off => (false, false, false),
+            Color::Red => (true, false, false),
+            Color::Green => (false, true, false),
+            Color::Blue => (false, false, true),
+            Color::Yellow => (true, true, false),
+            Color::Cyan => (false, true, true),
+        };
+        for (pin, on) in [
+            (&mut self.red, red),
+            (&mut self.green, green),
+            (&mut self.blue, blue),
+        ] {
+            if on == self.active_low {
+                pin.set_low();
+            } else {
+                pin.set_high();
+            }
+        }
+    }
+}
+
+/// Passive piezo buzzer driven with software PWM.
+#[derive(Debug)]
+pub struct Buzzer {
+    output_pin: Box<dyn OutputPin>,
+}
+
+impl Buzzer {
+    pub fn new(output_pin: Box<dyn OutputPin>) -> Self {
+        Self { output_pin }
+    }
+
+    fn set(&mut self, tone_hz: Option<u32>) {
+        let result = match tone_hz {
+            Some(hz) => {
+                let period = Duration::from_secs(1) / hz;
+                self.output_pin.set_pwm(period, period / 2)
+            }
+            None => self
+                .output_pin
+                .clear_pwm()
+                .map(|()| self.output_pin.set_low()),
+        };
+        if let Err(error) = result {
+            warn!(error = %error, "failed to drive buzzer");
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Outputs {
+    led: Option<RgbLed>,
+    buzzer: Option<Buzzer>,
+}
+
+impl Outputs {
+    fn set(&mut self, color: Color, tone_hz: Option<u32>) {
+        if let Some(led) = &mut self.led {
+            led.set(color);
+        }
+        if let Some(buzzer) = &mut self.buzzer {
+            buzzer.set(tone_hz);
+        }
+    }
+}
+
+/// Plays indicator patterns on an RGB LED and a buzzer in a background task.
+/// A new pattern cuts off the one being played.
+#[derive(Debug, Clone)]
+pub struct GpioIndicator {
+    tx: watch::Sender<Option<IndicatorPattern>>,
+}
+
+impl GpioIndicator {
+    pub fn spawn(led: Option<RgbLed>, buzzer: Option<Buzzer>) -> Self {
+        let (tx, rx) = watch::channel(None);
+        info!(
+            led = led.is_some(),
+            buzzer = buzzer.is_some(),
+            "initialized gpio indicator"
+        );
+
+        let mut outputs = Outputs { led, buzzer };
+        outputs.set(Color::Off, None);
+        tokio::spawn(run(outputs, rx));
+
+        Self { tx }
+    }
+}
+
+impl Indicator for GpioIndicator {
+    fn show(&self, pattern: IndicatorPattern) {
+        self.tx.send_replace(Some(pattern));
+    }
+}
+
+async fn run(mut outputs: Outputs, mut rx: watch::Receiver<Option<IndicatorPattern>>) {
+    let mut next = None;
+    loop {
+        if next.is_none() {
+            if rx.changed().await.is_err() {
+                return;
+            }
+            next = *rx.borrow_and_update();
+        }
+        let Some(pattern) = next.take() else {
+            continue;
+        };
+
+        let mut interrupted = false;
+        for step in steps(pattern) {
+            outputs.set(step.color, step.tone_hz);
+            tokio::select! {
+                () = time::sleep(step.duration) => {}
+                result = rx.changed() => {
+                    if result.is_err() {
+                        return;
+                    }
+                    next = *rx.borrow_and_update();
+                    interrupted = true;
+                    break;
+                }
+            }
+        }
+        if !interrupted {
+            outputs.set(Color::Off, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::gpio::recording::{PinEvent, RecordingOutputPin};
+
+    struct Pins {
+        red: RecordingOutputPin,
+        green: RecordingOutputPin,
+        blue: RecordingOutputPin,
+        buzzer: RecordingOutputPin,
+    }
+
+    fn spawn_indicator(active_low: bool) -> (GpioIndicator, Pins) {
+        let pins = Pins {
+            red: RecordingOutputPin::default(),
+            green: RecordingOutputPin::default(),
+            blue: RecordingOutputPin::default(),
+            buzzer: RecordingOutputPin::default(),
+        };
+        let led = RgbLed {
+            red: pins.red.boxed(),
+            green: pins.green.boxed(),
+            blue: pins.blue.boxed(),
+            active_low,
+        };
+        let indicator = GpioIndicator::spawn(Some(led), Some(Buzzer::new(pins.buzzer.boxed())));
+        (indicator, pins)
+    }
+
+    fn last(pin: &RecordingOutputPin) -> Option<PinEvent> {
+        pin.events().last().copied()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn success_entry_lights_green_and_beeps_once() {
+        let (indicator, pins) = spawn_indicator(false);
+
+        indicator.show(IndicatorPattern::SuccessEntry);
+        time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(last(&pins.green), Some(PinEvent::High));
+        assert_eq!(last(&pins.red), Some(PinEvent::Low));
+        assert_eq!(
+            last(&pins.buzzer),
+            Some(PinEvent::Pwm {
+                period: Duration::from_secs(1) / 1500,
+                pulse_width: Duration::from_secs(1) / 3000,
+            })
+        );
+
+        time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(last(&pins.green), Some(PinEvent::Low));
+        assert_eq!(last(&pins.buzzer), Some(PinEvent::Low));
+        let beeps = pins
+            .buzzer
+            .events()
+            .iter()
+            .filter(|event| matches!(event, PinEvent::Pwm { .. }))
+            .count();
+        assert_eq!(beeps, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn new_pattern_cuts_off_reading() {
+        let (indicator, pins) = spawn_indicator(false);
+
+        indicator.show(IndicatorPattern::Reading);
+        time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(last(&pins.blue), Some(PinEvent::High));
+
+        indicator.show(IndicatorPattern::Error);
+        time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(last(&pins.blue), Some(PinEvent::Low));
+        assert_eq!(last(&pins.red), Some(PinEvent::High));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn common_anode_led_is_driven_low() {
+        let (indicator, pins) = spawn_indicator(true);
+        assert_eq!(last(&pins.green), Some(PinEvent::High));
+
+        indicator.show(IndicatorPattern::Offline);
+        time::sleep(Duration::from_millis(10)).await;
+
+        // 黄色は赤と緑
+        assert_eq!(last(&pins.red), Some(PinEvent::Low));
+        assert_eq!(last(&pins.green), Some(PinEvent::Low));
+        assert_eq!(last(&pins.blue), Some(PinEvent::High));
+    }
+}
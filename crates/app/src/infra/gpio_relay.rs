@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use room_manager::domain::{DoorSensor, DoorState};
+use tokio::time;
+use tracing::{info, warn};
+
+use super::{gpio::OutputPin, gpio_door_sensor::GpioDoorSensor};
+
+/// Relay driving an electric strike or a solenoid bolt.
+///
+/// Without a pulse duration the relay stays energized while the door is
+/// unlocked (solenoid bolts, fail-safe strikes). With a pulse duration the
+/// relay is energized only for that long (fail-secure strikes that relatch by
+/// themselves), and releases early once the door sensor reports the door open.
+#[derive(Debug)]
+pub struct RelayActuator {
+    output_pin: Box<dyn OutputPin>,
+    active_low: bool,
+    pulse: Option<Duration>,
+    door_sensor: Option<GpioDoorSensor>,
+}
+
+impl RelayActuator {
+    /// `output_pin` should already be at the released level (high when
+    /// `active_low`).
+    pub fn new(
+        output_pin: Box<dyn OutputPin>,
+        active_low: bool,
+        pulse: Option<Duration>,
+        door_sensor: Option<GpioDoorSensor>,
+    ) -> Self {
+        info!(
+            active_low,
+            pulse_ms = pulse.map(|pulse| pulse.as_millis()),
+            door_sensor = door_sensor.is_some(),
+            "initialized relay actuator"
+        );
+
+        Self {
+            output_pin,
+            active_low,
+            pulse,
+            door_sensor,
+        }
+    }
+
+    /// A pulsed strike relatches by itself, so only a held relay keeps the
+    /// door unlocked.
+    pub fn holds_unlocked(&self) -> bool {
+        self.pulse.is_none()
+    }
+
+    pub async fn unlock(&mut self) -> anyhow::Result<()> {
+        self.energize();
+
+        if let Some(pulse) = self.pulse {
+            Self::wait_pulse(self.door_sensor.clone(), pulse).await;
+            self.release();
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::unused_async)]
+    pub async fn lock(&mut self) -> anyhow::Result<()> {
+        self.release();
+
+        Ok(())
+    }
+
+    async fn wait_pulse(door_sensor: Option<GpioDoorSensor>, pulse: Duration) {
+        let Some(mut door_sensor) = door_sensor else {
+            time::sleep(pulse).await;
+            return;
+        };
+
+        let door_opened = async {
+            while door_sensor.state() == DoorState::Closed {
+                if let Err(error) = door_sensor.wait_for_change().await {
+                    warn!(error = %error, "door sensor stopped during relay pulse");
+                    std::future::pending::<()>().await;
+                }
+            }
+        };
+
+        tokio::select! {
+            () = time::sleep(pulse) => {}
+            () = door_opened => info!("door opened; releasing relay early"),
+        }
+    }
+
+    fn energize(&mut self) {
+        if self.active_low {
+            self.output_pin.set_low();
+        } else {
+            self.output_pin.set_high();
+        }
+    }
+
+    fn release(&mut self) {
+        if self.active_low {
+            self.output_pin.set_high();
+        } else {
+            self.output_pin.set_low();
+        }
+    }
+}
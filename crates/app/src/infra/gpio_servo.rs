@@ -0,0 +1,218 @@
+use std::time::Duration;
+
+use room_manager::domain::{DoorSensor, DoorState};
+use tokio::time;
+use tracing::{info, warn};
+
+use super::{
+    gpio::{InputPin, OutputPin},
+    gpio_door_sensor::GpioDoorSensor,
+};
+use crate::{calibration::CalibrationServo, config::ServoAngles};
+
+const SERVO_PERIOD: Duration = Duration::from_millis(20);
+
+// 0.5ms ~ 2.5ms
+const SERVO_MIN_DUTY_CYCLE_US: u64 = 500;
+const SERVO_MAX_DUTY_CYCLE_US: u64 = 2500;
+
+const SERVO_MOVE_WAIT_TIME: Duration = Duration::from_secs(1);
+const MOVE_ATTEMPTS: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoltPosition {
+    Locked,
+    Unlocked,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ServoError {
+    #[error("servo angle must be between 0 and {max}: {angle}", max = ServoAngles::MAX)]
+    AngleOutOfRange { angle: u16 },
+    #[error("bolt did not reach the {0:?} position after {MOVE_ATTEMPTS} attempts")]
+    BoltDidNotMove(BoltPosition),
+}
+
+/// Input used to confirm that the bolt actually moved.
+#[derive(Debug)]
+pub enum LockFeedback {
+    /// Limit switch wired to ground with the internal pull-up, reading low
+    /// while the bolt is thrown.
+    LimitSwitch(Box<dyn InputPin>),
+    /// Door sensor. It cannot see the bolt, so it only confirms that the door
+    /// was closed when locking.
+    DoorSensor(GpioDoorSensor),
+}
+
+impl LockFeedback {
+    fn confirms(&self, position: BoltPosition) -> bool {
+        match self {
+            Self::LimitSwitch(input_pin) => {
+                input_pin.is_low() == (position == BoltPosition::Locked)
+            }
+            Self::DoorSensor(door_sensor) => {
+                position == BoltPosition::Unlocked || door_sensor.state() == DoorState::Closed
+            }
+        }
+    }
+}
+
+/// Hobby servo that pushes the thumb turn and returns to neutral.
+#[derive(Debug)]
+pub struct ServoActuator {
+    output_pin: Box<dyn OutputPin>,
+    angles: ServoAngles,
+    feedback: Option<LockFeedback>,
+}
+
+impl ServoActuator {
+    pub fn new(
+        output_pin: Box<dyn OutputPin>,
+        angles: ServoAngles,
+        feedback: Option<LockFeedback>,
+    ) -> Self {
+        info!(
+            ?angles,
+            feedback = feedback.is_some(),
+            "initialized servo actuator"
+        );
+
+        Self {
+            output_pin,
+            angles,
+            feedback,
+        }
+    }
+
+    pub async fn unlock(&mut self) -> anyhow::Result<()> {
+        self.move_bolt(BoltPosition::Unlocked).await
+    }
+
+    pub async fn lock(&mut self) -> anyhow::Result<()> {
+        self.move_bolt(BoltPosition::Locked).await
+    }
+
+    fn set_angle(&mut self, angle: u16) -> anyhow::Result<()> {
+        if angle > ServoAngles::MAX {
+            return Err(ServoError::AngleOutOfRange { angle }.into());
+        }
+
+        let duty_cycle_us = SERVO_MIN_DUTY_CYCLE_US
+            + (SERVO_MAX_DUTY_CYCLE_US - SERVO_MIN_DUTY_CYCLE_US) * u64::from(angle)
+                / u64::from(ServoAngles::MAX);
+
+        self.output_pin
+            .set_pwm(SERVO_PERIOD, Duration::from_micros(duty_cycle_us))?;
+
+        Ok(())
+    }
+
+    fn release(&mut self) -> anyhow::Result<()> {
+        self.output_pin.clear_pwm()?;
+
+        Ok(())
+    }
+
+    async fn move_bolt(&mut self, position: BoltPosition) -> anyhow::Result<()> {
+        let angle = match position {
+            BoltPosition::Locked => self.angles.lock,
+            BoltPosition::Unlocked => self.angles.unlock,
+        };
+
+        for attempt in 1..=MOVE_ATTEMPTS {
+            self.set_angle(angle)?;
+            time::sleep(SERVO_MOVE_WAIT_TIME).await;
+            self.set_angle(self.angles.neutral)?;
+            time::sleep(SERVO_MOVE_WAIT_TIME).await;
+            self.release()?;
+
+            let Some(feedback) = &self.feedback else {
+                return Ok(());
+            };
+            if feedback.confirms(position) {
+                return Ok(());
+            }
+            warn!(?position, attempt, "bolt did not move");
+        }
+
+        Err(ServoError::BoltDidNotMove(position).into())
+    }
+}
+
+impl CalibrationServo for ServoActuator {
+    fn set_angle(&mut self, angle: u16) -> anyhow::Result<()> {
+        Self::set_angle(self, angle)
+    }
+
+    fn release(&mut self) -> anyhow::Result<()> {
+        Self::release(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::gpio::recording::{PinEvent, RecordingInputPin, RecordingOutputPin};
+
+    const ANGLES: ServoAngles = ServoAngles {
+        lock: 0,
+        unlock: 180,
+        neutral: 90,
+    };
+
+    fn servo_moves(pin: &RecordingOutputPin) -> usize {
+        pin.events()
+            .iter()
+            .filter(|event| **event == PinEvent::ClearPwm)
+            .count()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limit_switch_confirms_lock() {
+        let pin = RecordingOutputPin::default();
+        let limit_switch = RecordingInputPin::new(true);
+        let mut servo = ServoActuator::new(
+            pin.boxed(),
+            ANGLES,
+            Some(LockFeedback::LimitSwitch(limit_switch.boxed())),
+        );
+
+        servo.lock().await.unwrap();
+
+        assert_eq!(servo_moves(&pin), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stuck_bolt_is_retried_then_reported() {
+        let pin = RecordingOutputPin::default();
+        let limit_switch = RecordingInputPin::new(false);
+        let mut servo = ServoActuator::new(
+            pin.boxed(),
+            ANGLES,
+            Some(LockFeedback::LimitSwitch(limit_switch.boxed())),
+        );
+
+        let error = servo.lock().await.unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<ServoError>(),
+            Some(ServoError::BoltDidNotMove(BoltPosition::Locked))
+        ));
+        assert_eq!(servo_moves(&pin), MOVE_ATTEMPTS as usize);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn door_sensor_feedback_rejects_lock_while_open() {
+        let pin = RecordingOutputPin::default();
+        let reed_switch = RecordingInputPin::new(false);
+        let door_sensor = GpioDoorSensor::spawn(reed_switch.boxed());
+        let mut servo = ServoActuator::new(
+            pin.boxed(),
+            ANGLES,
+            Some(LockFeedback::DoorSensor(door_sensor)),
+        );
+
+        servo.unlock().await.unwrap();
+        servo.lock().await.unwrap_err();
+    }
+}
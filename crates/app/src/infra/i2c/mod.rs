@@ -0,0 +1,32 @@
+//! Minimal I2C abstraction.
+//!
+//! Display drivers take a boxed device instead of rppal types, so they can
+//! run against a [`recording`] device off the Pi.
+
+use std::fmt::Debug;
+
+#[cfg(test)]
+pub mod recording;
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+mod rppal_backend;
+
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use rppal_backend::open;
+
+/// Device at a fixed address on an I2C bus.
+pub trait I2cDevice: Debug + Send {
+    /// Writes `bytes` in a single transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device does not acknowledge the write.
+    fn write(&mut self, bytes: &[u8]) -> anyhow::Result<()>;
+}
@@ -0,0 +1,30 @@
+use std::sync::{Arc, Mutex};
+
+use super::I2cDevice;
+
+/// I2C device that records every write. Clones share the same record.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingI2cDevice {
+    writes: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl RecordingI2cDevice {
+    pub fn boxed(&self) -> Box<dyn I2cDevice> {
+        Box::new(self.clone())
+    }
+
+    pub fn writes(&self) -> Vec<Vec<u8>> {
+        self.writes.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.writes.lock().unwrap().clear();
+    }
+}
+
+impl I2cDevice for RecordingI2cDevice {
+    fn write(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.writes.lock().unwrap().push(bytes.to_vec());
+        Ok(())
+    }
+}
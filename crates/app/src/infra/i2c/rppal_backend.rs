@@ -0,0 +1,19 @@
+use rppal::i2c::I2c;
+
+use super::I2cDevice;
+
+/// Opens the device at `address` on `/dev/i2c-<bus>`.
+pub fn open(bus: u8, address: u16) -> anyhow::Result<Box<dyn I2cDevice>> {
+    let mut i2c = I2c::with_bus(bus)?;
+    i2c.set_slave_address(address)?;
+
+    Ok(Box::new(i2c))
+}
+
+impl I2cDevice for I2c {
+    fn write(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        I2c::write(self, bytes)?;
+
+        Ok(())
+    }
+}
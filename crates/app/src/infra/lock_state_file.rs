@@ -0,0 +1,97 @@
+use std::{fs, io, path::PathBuf};
+
+use anyhow::Context as _;
+use room_manager::domain::{LockState, LockStateStore};
+
+/// Keeps the lock state in a one-word text file (`locked` / `unlocked`).
+#[derive(Debug)]
+pub struct FileLockStateStore {
+    path: PathBuf,
+}
+
+impl FileLockStateStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl LockStateStore for FileLockStateStore {
+    fn load(&self) -> anyhow::Result<Option<LockState>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read {}", self.path.display()));
+            }
+        };
+
+        match content.trim() {
+            "locked" => Ok(Some(LockState::Locked)),
+            "unlocked" => Ok(Some(LockState::Unlocked)),
+            other => anyhow::bail!("unknown lock state in {}: {other}", self.path.display()),
+        }
+    }
+
+    fn save(&self, state: LockState) -> anyhow::Result<()> {
+        let content = match state {
+            LockState::Locked => "locked\n",
+            LockState::Unlocked => "unlocked\n",
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+
+        // 書き込み途中で電源が落ちても壊れたファイルを残さないよう、置き換えで保存する
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, content).with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path)
+            .with_context(|| format!("failed to replace {}", self.path.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use room_manager::domain::{LockState, LockStateStore};
+
+    use super::FileLockStateStore;
+    use crate::infra::fixtures::TempPath;
+
+    fn temp_path(name: &str) -> TempPath {
+        TempPath::new(&format!("{name}-state-file"))
+    }
+
+    #[test]
+    fn missing_file_loads_as_unknown() {
+        let path = temp_path("missing");
+        let store = FileLockStateStore::new(path.to_path_buf());
+
+        assert_eq!(store.load().unwrap(), None);
+    }
+
+    #[test]
+    fn saved_state_round_trips() {
+        let path = temp_path("round-trip");
+        let store = FileLockStateStore::new(path.to_path_buf());
+
+        store.save(LockState::Unlocked).unwrap();
+        assert_eq!(store.load().unwrap(), Some(LockState::Unlocked));
+
+        store.save(LockState::Locked).unwrap();
+        assert_eq!(store.load().unwrap(), Some(LockState::Locked));
+    }
+
+    #[test]
+    fn corrupt_file_is_an_error() {
+        let path = temp_path("corrupt");
+        std::fs::write(&path, "ajar\n").unwrap();
+
+        FileLockStateStore::new(path.to_path_buf())
+            .load()
+            .unwrap_err();
+    }
+}
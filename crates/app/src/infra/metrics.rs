@@ -0,0 +1,344 @@
+//! Prometheus metrics of the terminal and the HTTP endpoint serving them.
+
+use std::{
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, atomic::AtomicU64},
+    time::{Duration, Instant},
+};
+
+use axum::{Router, extract::State, http::header, response::IntoResponse, routing::get};
+use prometheus_client::{
+    encoding::{EncodeLabelSet, text},
+    metrics::{
+        counter::Counter,
+        family::Family,
+        gauge::Gauge,
+        histogram::{Histogram, exponential_buckets},
+    },
+    registry::Registry,
+};
+use room_manager::domain::{ApiError, CardKind, Metrics, SoundEvent, TouchOutcome};
+use tokio::net::TcpListener;
+use tracing::{error, info};
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct TouchLabels {
+    kind: &'static str,
+    result: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct ApiLabels {
+    endpoint: &'static str,
+    result: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct SoundLabels {
+    sound: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct ReaderLabels {
+    reader: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct LockCycleLabels {
+    direction: &'static str,
+    result: &'static str,
+}
+
+/// Metrics shared by the use cases and drivers. Clones record into the same
+/// registry, so recording works whether or not the endpoint is served.
+#[derive(Clone)]
+pub struct PrometheusMetrics {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    registry: Registry,
+    started: Instant,
+    uptime: Gauge<f64, AtomicU64>,
+    touches: Family<TouchLabels, Counter>,
+    api_latency: Family<ApiLabels, Histogram, fn() -> Histogram>,
+    sound_queue_depth: Gauge,
+    sound_errors: Family<SoundLabels, Counter>,
+    readers_running: Gauge,
+    reader_failures: Family<ReaderLabels, Counter>,
+    lock_cycles: Family<LockCycleLabels, Counter>,
+}
+
+impl fmt::Debug for PrometheusMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrometheusMetrics").finish_non_exhaustive()
+    }
+}
+
+impl Default for PrometheusMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrometheusMetrics {
+    pub fn new() -> Self {
+        let mut registry = Registry::with_prefix("room_manager");
+        let inner = Inner {
+            started: Instant::now(),
+            uptime: Gauge::default(),
+            touches: Family::default(),
+            api_latency: Family::new_with_constructor(api_latency_histogram),
+            sound_queue_depth: Gauge::default(),
+            sound_errors: Family::default(),
+            readers_running: Gauge::default(),
+            reader_failures: Family::default(),
+            lock_cycles: Family::default(),
+            registry: Registry::default(),
+        };
+
+        registry.register(
+            "uptime_seconds",
+            "Seconds since the terminal started",
+            inner.uptime.clone(),
+        );
+        registry.register(
+            "touches",
+            "Card touches by card kind and result",
+            inner.touches.clone(),
+        );
+        registry.register(
+            "api_request_duration_seconds",
+            "Latency of requests to the API by endpoint and result",
+            inner.api_latency.clone(),
+        );
+        registry.register(
+            "sound_queue_depth",
+            "Sounds waiting behind the one being played",
+            inner.sound_queue_depth.clone(),
+        );
+        registry.register(
+            "sound_errors",
+            "Sounds that could not be played",
+            inner.sound_errors.clone(),
+        );
+        registry.register(
+            "readers_running",
+            "Card readers currently delivering cards",
+            inner.readers_running.clone(),
+        );
+        registry.register(
+            "reader_failures",
+            "Card reader threads that stopped with an error",
+            inner.reader_failures.clone(),
+        );
+        registry.register(
+            "door_lock_cycles",
+            "Movements of the door lock bolt by direction and result",
+            inner.lock_cycles.clone(),
+        );
+
+        Self {
+            inner: Arc::new(Inner { registry, ..inner }),
+        }
+    }
+
+    pub fn observe_api(&self, endpoint: &'static str, elapsed: Duration, error: Option<&ApiError>) {
+        let result = match error {
+            None => "ok",
+            Some(ApiError::Timeout) => "timeout",
+            Some(ApiError::Connection(_)) => "connection",
+            Some(ApiError::Status(_)) => "status",
+            Some(ApiError::InvalidResponse(_)) => "invalid_response",
+        };
+        self.inner
+            .api_latency
+            .get_or_create(&ApiLabels { endpoint, result })
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn readers_running(&self, count: usize) {
+        self.inner
+            .readers_running
+            .set(i64::try_from(count).unwrap_or(i64::MAX));
+    }
+
+    #[cfg(any(
+        test,
+        all(
+            feature = "raspi-runtime",
+            target_os = "linux",
+            any(target_arch = "arm", target_arch = "aarch64")
+        )
+    ))]
+    pub fn reader_failed(&self, reader: &room_manager::domain::ReaderId) {
+        self.inner
+            .reader_failures
+            .get_or_create(&ReaderLabels {
+                reader: reader.to_string(),
+            })
+            .inc();
+    }
+
+    #[cfg(any(
+        test,
+        all(
+            feature = "raspi-runtime",
+            target_os = "linux",
+            any(target_arch = "arm", target_arch = "aarch64")
+        )
+    ))]
+    pub fn lock_cycle(&self, state: room_manager::domain::LockState, succeeded: bool) {
+        use room_manager::domain::LockState;
+
+        let direction = match state {
+            LockState::Locked => "lock",
+            LockState::Unlocked => "unlock",
+        };
+        let result = if succeeded { "ok" } else { "error" };
+        self.inner
+            .lock_cycles
+            .get_or_create(&LockCycleLabels { direction, result })
+            .inc();
+    }
+
+    /// Renders every metric in the `OpenMetrics` text format.
+    pub fn encode(&self) -> String {
+        self.inner
+            .uptime
+            .set(self.inner.started.elapsed().as_secs_f64());
+        let mut body = String::new();
+        // String への書き込みは失敗しない
+        let _ = text::encode(&mut body, &self.inner.registry);
+        body
+    }
+}
+
+impl Metrics for PrometheusMetrics {
+    fn touch(&self, kind: CardKind, outcome: TouchOutcome) {
+        self.inner
+            .touches
+            .get_or_create(&TouchLabels {
+                kind: kind.as_str(),
+                result: outcome.as_str(),
+            })
+            .inc();
+    }
+
+    fn sound_error(&self, sound: SoundEvent) {
+        self.inner
+            .sound_errors
+            .get_or_create(&SoundLabels {
+                sound: sound.as_str(),
+            })
+            .inc();
+    }
+
+    fn sound_queue_depth(&self, depth: usize) {
+        self.inner
+            .sound_queue_depth
+            .set(i64::try_from(depth).unwrap_or(i64::MAX));
+    }
+}
+
+/// From 50 ms up to about 6.4 s, a bit past the client timeout.
+fn api_latency_histogram() -> Histogram {
+    Histogram::new(exponential_buckets(0.05, 2.0, 8))
+}
+
+pub fn router(metrics: PrometheusMetrics) -> Router {
+    Router::new()
+        .route("/metrics", get(serve_metrics))
+        .with_state(metrics)
+}
+
+async fn serve_metrics(State(metrics): State<PrometheusMetrics>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], metrics.encode())
+}
+
+/// Serves `/metrics` on `addr` in the background.
+///
+/// # Errors
+///
+/// Returns an error if `addr` cannot be bound.
+pub async fn spawn_server(addr: SocketAddr, metrics: PrometheusMetrics) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!(%addr, "serving metrics");
+    tokio::spawn(async move {
+        if let Err(error) = axum::serve(listener, router(metrics)).await {
+            error!(error = %error, "metrics server stopped");
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use room_manager::domain::{LockState, ReaderId};
+
+    use super::*;
+
+    #[test]
+    fn touches_are_labelled_by_kind_and_result() {
+        let metrics = PrometheusMetrics::new();
+        metrics.touch(CardKind::StudentCard, TouchOutcome::Entered);
+        metrics.touch(CardKind::StudentCard, TouchOutcome::Entered);
+        metrics.touch(CardKind::Transit, TouchOutcome::Offline);
+
+        let body = metrics.encode();
+        assert!(
+            body.contains(r#"room_manager_touches_total{kind="student_card",result="entered"} 2"#),
+            "{body}"
+        );
+        assert!(body.contains(r#"room_manager_touches_total{kind="transit",result="offline"} 1"#));
+        assert!(body.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn api_latency_is_bucketed_by_result() {
+        let metrics = PrometheusMetrics::new();
+        metrics.observe_api("touch-card", Duration::from_millis(80), None);
+        metrics.observe_api(
+            "touch-card",
+            Duration::from_secs(5),
+            Some(&ApiError::Timeout),
+        );
+
+        let body = metrics.encode();
+        assert!(body.contains(
+            r#"room_manager_api_request_duration_seconds_bucket{le="0.1",endpoint="touch-card",result="ok"} 1"#
+        ), "{body}");
+        assert!(body.contains(
+            r#"room_manager_api_request_duration_seconds_count{endpoint="touch-card",result="timeout"} 1"#
+        ));
+    }
+
+    #[test]
+    fn gauges_and_driver_counters_are_exported() {
+        let metrics = PrometheusMetrics::new();
+        metrics.sound_queue_depth(3);
+        metrics.readers_running(2);
+        metrics.sound_error(SoundEvent::GoodBye);
+        metrics.lock_cycle(LockState::Unlocked, true);
+        metrics.reader_failed(&ReaderId {
+            usb_path: "1-1.2".to_string(),
+            label: Some("front".to_string()),
+        });
+
+        let body = metrics.encode();
+        assert!(body.contains("room_manager_sound_queue_depth 3"), "{body}");
+        assert!(body.contains("room_manager_readers_running 2"));
+        assert!(body.contains(r#"room_manager_sound_errors_total{sound="good_bye"} 1"#));
+        assert!(
+            body.contains(
+                r#"room_manager_door_lock_cycles_total{direction="unlock",result="ok"} 1"#
+            )
+        );
+        assert!(body.contains(r#"room_manager_reader_failures_total{reader="front@1-1.2"} 1"#));
+        assert!(body.contains("room_manager_uptime_seconds "));
+    }
+}
@@ -1,15 +1,97 @@
+pub mod admin;
 pub mod api_reqwest;
+pub mod display;
+#[cfg(test)]
+pub mod fixtures;
+pub mod metrics;
+pub mod reader_pool;
 pub mod system_clock;
 
 pub use api_reqwest::HttpCardApi;
+pub use display::{TextDisplay, terminal::TerminalPanel};
+pub use metrics::PrometheusMetrics;
+pub use reader_pool::ReaderPool;
 pub use system_clock::SystemClock;
 
+// ドア周りのドライバは GPIO 抽象越しに動くので、x86 でもテスト時はビルドする
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod gpio;
 #[cfg(all(
     feature = "raspi-runtime",
     target_os = "linux",
     any(target_arch = "arm", target_arch = "aarch64")
 ))]
+pub mod gpio_button;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
 pub mod gpio_door_lock;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod gpio_door_sensor;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod gpio_indicator;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod gpio_relay;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod gpio_servo;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod i2c;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod lock_state_file;
 #[cfg(all(
     feature = "raspi-runtime",
     target_os = "linux",
@@ -22,13 +104,73 @@ pub mod player_rodio;
     any(target_arch = "arm", target_arch = "aarch64")
 ))]
 pub mod reader_pasori;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod sound_pack;
+#[cfg(any(
+    test,
+    all(
+        feature = "raspi-runtime",
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    )
+))]
+pub mod tts;
 
 #[cfg(all(
     feature = "raspi-runtime",
     target_os = "linux",
     any(target_arch = "arm", target_arch = "aarch64")
 ))]
-pub use gpio_door_lock::GpioDoorLock;
+pub use display::{hd44780::Hd44780, ssd1306::Ssd1306};
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use gpio_button::GpioButton;
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use gpio_door_lock::{GpioActuator, GpioDoorLock};
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use gpio_door_sensor::GpioDoorSensor;
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use gpio_indicator::{Buzzer, GpioIndicator, RgbLed};
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use gpio_relay::RelayActuator;
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use gpio_servo::{LockFeedback, ServoActuator};
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use lock_state_file::FileLockStateStore;
 #[cfg(all(
     feature = "raspi-runtime",
     target_os = "linux",
@@ -41,3 +183,15 @@ pub use player_rodio::RodioPlayer;
     any(target_arch = "arm", target_arch = "aarch64")
 ))]
 pub use reader_pasori::PasoriReader;
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use sound_pack::SoundPack;
+#[cfg(all(
+    feature = "raspi-runtime",
+    target_os = "linux",
+    any(target_arch = "arm", target_arch = "aarch64")
+))]
+pub use tts::{CachedSynthesizer, OpenJTalk};
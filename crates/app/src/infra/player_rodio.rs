@@ -1,65 +1,187 @@
-use std::io::Cursor;
+use std::{
+    io::Cursor,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        mpsc::{self, Sender},
+    },
+    thread,
+};
 
-use rodio::{Decoder, DeviceSinkBuilder, MixerDeviceSink, Player};
-use room_manager::domain::{SoundEvent, SoundPlayer};
-use tracing::info;
+use rodio::{Decoder, DeviceSinkBuilder, MixerDeviceSink, Player, Source as _};
+use room_manager::domain::{AudioOutput, SoundError, SoundEvent, SoundRequest};
+use tracing::{error, info, warn};
+
+use super::{
+    SoundPack,
+    tts::{CachedSynthesizer, OpenJTalk, Synthesizer as _},
+};
 
 pub struct RodioPlayer {
     _sink: MixerDeviceSink,
+    output: Arc<Output>,
+    speech: Option<Sender<Utterance>>,
+}
+
+/// What both the caller and the synthesis thread append to.
+struct Output {
     player: Player,
+    sound_pack: SoundPack,
+    speech: Mutex<SpeechState>,
+}
+
+#[derive(Debug, Default)]
+struct SpeechState {
+    /// Bumped by every stop, so that speech finished after it is dropped.
+    generation: u64,
+    synthesizing: bool,
+}
+
+#[derive(Debug)]
+struct Utterance {
+    text: String,
+    fallback: SoundEvent,
+    gain: f32,
+    generation: u64,
 }
 
 impl RodioPlayer {
-    pub fn new() -> anyhow::Result<Self> {
+    pub fn new(
+        sound_pack: SoundPack,
+        tts: Option<CachedSynthesizer<OpenJTalk>>,
+    ) -> anyhow::Result<Self> {
         info!("initializing rodio player");
 
         let sink = DeviceSinkBuilder::open_default_sink()?;
-        let player = Player::connect_new(sink.mixer());
+        let output = Arc::new(Output {
+            player: Player::connect_new(sink.mixer()),
+            sound_pack,
+            speech: Mutex::default(),
+        });
+
+        let speech = tts
+            .map(|tts| {
+                let (tx, rx) = mpsc::channel();
+                let output = Arc::clone(&output);
+                thread::Builder::new()
+                    .name("tts".to_string())
+                    .spawn(move || {
+                        for utterance in rx {
+                            output.speak(&tts, utterance);
+                        }
+                    })
+                    .map(|_| tx)
+            })
+            .transpose()?;
 
-        let player = Self {
+        Ok(Self {
             _sink: sink,
-            player,
-        };
-        player.play(SoundEvent::Boot)?;
-        Ok(player)
+            output,
+            speech,
+        })
     }
-}
 
-impl SoundPlayer for RodioPlayer {
-    fn play(&self, sound: SoundEvent) -> anyhow::Result<()> {
-        info!(?sound, "queueing sound");
+    /// Checks that rodio can decode `data`, for validating sound packs.
+    pub fn decodable(data: &[u8]) -> anyhow::Result<()> {
+        Decoder::new(Cursor::new(data.to_vec()))?;
+
+        Ok(())
+    }
 
-        let reader = sound_to_reader(sound);
-        let source = Decoder::new(reader)?;
+    /// Hands `text` to the synthesis thread, so that a slow `open_jtalk` never
+    /// blocks the caller. Until it is spoken the output counts as playing.
+    fn speak(&self, text: &str, fallback: SoundEvent, gain: f32) -> Result<(), SoundError> {
+        let Some(speech) = &self.speech else {
+            return self.output.play_clip(fallback, gain);
+        };
 
+        let mut state = self.output.speech();
+        let utterance = Utterance {
+            text: text.to_string(),
+            fallback,
+            gain,
+            generation: state.generation,
+        };
+        if speech.send(utterance).is_err() {
+            error!(
+                ?fallback,
+                "speech synthesis thread stopped; playing stock sound"
+            );
+            drop(state);
+            return self.output.play_clip(fallback, gain);
+        }
+        state.synthesizing = true;
+
+        Ok(())
+    }
+}
+
+impl Output {
+    fn play_clip(&self, sound: SoundEvent, gain: f32) -> Result<(), SoundError> {
+        let clip = self.sound_pack.clip(sound);
+        let source = Decoder::new(Cursor::new(clip.data))
+            .map_err(|e| SoundError::Decode(e.into()))?
+            .amplify(clip.volume * gain);
         self.player.append(source);
 
         Ok(())
     }
 
-    fn reset(&self) {
-        self.player.clear();
-        self.player.play();
+    /// Runs on the synthesis thread. Texts spoken before come from the cache.
+    fn speak(&self, tts: &CachedSynthesizer<OpenJTalk>, utterance: Utterance) {
+        let Utterance {
+            text,
+            fallback,
+            gain,
+            generation,
+        } = utterance;
+        let source = tts
+            .synthesize(&text)
+            .and_then(|wav| Ok(Decoder::new(Cursor::new(wav))?));
+
+        let mut state = self.speech();
+        if state.generation != generation {
+            info!(text, "dropping speech stopped during synthesis");
+            return;
+        }
+        state.synthesizing = false;
+        match source {
+            Ok(source) => {
+                info!(text, "playing speech");
+                self.player
+                    .append(source.amplify(self.sound_pack.volume(fallback) * gain));
+            }
+            Err(error) => {
+                warn!(error = %format!("{error:#}"), ?fallback, "speech synthesis failed; playing stock sound");
+                if let Err(error) = self.play_clip(fallback, gain) {
+                    error!(?fallback, error = %error, "failed to play sound");
+                }
+            }
+        }
+    }
+
+    fn speech(&self) -> MutexGuard<'_, SpeechState> {
+        self.speech.lock().unwrap_or_else(PoisonError::into_inner)
     }
 }
 
-fn sound_to_reader(sound: SoundEvent) -> Cursor<&'static [u8]> {
-    let buf = match sound {
-        SoundEvent::Boot => include_bytes!("../assets/sounds/boot.wav").as_slice(),
-        SoundEvent::Touch => include_bytes!("../assets/sounds/touch.wav").as_slice(),
-        SoundEvent::GoodMorning => include_bytes!("../assets/sounds/good_morning.wav").as_slice(),
-        SoundEvent::Hello => include_bytes!("../assets/sounds/hello.wav").as_slice(),
-        SoundEvent::GoodEvening => include_bytes!("../assets/sounds/good_evening.wav").as_slice(),
-        SoundEvent::GoodBye => include_bytes!("../assets/sounds/good_bye.wav").as_slice(),
-        SoundEvent::Last => include_bytes!("../assets/sounds/last.wav").as_slice(),
-        SoundEvent::Error => include_bytes!("../assets/sounds/error.wav").as_slice(),
-        SoundEvent::RegisterStudentCard => {
-            include_bytes!("../assets/sounds/register_student_card.wav").as_slice()
+impl AudioOutput for RodioPlayer {
+    fn start(&self, request: &SoundRequest, gain: f32) -> Result<(), SoundError> {
+        self.stop();
+        match request {
+            SoundRequest::Event(sound) => self.output.play_clip(*sound, gain),
+            SoundRequest::Speech { text, fallback } => self.speak(text, *fallback, gain),
         }
-        SoundEvent::RegisterNfcCard => {
-            include_bytes!("../assets/sounds/register_nfc_card.wav").as_slice()
-        }
-    };
+    }
 
-    Cursor::new(buf)
+    fn stop(&self) {
+        let mut state = self.output.speech();
+        state.generation += 1;
+        state.synthesizing = false;
+        self.output.player.clear();
+        self.output.player.play();
+    }
+
+    fn is_playing(&self) -> bool {
+        self.output.speech().synthesizing || !self.output.player.empty()
+    }
 }
@@ -1,4 +1,7 @@
-use std::{thread, time::Duration};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
 
 use anyhow::{anyhow, ensure};
 use async_stream::stream;
@@ -10,12 +13,14 @@ use pasori::{
     rusb::{Context as RusbContext, Device as RusbDevice},
     transport::Usb,
 };
-use room_manager::domain::Card;
+use room_manager::domain::{Card, ReaderId, TouchIntent};
 use tokio::sync::{
     mpsc::{self, UnboundedReceiver},
     oneshot::{self, error::TryRecvError},
 };
-use tracing::{info, warn};
+use tracing::{info, info_span, warn};
+
+use super::PrometheusMetrics;
 
 type DeviceReader = Box<dyn Device + Send + Sync>;
 
@@ -26,16 +31,24 @@ const SUICA_SERVICE_CODE: u16 = 0x090f;
 
 struct InternalPasoriReader {
     device: DeviceReader,
+    reader: ReaderId,
+    intent: TouchIntent,
 }
 
 impl InternalPasoriReader {
-    pub fn new(dev: RusbDevice<RusbContext>) -> anyhow::Result<Self> {
+    pub fn new(
+        dev: RusbDevice<RusbContext>,
+        reader: ReaderId,
+        intent: TouchIntent,
+    ) -> anyhow::Result<Self> {
         let transport = Usb::from_device(dev)?;
         let device = RCS380::new(transport)?;
-        info!("initialized pasori reader");
+        info!(%reader, ?intent, "initialized pasori reader");
 
         Ok(Self {
             device: Box::new(device),
+            reader,
+            intent,
         })
     }
 
@@ -50,6 +63,8 @@ impl InternalPasoriReader {
             return Ok(None);
         };
 
+        // 学生証や残高の読み取りにかかる時間もタッチの待ち時間に含める
+        let detected_at = Instant::now();
         let felica_card = polling_res.card;
         let idm = idm_to_string(&felica_card.idm());
         info!(idm = %idm, "detected felica card");
@@ -60,6 +75,9 @@ impl InternalPasoriReader {
                 idm,
                 student_id: None,
                 balance: None,
+                intent: self.intent,
+                reader: self.reader.clone(),
+                detected_at,
             };
             return Ok(Some((felica_card, card)));
         };
@@ -80,6 +98,9 @@ impl InternalPasoriReader {
                             idm,
                             student_id: None,
                             balance: None,
+                            intent: self.intent,
+                            reader: self.reader.clone(),
+                            detected_at,
                         };
                         return Ok(Some((felica_card, card)));
                     }
@@ -91,6 +112,9 @@ impl InternalPasoriReader {
                         idm,
                         student_id: None,
                         balance: None,
+                        intent: self.intent,
+                        reader: self.reader.clone(),
+                        detected_at,
                     };
                     return Ok(Some((felica_card, card)));
                 };
@@ -102,6 +126,9 @@ impl InternalPasoriReader {
                             idm,
                             student_id: None,
                             balance: None,
+                            intent: self.intent,
+                            reader: self.reader.clone(),
+                            detected_at,
                         };
                         return Ok(Some((felica_card, card)));
                     }
@@ -112,6 +139,9 @@ impl InternalPasoriReader {
                     idm,
                     student_id: Some(student_id),
                     balance: None,
+                    intent: self.intent,
+                    reader: self.reader.clone(),
+                    detected_at,
                 };
                 Ok(Some((felica_card, card)))
             }
@@ -147,6 +177,9 @@ impl InternalPasoriReader {
                     idm,
                     student_id: None,
                     balance: Some(balance),
+                    intent: self.intent,
+                    reader: self.reader.clone(),
+                    detected_at,
                 };
                 Ok(Some((felica_card, card)))
             }
@@ -156,6 +189,9 @@ impl InternalPasoriReader {
                     idm,
                     student_id: None,
                     balance: None,
+                    intent: self.intent,
+                    reader: self.reader.clone(),
+                    detected_at,
                 };
                 Ok(Some((felica_card, card)))
             }
@@ -187,46 +223,59 @@ impl InternalPasoriReader {
 }
 
 pub struct PasoriReader {
+    reader: ReaderId,
+    metrics: PrometheusMetrics,
     rx: UnboundedReceiver<Card>,
     stop_tx: Option<oneshot::Sender<()>>,
     handle: Option<thread::JoinHandle<anyhow::Result<()>>>,
 }
 
 impl PasoriReader {
-    pub fn spawn(dev: RusbDevice<RusbContext>) -> anyhow::Result<Self> {
+    pub fn spawn(
+        dev: RusbDevice<RusbContext>,
+        reader: ReaderId,
+        intent: TouchIntent,
+        metrics: PrometheusMetrics,
+    ) -> anyhow::Result<Self> {
         let (tx, rx) = mpsc::unbounded_channel();
         let (stop_tx, mut stop_rx) = oneshot::channel();
 
-        let mut reader = InternalPasoriReader::new(dev)?;
-
-        let handle = thread::Builder::new()
-            .name("pasori_reader".to_string())
-            .spawn(move || -> anyhow::Result<()> {
-                info!("pasori reader thread started");
-                loop {
-                    match stop_rx.try_recv() {
-                        Ok(()) | Err(TryRecvError::Closed) => {
-                            break;
+        let span = info_span!("pasori_reader", reader = %reader);
+        let thread_name = format!("pasori_reader_{}", reader.usb_path);
+        let mut internal = InternalPasoriReader::new(dev, reader.clone(), intent)?;
+
+        let handle =
+            thread::Builder::new()
+                .name(thread_name)
+                .spawn(move || -> anyhow::Result<()> {
+                    let _span = span.enter();
+                    info!("pasori reader thread started");
+                    loop {
+                        match stop_rx.try_recv() {
+                            Ok(()) | Err(TryRecvError::Closed) => {
+                                break;
+                            }
+                            Err(TryRecvError::Empty) => {}
                         }
-                        Err(TryRecvError::Empty) => {}
-                    }
 
-                    if let Some((felica_card, card)) = reader.scan_card()? {
-                        if tx.send(card).is_err() {
-                            warn!("stopping pasori reader thread because receiver was dropped");
-                            break;
+                        if let Some((felica_card, card)) = internal.scan_card()? {
+                            if tx.send(card).is_err() {
+                                warn!("stopping pasori reader thread because receiver was dropped");
+                                break;
+                            }
+                            internal.wait_release(&felica_card);
                         }
-                        reader.wait_release(&felica_card);
-                    }
 
-                    thread::sleep(Duration::from_millis(100));
-                }
+                        thread::sleep(Duration::from_millis(100));
+                    }
 
-                info!("pasori reader thread stopped");
-                Ok(())
-            })?;
+                    info!("pasori reader thread stopped");
+                    Ok(())
+                })?;
 
         Ok(Self {
+            reader,
+            metrics,
             rx,
             stop_tx: Some(stop_tx),
             handle: Some(handle),
@@ -239,8 +288,13 @@ impl PasoriReader {
             if handle.is_finished();
             if let Some(handle) = self.handle.take();
             then {
-                handle.join()
-                    .map_err(|payload| anyhow!("PasoriReader thread panicked: {payload:?}"))??;
+                let result = handle.join()
+                    .map_err(|payload| anyhow!("PasoriReader thread panicked: {payload:?}"))
+                    .and_then(|result| result);
+                if let Err(error) = result {
+                    self.metrics.reader_failed(&self.reader);
+                    return Err(error);
+                }
 
                 return Ok(None);
             }
@@ -0,0 +1,233 @@
+//! Card readers that can be rescanned while the terminal is running.
+
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
+
+use futures_util::{
+    Stream, StreamExt as _, future,
+    stream::{self, SelectAll},
+};
+use room_manager::domain::{Card, ReaderId};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use super::PrometheusMetrics;
+use crate::runtime::CardStream;
+
+type Scan<'a> = Box<dyn Fn(&[ReaderId]) -> anyhow::Result<Vec<(ReaderId, CardStream)>> + 'a>;
+type ReaderStream = Pin<Box<dyn Stream<Item = Card> + Send>>;
+
+/// Keeps track of the running readers and hands streams of newly found ones
+/// to the card loop.
+pub struct ReaderPool<'a> {
+    scan: Scan<'a>,
+    running: Arc<Mutex<Vec<ReaderId>>>,
+    streams: mpsc::UnboundedSender<ReaderStream>,
+    metrics: PrometheusMetrics,
+}
+
+impl<'a> ReaderPool<'a> {
+    /// `scan` is given the running readers and spawns only the others.
+    pub fn new(
+        metrics: PrometheusMetrics,
+        scan: impl Fn(&[ReaderId]) -> anyhow::Result<Vec<(ReaderId, CardStream)>> + 'a,
+    ) -> (Self, Cards) {
+        let (streams, new_readers) = mpsc::unbounded_channel();
+        let pool = Self {
+            scan: Box::new(scan),
+            running: Arc::default(),
+            streams,
+            metrics,
+        };
+        let cards = Cards {
+            new_readers,
+            readers: SelectAll::new(),
+        };
+        (pool, cards)
+    }
+
+    pub fn readers(&self) -> Vec<ReaderId> {
+        self.running.lock().unwrap().clone()
+    }
+
+    /// Spawns readers that are not running yet and returns them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scan fails.
+    pub fn rescan(&self) -> anyhow::Result<Vec<ReaderId>> {
+        let running = self.readers();
+        let found = (self.scan)(&running)?;
+
+        let mut added = Vec::with_capacity(found.len());
+        for (reader, stream) in found {
+            info!(%reader, "started card reader");
+            // エラーを出したリーダーはそこで止める
+            let failed = reader.clone();
+            let stream = stream.scan((), move |(), card| {
+                future::ready(card.map_err(|error| {
+                    error!(reader = %failed, error = %format!("{error:#}"), "card reader failed");
+                }).ok())
+            });
+            // 止まったリーダーは一覧から外し、次の再スキャンで拾い直せるようにする
+            let running = Arc::clone(&self.running);
+            let metrics = self.metrics.clone();
+            let stopped = reader.clone();
+            let stream = stream.chain(stream::poll_fn(move |_| {
+                warn!(reader = %stopped, "card reader stopped");
+                let mut running = running.lock().unwrap();
+                running.retain(|reader| reader != &stopped);
+                metrics.readers_running(running.len());
+                Poll::Ready(None)
+            }));
+
+            let mut running = self.running.lock().unwrap();
+            running.push(reader.clone());
+            self.metrics.readers_running(running.len());
+            drop(running);
+            if self.streams.send(Box::pin(stream)).is_err() {
+                anyhow::bail!("card loop has stopped");
+            }
+            added.push(reader);
+        }
+
+        Ok(added)
+    }
+}
+
+/// Cards from every running reader, including readers added by a rescan.
+pub struct Cards {
+    new_readers: mpsc::UnboundedReceiver<ReaderStream>,
+    readers: SelectAll<ReaderStream>,
+}
+
+impl Cards {
+    /// Waits for the next card. While no reader is running it waits for a
+    /// rescan, and returns `None` only once the pool is gone too.
+    pub async fn next(&mut self) -> Option<Card> {
+        loop {
+            tokio::select! {
+                biased;
+                Some(stream) = self.new_readers.recv() => self.readers.push(stream),
+                Some(card) = self.readers.next(), if !self.readers.is_empty() => return Some(card),
+                else => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use futures_util::FutureExt as _;
+    use room_manager::domain::TouchIntent;
+
+    use super::*;
+    use crate::infra::fixtures::test_card;
+
+    fn reader(usb_path: &str) -> ReaderId {
+        ReaderId {
+            usb_path: usb_path.to_string(),
+            label: None,
+        }
+    }
+
+    fn card(reader: ReaderId) -> Card {
+        Card {
+            reader,
+            ..test_card(TouchIntent::Toggle)
+        }
+    }
+
+    #[tokio::test]
+    async fn rescan_only_adds_new_readers() {
+        let plugged = Mutex::new(vec![reader("1-1")]);
+        let (pool, mut cards) = ReaderPool::new(PrometheusMetrics::new(), |running| {
+            Ok(plugged
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|reader| !running.contains(reader))
+                .map(|reader| {
+                    let stream: CardStream = Box::pin(stream::pending());
+                    (reader.clone(), stream)
+                })
+                .collect())
+        });
+
+        assert_eq!(pool.rescan().unwrap(), vec![reader("1-1")]);
+        plugged.lock().unwrap().push(reader("1-2"));
+        assert_eq!(pool.rescan().unwrap(), vec![reader("1-2")]);
+        assert_eq!(pool.readers(), vec![reader("1-1"), reader("1-2")]);
+
+        assert!(cards.new_readers.try_recv().is_ok());
+        assert!(cards.new_readers.try_recv().is_ok());
+        assert!(cards.new_readers.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn stopped_reader_can_be_found_again() {
+        let (pool, mut cards) = ReaderPool::new(PrometheusMetrics::new(), |running| {
+            if running.is_empty() {
+                let stream: CardStream = Box::pin(stream::iter([Ok(card(reader("1-1")))]));
+                Ok(vec![(reader("1-1"), stream)])
+            } else {
+                Ok(Vec::new())
+            }
+        });
+
+        pool.rescan().unwrap();
+        assert!(pool.rescan().unwrap().is_empty());
+
+        assert!(cards.next().await.is_some());
+        // リーダーがなくなっても再スキャンを待ち続ける
+        assert!(cards.next().now_or_never().is_none());
+        assert!(pool.readers().is_empty());
+        assert_eq!(pool.rescan().unwrap(), vec![reader("1-1")]);
+    }
+
+    #[tokio::test]
+    async fn failed_reader_is_dropped_and_can_be_rescanned() {
+        let scans = Cell::new(0);
+        let metrics = PrometheusMetrics::new();
+        let (pool, mut cards) = ReaderPool::new(metrics.clone(), |_| {
+            scans.set(scans.get() + 1);
+            // 1回目のリーダーはエラーを返し、その後のカードは読まれない
+            let stream: CardStream = if scans.get() == 1 {
+                Box::pin(stream::iter([
+                    Err(anyhow::anyhow!("usb transfer failed")),
+                    Ok(card(reader("1-1"))),
+                ]))
+            } else {
+                Box::pin(stream::iter([Ok(card(reader("1-1")))]))
+            };
+            Ok(vec![(reader("1-1"), stream)])
+        });
+
+        pool.rescan().unwrap();
+        assert!(metrics.encode().contains("room_manager_readers_running 1"));
+        assert!(cards.next().now_or_never().is_none());
+        assert!(pool.readers().is_empty());
+        assert!(metrics.encode().contains("room_manager_readers_running 0"));
+
+        assert_eq!(pool.rescan().unwrap(), vec![reader("1-1")]);
+        assert_eq!(cards.next().await.unwrap().reader, reader("1-1"));
+    }
+
+    #[tokio::test]
+    async fn cards_end_once_pool_and_readers_are_gone() {
+        let (pool, mut cards) = ReaderPool::new(PrometheusMetrics::new(), |_| {
+            let stream: CardStream = Box::pin(stream::iter([Ok(card(reader("1-1")))]));
+            Ok(vec![(reader("1-1"), stream)])
+        });
+
+        pool.rescan().unwrap();
+        drop(pool);
+        assert!(cards.next().await.is_some());
+        assert!(cards.next().await.is_none());
+    }
+}
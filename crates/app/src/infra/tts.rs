@@ -0,0 +1,337 @@
+use std::{
+    fs,
+    io::{Read as _, Write as _},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use tracing::{debug, info};
+
+// 長すぎる名前で合成に時間がかからないよう、読み上げる文字数を制限する
+const MAX_TEXT_CHARS: usize = 64;
+
+// 止まった open_jtalk で再生中のまま後続の音声が鳴らなくならないよう、待つ時間を制限する
+const SYNTHESIS_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Offline text-to-speech engine producing WAV data.
+pub trait Synthesizer {
+    /// Synthesizes `text` into a WAV file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the engine is unavailable or fails.
+    fn synthesize(&self, text: &str) -> anyhow::Result<Vec<u8>>;
+}
+
+/// `open_jtalk` run as a subprocess. The text is passed on stdin, so it is
+/// never interpreted by a shell. A run that does not finish within the
+/// timeout is killed and reported as a failure.
+#[derive(Debug)]
+pub struct OpenJTalk {
+    command: PathBuf,
+    dictionary: PathBuf,
+    voice: PathBuf,
+    work_dir: PathBuf,
+    timeout: Duration,
+}
+
+impl OpenJTalk {
+    pub fn new(command: PathBuf, dictionary: PathBuf, voice: PathBuf, work_dir: PathBuf) -> Self {
+        Self {
+            command,
+            dictionary,
+            voice,
+            work_dir,
+            timeout: SYNTHESIS_TIMEOUT,
+        }
+    }
+
+    #[cfg(test)]
+    fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Waits for `child` to exit, killing it once the timeout has passed.
+    fn wait(&self, child: &mut Child) -> anyhow::Result<ExitStatus> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status);
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                anyhow::bail!(
+                    "{} did not finish within {:?}",
+                    self.command.display(),
+                    self.timeout
+                );
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Synthesizer for OpenJTalk {
+    fn synthesize(&self, text: &str) -> anyhow::Result<Vec<u8>> {
+        fs::create_dir_all(&self.work_dir)
+            .with_context(|| format!("failed to create {}", self.work_dir.display()))?;
+        let output = unique_path(&self.work_dir, "synthesizing", "wav");
+
+        let mut child = Command::new(&self.command)
+            .arg("-x")
+            .arg(&self.dictionary)
+            .arg("-m")
+            .arg(&self.voice)
+            .arg("-ow")
+            .arg(&output)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start {}", self.command.display()))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+
+        let status = match self.wait(&mut child) {
+            Ok(status) => status,
+            Err(error) => {
+                let _ = fs::remove_file(&output);
+                return Err(error);
+            }
+        };
+        if !status.success() {
+            let mut stderr = String::new();
+            if let Some(mut pipe) = child.stderr.take() {
+                let _ = pipe.read_to_string(&mut stderr);
+            }
+            anyhow::bail!(
+                "{} exited with {status}: {}",
+                self.command.display(),
+                stderr.trim()
+            );
+        }
+
+        let wav = fs::read(&output).with_context(|| format!("failed to read {}", output.display()));
+        let _ = fs::remove_file(&output);
+        wav
+    }
+}
+
+/// Keeps synthesized utterances on disk, so each user's greeting is only
+/// synthesized once.
+#[derive(Debug)]
+pub struct CachedSynthesizer<S> {
+    inner: S,
+    cache_dir: PathBuf,
+}
+
+impl<S: Synthesizer> CachedSynthesizer<S> {
+    pub fn new(inner: S, cache_dir: PathBuf) -> Self {
+        info!(cache_dir = %cache_dir.display(), "initialized tts cache");
+        Self { inner, cache_dir }
+    }
+}
+
+impl<S: Synthesizer> Synthesizer for CachedSynthesizer<S> {
+    fn synthesize(&self, text: &str) -> anyhow::Result<Vec<u8>> {
+        let text: String = text.chars().take(MAX_TEXT_CHARS).collect();
+        let path = self.cache_dir.join(format!("{:016x}.wav", fnv1a(&text)));
+        if let Ok(wav) = fs::read(&path) {
+            debug!(path = %path.display(), "tts cache hit");
+            return Ok(wav);
+        }
+
+        let wav = self.inner.synthesize(&text)?;
+        // キャッシュに書けなくても読み上げはできるので失敗は無視する。
+        // 書きかけのファイルを読まないよう、一時ファイルに書いてから置き換える
+        if fs::create_dir_all(&self.cache_dir).is_ok() {
+            let partial = unique_path(&self.cache_dir, "partial", "tmp");
+            if fs::write(&partial, &wav).is_err() || fs::rename(&partial, &path).is_err() {
+                let _ = fs::remove_file(&partial);
+            }
+        }
+
+        Ok(wav)
+    }
+}
+
+/// A file name in `dir` that no other synthesis, in this process or another,
+/// is using.
+fn unique_path(dir: &Path, prefix: &str, extension: &str) -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    dir.join(format!("{prefix}-{}-{n}.{extension}", std::process::id()))
+}
+
+// 再起動やビルドをまたいでも同じファイル名になるよう、固定のハッシュを使う
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+
+    use super::{CachedSynthesizer, OpenJTalk, Synthesizer, unique_path};
+    use crate::infra::fixtures::TempPath;
+
+    #[derive(Default)]
+    struct CountingSynthesizer {
+        calls: Cell<u32>,
+        fail: bool,
+    }
+
+    impl Synthesizer for &CountingSynthesizer {
+        fn synthesize(&self, text: &str) -> anyhow::Result<Vec<u8>> {
+            self.calls.set(self.calls.get() + 1);
+            anyhow::ensure!(!self.fail, "engine missing");
+            Ok(format!("RIFF{text}").into_bytes())
+        }
+    }
+
+    fn cache_dir(name: &str) -> TempPath {
+        TempPath::new(&format!("{name}-tts-cache"))
+    }
+
+    #[test]
+    fn cache_synthesizes_each_text_once() {
+        let inner = CountingSynthesizer::default();
+        let dir = cache_dir("once");
+        let tts = CachedSynthesizer::new(&inner, dir.to_path_buf());
+
+        let first = tts.synthesize("こんにちは、山田さん").unwrap();
+        let second = tts.synthesize("こんにちは、山田さん").unwrap();
+        tts.synthesize("こんにちは、佐藤さん").unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(inner.calls.get(), 2);
+    }
+
+    #[test]
+    fn failures_are_not_cached() {
+        let inner = CountingSynthesizer {
+            fail: true,
+            ..CountingSynthesizer::default()
+        };
+        let dir = cache_dir("failure");
+        let tts = CachedSynthesizer::new(&inner, dir.to_path_buf());
+
+        tts.synthesize("こんばんは").unwrap_err();
+        tts.synthesize("こんばんは").unwrap_err();
+
+        assert_eq!(inner.calls.get(), 2);
+    }
+
+    #[test]
+    fn long_text_is_truncated() {
+        let inner = CountingSynthesizer::default();
+        let dir = cache_dir("truncate");
+        let tts = CachedSynthesizer::new(&inner, dir.to_path_buf());
+
+        let wav = tts.synthesize(&"あ".repeat(200)).unwrap();
+
+        assert_eq!(wav, format!("RIFF{}", "あ".repeat(64)).into_bytes());
+    }
+
+    #[test]
+    fn cache_leaves_only_complete_files() {
+        let inner = CountingSynthesizer::default();
+        let dir = cache_dir("complete");
+        let tts = CachedSynthesizer::new(&inner, dir.to_path_buf());
+
+        tts.synthesize("こんにちは").unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].extension().unwrap(), "wav");
+        assert_eq!(
+            std::fs::read(&files[0]).unwrap(),
+            "RIFFこんにちは".as_bytes()
+        );
+    }
+
+    #[test]
+    fn work_files_are_unique() {
+        let dir = PathBuf::from("work");
+        assert_ne!(
+            unique_path(&dir, "synthesizing", "wav"),
+            unique_path(&dir, "synthesizing", "wav")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn open_jtalk_reads_text_from_stdin_and_writes_wav() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        // -ow で指定されたファイルに標準入力をそのまま書く偽の open_jtalk
+        let dir = cache_dir("open-jtalk");
+        std::fs::create_dir_all(&dir).unwrap();
+        let command = dir.join("open_jtalk");
+        std::fs::write(
+            &command,
+            "#!/bin/sh\nwhile [ $# -gt 0 ]; do [ \"$1\" = -ow ] && out=$2; shift; done\n{ printf RIFF; cat; } > \"$out\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&command, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let tts = OpenJTalk::new(command, "dic".into(), "voice".into(), dir.join("work"));
+
+        assert_eq!(
+            tts.synthesize("おはようございます").unwrap(),
+            "RIFFおはようございます".as_bytes()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn open_jtalk_is_killed_when_it_hangs() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        // 辞書の読み込みなどで止まった open_jtalk の代わり
+        let dir = cache_dir("hung-engine");
+        std::fs::create_dir_all(&dir).unwrap();
+        let command = dir.join("open_jtalk");
+        std::fs::write(&command, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        std::fs::set_permissions(&command, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let tts = OpenJTalk::new(command, "dic".into(), "voice".into(), dir.join("work"))
+            .with_timeout(Duration::from_millis(200));
+
+        let started = Instant::now();
+        let error = tts.synthesize("こんにちは").unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(error.to_string().contains("did not finish"), "{error:#}");
+    }
+
+    #[test]
+    fn open_jtalk_reports_missing_engine() {
+        let dir = cache_dir("missing-engine");
+        let tts = OpenJTalk::new(
+            dir.join("open_jtalk"),
+            "dic".into(),
+            "voice".into(),
+            dir.join("work"),
+        );
+
+        tts.synthesize("こんにちは").unwrap_err();
+    }
+}
@@ -1,69 +1,254 @@
 #![warn(clippy::all, clippy::pedantic)]
+mod calibration;
 mod config;
 mod infra;
 mod runtime;
+mod telemetry;
 
+use std::time::Duration;
+
+use anyhow::Context as _;
+use chrono::Local;
 use clap::Parser;
-use config::Config;
-use futures_util::StreamExt as _;
-use futures_util::stream::select_all;
-use infra::{HttpCardApi, SystemClock};
-use room_manager::app::TouchCardUseCase;
-use runtime::{new_sound_player, spawn_door_lock, spawn_readers};
-use tracing::{error, info};
+use config::{Command, Config};
+use futures_util::TryFutureExt as _;
+use infra::{
+    HttpCardApi, PrometheusMetrics, ReaderPool, SystemClock,
+    admin::{self, AdminService, TerminalLog, TouchRecord},
+    reader_pool::Cards,
+};
+use room_manager::{
+    app::{
+        DoorMonitorUseCase, HeartbeatUseCase, LockButtonUseCase, LockModeOverride,
+        LockScheduleUseCase, TerminalHealth, TouchCardUseCase,
+    },
+    domain::{
+        Card, CardApi, Clock, DoorLock, Indicator, IndicatorPattern, Metrics, SoundPlayer,
+        StatusDisplay,
+    },
+};
+use runtime::{
+    new_calibration_servo, new_sound_player, spawn_display, spawn_door_lock, spawn_door_sensor,
+    spawn_indicator, spawn_lock_button, spawn_readers,
+};
+use telemetry::Telemetry;
+use tokio::sync::mpsc;
+use tracing::{Instrument as _, Span, error, field, info, info_span};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt()
-        .with_file(true)
-        .with_line_number(true)
-        .init();
-
-    let config = Config::parse();
-    info!(
-        version = env!("CARGO_PKG_VERSION"),
-        api_path = %config.api_path,
-        "starting room-manager app"
-    );
+    let mut config = Config::parse();
+    let telemetry = telemetry::init(&config.tracing)?;
 
-    let api = HttpCardApi::new(config.api_path, config.api_token)?;
-    info!("initialized api client");
+    if let Some(Command::CalibrateServo { env_file }) = &config.command {
+        info!(env_file = %env_file.display(), "starting servo calibration");
+        let servo = new_calibration_servo(&config.door_lock)?;
+        return calibration::calibrate(servo, config.door_lock.servo_angles(), env_file);
+    }
 
-    let player = new_sound_player()?;
-    info!("initialized sound player");
+    let api_path = config.api_path.take().context("--api-path is required")?;
+    let api_token = config.api_token.take().context("--api-token is required")?;
+    info!(version = env!("CARGO_PKG_VERSION"), %api_path, "starting room-manager app");
+
+    let metrics = PrometheusMetrics::new();
+    let admin_commands = spawn_servers(&config, &metrics, &telemetry).await?;
+
+    let api = HttpCardApi::new(api_path, api_token, metrics.clone())?;
+    info!("initialized api client");
 
     let clock = SystemClock::new();
     info!("initialized system clock");
 
-    let readers = spawn_readers()?;
-    let mut readers = select_all(readers);
+    let player = new_sound_player(&config.sound, &clock, &metrics)?;
+    info!("initialized sound player");
+
+    let (reader_pool, cards) = ReaderPool::new(metrics.clone(), |running| {
+        spawn_readers(
+            &config.reader_roles,
+            &config.reader_labels,
+            &metrics,
+            running,
+        )
+    });
+    reader_pool.rescan()?;
     info!("spawned card readers");
 
-    let door_lock = spawn_door_lock().await?;
+    let door_sensor = spawn_door_sensor(&config.door_lock)?;
+    let door_lock = spawn_door_lock(&config.door_lock, door_sensor.clone(), &metrics).await?;
     info!("spawned door lock");
 
-    let touch_card_use_case = TouchCardUseCase::new(api, player, clock, door_lock);
+    let lock_button = spawn_lock_button(&config.door_lock)?;
+    let indicator = spawn_indicator(&config.indicator)?;
+    let display = spawn_display(&config.display)?;
+    if door_lock.state().await.is_none() {
+        // 起動時に錠を動かせなくても再起動を繰り返さず、LED で知らせて動き続ける
+        indicator.show(IndicatorPattern::Error);
+    }
+
+    let lock_policy = config.lock_policy.policy()?;
+    info!(mode = ?config.lock_policy.lock_mode, "loaded lock policy");
+
+    let lock_mode = LockModeOverride::default();
+    let touch_card_use_case = TouchCardUseCase::new(&api, &player, &clock, &door_lock)
+        .with_lock_policy(lock_policy.clone())
+        .with_lock_mode_override(lock_mode.clone())
+        .with_greetings(config.sound.load_greetings()?)
+        .with_indicator(&indicator)
+        .with_display(&display)
+        .with_metrics(&metrics);
+    let lock_schedule = LockScheduleUseCase::new(&clock, &door_lock, lock_policy)
+        .with_lock_mode_override(lock_mode)
+        .run();
+    let sound_schedule = player.run();
+
+    let left_open_threshold = Duration::from_secs(config.door_lock.door_left_open_secs);
+    let report_door_events = config.door_lock.report_door_events;
+    let door_monitor = run_or_pend(door_sensor.map(|door_sensor| {
+        info!("starting door monitor");
+        DoorMonitorUseCase::new(
+            door_sensor,
+            &api,
+            &player,
+            left_open_threshold,
+            report_door_events,
+        )
+        .run()
+        .unwrap_or_else(|error| error!(error = %error, "door monitor stopped"))
+    }));
+
+    let report_button_events = config.door_lock.report_button_events;
+    let lock_button_loop = run_or_pend(lock_button.map(|lock_button| {
+        info!("starting lock button loop");
+        LockButtonUseCase::new(lock_button, &door_lock, &api, report_button_events)
+            .run()
+            .unwrap_or_else(|error| error!(error = %error, "lock button loop stopped"))
+    }));
+
+    let log = TerminalLog::default();
+    let health = || TerminalHealth {
+        readers: reader_pool.readers().len(),
+        last_error: log.last_error(),
+    };
+    let heartbeat = run_or_pend((config.heartbeat_secs > 0).then(|| {
+        info!("starting heartbeat");
+        HeartbeatUseCase::new(&api, &door_lock, &player, health)
+            .with_interval(Duration::from_secs(config.heartbeat_secs))
+            .run()
+    }));
+    let admin_loop = run_or_pend(admin_commands.map(|commands| {
+        info!("starting admin service");
+        AdminService::new(&api, &door_lock, &player, &reader_pool, &log).run(commands)
+    }));
+
+    tokio::select! {
+        () = read_cards(cards, &touch_card_use_case, &log) => Ok(()),
+        () = heartbeat => unreachable!("heartbeat never completes"),
+        () = admin_loop => unreachable!("admin service never completes"),
+        () = door_monitor => unreachable!("door monitor never completes"),
+        () = lock_button_loop => unreachable!("lock button loop never completes"),
+        () = lock_schedule => unreachable!("lock schedule never completes"),
+        () = sound_schedule => unreachable!("sound scheduler never completes"),
+    }
+}
+
+/// Serves metrics and the admin API where configured, returning the admin
+/// actions to carry out.
+async fn spawn_servers(
+    config: &Config,
+    metrics: &PrometheusMetrics,
+    telemetry: &Telemetry,
+) -> anyhow::Result<Option<mpsc::Receiver<admin::Command>>> {
+    if let Some(addr) = config.metrics_addr {
+        infra::metrics::spawn_server(addr, metrics.clone()).await?;
+    }
+    let Some(addr) = config.admin.admin_addr else {
+        return Ok(None);
+    };
+    let token = config.admin.admin_token.clone();
+    let commands = admin::spawn_server(addr, token, telemetry.log_filter()).await?;
+
+    Ok(Some(commands))
+}
+
+/// Runs `task` if there is one, then never completes, so that a disabled
+/// feature does not end the `select!` in `main`.
+async fn run_or_pend(task: Option<impl Future<Output = ()>>) {
+    if let Some(task) = task {
+        task.await;
+    }
+    std::future::pending::<()>().await;
+}
 
+/// Handles cards from every reader, including readers added by a rescan.
+async fn read_cards<A, P, C, D, I, S, M>(
+    mut cards: Cards,
+    use_case: &TouchCardUseCase<A, P, C, D, I, S, M>,
+    log: &TerminalLog,
+) where
+    A: CardApi,
+    P: SoundPlayer,
+    C: Clock,
+    D: DoorLock,
+    I: Indicator,
+    S: StatusDisplay,
+    M: Metrics,
+{
     info!("starting card reader loop");
-    while let Some(card) = readers.next().await {
-        let card = card?;
+    while let Some(card) = cards.next().await {
+        process_card(use_case, &card, log).await;
+    }
+
+    info!("card reader loop finished");
+}
+
+async fn process_card<A, P, C, D, I, S, M>(
+    use_case: &TouchCardUseCase<A, P, C, D, I, S, M>,
+    card: &Card,
+    log: &TerminalLog,
+) where
+    A: CardApi,
+    P: SoundPlayer,
+    C: Clock,
+    D: DoorLock,
+    I: Indicator,
+    S: StatusDisplay,
+    M: Metrics,
+{
+    let span = info_span!(
+        "touch",
+        reader = %card.reader,
+        card_kind = card.kind().as_str(),
+        queued_ms = card.detected_at.elapsed().as_millis(),
+        latency_ms = field::Empty,
+        result = field::Empty,
+    );
+    async {
         info!(
             idm = %card.idm,
             student_id = ?card.student_id,
             balance = ?card.balance,
+            intent = ?card.intent,
             "received card event"
         );
-        if let Err(error) = touch_card_use_case.execute(&card).await {
-            error!(
-                idm = %card.idm,
-                student_id = ?card.student_id,
-                balance = ?card.balance,
-                error = %error,
-                "failed to process card event"
-            );
-        }
+        let outcome = match use_case.execute(card).await {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                error!(
+                    idm = %card.idm,
+                    student_id = ?card.student_id,
+                    balance = ?card.balance,
+                    error = %error,
+                    "failed to process card event"
+                );
+                log.record_error(&error);
+                error.outcome()
+            }
+        };
+        Span::current()
+            .record("result", outcome.as_str())
+            .record("latency_ms", card.detected_at.elapsed().as_millis());
+        log.record_touch(TouchRecord::new(card, outcome, Local::now()));
     }
-
-    info!("card reader loop finished");
-    Ok(())
+    .instrument(span)
+    .await;
 }
@@ -23,10 +23,16 @@ mod raspi;
     target_os = "linux",
     any(target_arch = "arm", target_arch = "aarch64")
 )))]
-pub use portable::{new_sound_player, spawn_door_lock, spawn_readers};
+pub use portable::{
+    new_calibration_servo, new_sound_player, spawn_display, spawn_door_lock, spawn_door_sensor,
+    spawn_indicator, spawn_lock_button, spawn_readers,
+};
 #[cfg(all(
     feature = "raspi-runtime",
     target_os = "linux",
     any(target_arch = "arm", target_arch = "aarch64")
 ))]
-pub use raspi::{new_sound_player, spawn_door_lock, spawn_readers};
+pub use raspi::{
+    new_calibration_servo, new_sound_player, spawn_display, spawn_door_lock, spawn_door_sensor,
+    spawn_indicator, spawn_lock_button, spawn_readers,
+};
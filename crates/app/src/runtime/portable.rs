@@ -1,8 +1,24 @@
+use std::time::Duration;
+
 use futures_util::stream;
-use room_manager::domain::{Card, DoorLock, SoundEvent, SoundPlayer};
+use room_manager::{
+    app::SoundScheduler,
+    domain::{
+        AudioOutput, ButtonPress, Card, Clock, DoorLock, DoorSensor, DoorState, LockButton,
+        LockError, Metrics, NoopIndicator, ReaderId, SoundError, SoundRequest,
+    },
+};
 use tracing::warn;
 
-use crate::runtime::CardStream;
+use crate::{
+    calibration::CalibrationServo,
+    config::{
+        DisplayConfig, DisplayKind, DoorLockConfig, IndicatorConfig, ReaderLabel, ReaderRole,
+        SoundConfig,
+    },
+    infra::{PrometheusMetrics, TerminalPanel, TextDisplay},
+    runtime::CardStream,
+};
 
 pub struct NoopSoundPlayer;
 
@@ -15,13 +31,17 @@ impl NoopSoundPlayer {
     }
 }
 
-impl SoundPlayer for NoopSoundPlayer {
-    fn play(&self, sound: SoundEvent) -> anyhow::Result<()> {
-        warn!("Ignoring sound event on noop runtime: {:?}", sound);
+impl AudioOutput for NoopSoundPlayer {
+    fn start(&self, request: &SoundRequest, _gain: f32) -> Result<(), SoundError> {
+        warn!("Ignoring sound event on noop runtime: {:?}", request);
         Ok(())
     }
 
-    fn reset(&self) {}
+    fn stop(&self) {}
+
+    fn is_playing(&self) -> bool {
+        false
+    }
 }
 
 pub struct NoopDoorLock;
@@ -36,22 +56,131 @@ impl NoopDoorLock {
 }
 
 impl DoorLock for NoopDoorLock {
-    async fn unlock(&self) -> anyhow::Result<()> {
+    async fn unlock(&self) -> Result<(), LockError> {
         warn!("Ignoring unlock request on noop runtime");
         Ok(())
     }
+
+    async fn lock(&self) -> Result<(), LockError> {
+        warn!("Ignoring lock request on noop runtime");
+        Ok(())
+    }
+
+    async fn hold_open(&self) -> Result<(), LockError> {
+        warn!("Ignoring hold-open request on noop runtime");
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct NoopDoorSensor;
+
+impl DoorSensor for NoopDoorSensor {
+    fn state(&self) -> DoorState {
+        DoorState::Closed
+    }
+
+    async fn wait_for_change(&mut self) -> anyhow::Result<DoorState> {
+        std::future::pending().await
+    }
+}
+
+pub struct NoopLockButton;
+
+impl LockButton for NoopLockButton {
+    async fn next_press(&mut self) -> anyhow::Result<ButtonPress> {
+        std::future::pending().await
+    }
+}
+
+pub struct NoopServo;
+
+impl CalibrationServo for NoopServo {
+    fn set_angle(&mut self, angle: u16) -> anyhow::Result<()> {
+        warn!(angle, "Ignoring servo angle on noop runtime");
+        Ok(())
+    }
+
+    fn release(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn new_sound_player<C: Clock, M: Metrics>(
+    config: &SoundConfig,
+    clock: C,
+    metrics: M,
+) -> anyhow::Result<SoundScheduler<NoopSoundPlayer, C, M>> {
+    Ok(SoundScheduler::new(NoopSoundPlayer::new()?, clock)
+        .with_volume(config.volume_policy())
+        .with_metrics(metrics))
+}
+
+#[allow(clippy::unnecessary_wraps)]
+pub fn spawn_door_sensor(_config: &DoorLockConfig) -> anyhow::Result<Option<NoopDoorSensor>> {
+    warn!("Running without door sensor on this platform");
+    Ok(None)
+}
+
+#[allow(clippy::unnecessary_wraps)]
+pub fn spawn_indicator(_config: &IndicatorConfig) -> anyhow::Result<NoopIndicator> {
+    warn!("Running without status LED or buzzer on this platform");
+    Ok(NoopIndicator)
+}
+
+#[allow(clippy::unnecessary_wraps)]
+pub fn spawn_display(config: &DisplayConfig) -> anyhow::Result<Option<TextDisplay>> {
+    let hold = Duration::from_secs(config.display_hold_secs);
+    match config.kind {
+        DisplayKind::None => Ok(None),
+        DisplayKind::Terminal => {
+            let panel = TerminalPanel::new(config.lcd_size.columns, config.lcd_size.rows);
+            Ok(Some(TextDisplay::spawn(panel, hold)))
+        }
+        DisplayKind::Ssd1306 | DisplayKind::Hd44780 => {
+            warn!("Running without i2c display on this platform");
+            Ok(None)
+        }
+    }
 }
 
-pub fn new_sound_player() -> anyhow::Result<NoopSoundPlayer> {
-    NoopSoundPlayer::new()
+#[allow(clippy::unnecessary_wraps)]
+pub fn spawn_lock_button(_config: &DoorLockConfig) -> anyhow::Result<Option<NoopLockButton>> {
+    warn!("Running without lock button on this platform");
+    Ok(None)
 }
 
-pub async fn spawn_door_lock() -> anyhow::Result<NoopDoorLock> {
+pub async fn spawn_door_lock(
+    _config: &DoorLockConfig,
+    _door_sensor: Option<NoopDoorSensor>,
+    _metrics: &PrometheusMetrics,
+) -> anyhow::Result<NoopDoorLock> {
     NoopDoorLock::spawn().await
 }
 
 #[allow(clippy::unnecessary_wraps)]
-pub fn spawn_readers() -> anyhow::Result<Vec<CardStream>> {
+pub fn new_calibration_servo(_config: &DoorLockConfig) -> anyhow::Result<NoopServo> {
+    warn!("Running servo calibration with noop servo on this platform");
+    Ok(NoopServo)
+}
+
+#[allow(clippy::unnecessary_wraps)]
+pub fn spawn_readers(
+    _roles: &[ReaderRole],
+    _labels: &[ReaderLabel],
+    _metrics: &PrometheusMetrics,
+    running: &[ReaderId],
+) -> anyhow::Result<Vec<(ReaderId, CardStream)>> {
+    if !running.is_empty() {
+        return Ok(Vec::new());
+    }
     warn!("Running without Pasori readers on this platform; no card events will be produced");
-    Ok(vec![Box::pin(stream::pending::<anyhow::Result<Card>>())])
+    let reader = ReaderId {
+        usb_path: "noop".to_string(),
+        label: None,
+    };
+    Ok(vec![(
+        reader,
+        Box::pin(stream::pending::<anyhow::Result<Card>>()),
+    )])
 }
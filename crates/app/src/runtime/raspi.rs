@@ -1,24 +1,190 @@
-use anyhow::bail;
+use std::time::Duration;
+
+use anyhow::{Context as _, bail};
 use futures_util::StreamExt as _;
-use pasori::rusb::{Context as RusbContext, UsbContext};
+use pasori::rusb::{Context as RusbContext, Device as RusbDevice, UsbContext};
+use room_manager::{
+    app::SoundScheduler,
+    domain::{Clock, LockState, Metrics, ReaderId, SoundEvent, SoundPlayer as _, TouchIntent},
+};
+use tracing::{error, info};
 
 use crate::{
-    infra::{GpioDoorLock, PasoriReader, RodioPlayer},
+    config::{
+        BootLockPolicy, DisplayConfig, DisplayKind, DoorLockConfig, DoorLockKind, IndicatorConfig,
+        LockFeedbackKind, ReaderLabel, ReaderRole, SoundConfig,
+    },
+    infra::{
+        Buzzer, CachedSynthesizer, FileLockStateStore, GpioActuator, GpioButton, GpioDoorLock,
+        GpioDoorSensor, GpioIndicator, Hd44780, LockFeedback, OpenJTalk, PasoriReader,
+        PrometheusMetrics, RelayActuator, RgbLed, RodioPlayer, ServoActuator, SoundPack, Ssd1306,
+        TerminalPanel, TextDisplay, gpio, i2c,
+    },
     runtime::CardStream,
 };
 
 const VENDOR_ID: u16 = 0x054c;
 const PRODUCT_ID: u16 = 0x06c3;
 
-pub fn new_sound_player() -> anyhow::Result<RodioPlayer> {
-    RodioPlayer::new()
+pub fn new_sound_player<C: Clock, M: Metrics>(
+    config: &SoundConfig,
+    clock: C,
+    metrics: M,
+) -> anyhow::Result<SoundScheduler<RodioPlayer, C, M>> {
+    let sound_pack = match &config.sound_pack {
+        Some(dir) => SoundPack::load(dir, RodioPlayer::decodable).unwrap_or_else(|error| {
+            error!(error = %format!("{error:#}"), "failed to load sound pack; using embedded sounds");
+            SoundPack::embedded()
+        }),
+        None => SoundPack::embedded(),
+    };
+
+    let tts = config.tts.then(|| {
+        let open_jtalk = OpenJTalk::new(
+            config.tts_command.clone(),
+            config.tts_dictionary.clone(),
+            config.tts_voice.clone(),
+            std::env::temp_dir().join("room-manager-tts"),
+        );
+        CachedSynthesizer::new(open_jtalk, config.tts_cache_dir.clone())
+    });
+
+    let player = SoundScheduler::new(RodioPlayer::new(sound_pack, tts)?, clock)
+        .with_volume(config.volume_policy())
+        .with_metrics(metrics);
+    player.play(SoundEvent::Boot)?;
+    Ok(player)
+}
+
+pub fn spawn_door_sensor(config: &DoorLockConfig) -> anyhow::Result<Option<GpioDoorSensor>> {
+    config
+        .door_sensor_pin
+        .map(|pin| Ok(GpioDoorSensor::spawn(gpio::input_pullup(pin)?)))
+        .transpose()
+}
+
+pub fn spawn_lock_button(config: &DoorLockConfig) -> anyhow::Result<Option<GpioButton>> {
+    let long_press = Duration::from_millis(config.button_long_press_ms);
+    config
+        .button_pin
+        .map(|pin| Ok(GpioButton::spawn(gpio::input_pullup(pin)?, long_press)))
+        .transpose()
+}
+
+pub fn spawn_indicator(config: &IndicatorConfig) -> anyhow::Result<GpioIndicator> {
+    // 消灯状態で初期化する
+    let off = config.led_active_low;
+    let led = config
+        .led_pins
+        .map(|pins| {
+            anyhow::Ok(RgbLed {
+                red: gpio::output(pins.red, off)?,
+                green: gpio::output(pins.green, off)?,
+                blue: gpio::output(pins.blue, off)?,
+                active_low: config.led_active_low,
+            })
+        })
+        .transpose()?;
+    let buzzer = config
+        .buzzer_pin
+        .map(|pin| anyhow::Ok(Buzzer::new(gpio::output(pin, false)?)))
+        .transpose()?;
+
+    Ok(GpioIndicator::spawn(led, buzzer))
+}
+
+pub fn spawn_display(config: &DisplayConfig) -> anyhow::Result<Option<TextDisplay>> {
+    let hold = Duration::from_secs(config.display_hold_secs);
+    let open = |default_address| {
+        i2c::open(
+            config.display_i2c_bus,
+            config.display_i2c_address.unwrap_or(default_address),
+        )
+    };
+    let display = match config.kind {
+        DisplayKind::None => return Ok(None),
+        DisplayKind::Terminal => TextDisplay::spawn(
+            TerminalPanel::new(config.lcd_size.columns, config.lcd_size.rows),
+            hold,
+        ),
+        DisplayKind::Ssd1306 => TextDisplay::spawn(Ssd1306::new(open(0x3c)?)?, hold),
+        DisplayKind::Hd44780 => TextDisplay::spawn(
+            Hd44780::new(open(0x27)?, config.lcd_size.columns, config.lcd_size.rows)?,
+            hold,
+        ),
+    };
+
+    Ok(Some(display))
 }
 
-pub async fn spawn_door_lock() -> anyhow::Result<GpioDoorLock> {
-    GpioDoorLock::spawn().await
+pub async fn spawn_door_lock(
+    config: &DoorLockConfig,
+    door_sensor: Option<GpioDoorSensor>,
+    metrics: &PrometheusMetrics,
+) -> anyhow::Result<GpioDoorLock> {
+    let actuator = match config.kind {
+        DoorLockKind::Servo => {
+            let feedback = match config.lock_feedback {
+                LockFeedbackKind::None => None,
+                LockFeedbackKind::LimitSwitch => {
+                    let pin = config
+                        .lock_feedback_pin
+                        .context("--lock-feedback-pin is required for limit switch feedback")?;
+                    Some(LockFeedback::LimitSwitch(gpio::input_pullup(pin)?))
+                }
+                LockFeedbackKind::DoorSensor => {
+                    Some(LockFeedback::DoorSensor(door_sensor.clone().context(
+                        "--door-sensor-pin is required for door sensor feedback",
+                    )?))
+                }
+            };
+            GpioActuator::Servo(ServoActuator::new(
+                gpio::output(config.servo_pin, false)?,
+                config.servo_angles(),
+                feedback,
+            ))
+        }
+        DoorLockKind::Relay => {
+            let pin = config
+                .relay_pin
+                .context("--relay-pin is required for the relay door lock")?;
+            GpioActuator::Relay(RelayActuator::new(
+                gpio::output(pin, config.relay_active_low)?,
+                config.relay_active_low,
+                config.relay_pulse_ms.map(Duration::from_millis),
+                door_sensor.clone(),
+            ))
+        }
+    };
+
+    Ok(GpioDoorLock::spawn(
+        actuator,
+        FileLockStateStore::new(config.lock_state_file.clone()),
+        match config.boot_lock_policy {
+            BootLockPolicy::FailSecure => LockState::Locked,
+            BootLockPolicy::FailSafe => LockState::Unlocked,
+        },
+        usize::from(config.max_lock_cycles_per_minute),
+        door_sensor,
+        metrics.clone(),
+    )
+    .await)
 }
 
-pub fn spawn_readers() -> anyhow::Result<Vec<CardStream>> {
+pub fn new_calibration_servo(config: &DoorLockConfig) -> anyhow::Result<ServoActuator> {
+    Ok(ServoActuator::new(
+        gpio::output(config.servo_pin, false)?,
+        config.servo_angles(),
+        None,
+    ))
+}
+
+pub fn spawn_readers(
+    roles: &[ReaderRole],
+    labels: &[ReaderLabel],
+    metrics: &PrometheusMetrics,
+    running: &[ReaderId],
+) -> anyhow::Result<Vec<(ReaderId, CardStream)>> {
     let readers = RusbContext::new()?
         .devices()?
         .iter()
@@ -29,12 +195,45 @@ pub fn spawn_readers() -> anyhow::Result<Vec<CardStream>> {
 
             dev_desc.vendor_id() == VENDOR_ID && dev_desc.product_id() == PRODUCT_ID
         })
-        .map(|dev| PasoriReader::spawn(dev).map(|reader| reader.into_stream().boxed()))
-        .collect::<Result<Vec<_>, _>>()?;
+        .map(|dev| Ok((usb_path(&dev)?, dev)))
+        .filter(|result: &anyhow::Result<_>| {
+            !matches!(result, Ok((usb_path, _))
+                if running.iter().any(|reader| &reader.usb_path == usb_path))
+        })
+        .map(|result| {
+            let (usb_path, dev) = result?;
+            let intent = roles
+                .iter()
+                .find(|role| role.usb_path == usb_path)
+                .map_or(TouchIntent::Toggle, |role| role.intent);
+            let label = labels
+                .iter()
+                .find(|label| label.usb_path == usb_path)
+                .map(|label| label.label.clone());
+            let reader = ReaderId { usb_path, label };
+            info!(%reader, ?intent, "found pasori reader");
 
-    if readers.is_empty() {
+            let stream = PasoriReader::spawn(dev, reader.clone(), intent, metrics.clone())?
+                .into_stream()
+                .boxed();
+            Ok((reader, stream))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    if readers.is_empty() && running.is_empty() {
         bail!("No Pasori reader found");
     }
 
     Ok(readers)
 }
+
+fn usb_path(dev: &RusbDevice<RusbContext>) -> anyhow::Result<String> {
+    let ports = dev
+        .port_numbers()?
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(".");
+
+    Ok(format!("{}-{ports}", dev.bus_number()))
+}
@@ -0,0 +1,190 @@
+//! Log output and optional OpenTelemetry trace export.
+
+use std::fmt;
+
+use anyhow::Context as _;
+use opentelemetry::{KeyValue, global, trace::TracerProvider as _};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig as _};
+use opentelemetry_sdk::{Resource, propagation::TraceContextPropagator, trace::SdkTracerProvider};
+use rolling_file::{RollingConditionBasic, RollingFileAppender};
+use tracing::error;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{
+    EnvFilter, Registry, layer::SubscriberExt as _, reload, util::SubscriberInitExt as _,
+};
+
+use crate::config::{LogFormat, TracingConfig};
+
+const SERVICE_NAME: &str = "room-manager";
+
+/// Flushes the log file and exported spans when dropped, so keep it alive
+/// until `main` returns.
+pub struct Telemetry {
+    log_filter: LogFilter,
+    provider: Option<SdkTracerProvider>,
+    _file_guard: Option<WorkerGuard>,
+}
+
+impl Telemetry {
+    pub fn log_filter(&self) -> LogFilter {
+        self.log_filter.clone()
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take()
+            && let Err(error) = provider.shutdown()
+        {
+            error!(error = %error, "failed to flush traces");
+        }
+    }
+}
+
+/// The `EnvFilter` of the installed subscriber, replaceable at runtime.
+#[derive(Clone)]
+pub struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+}
+
+impl fmt::Debug for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LogFilter").field(&self.current()).finish()
+    }
+}
+
+impl LogFilter {
+    /// Returns the filter layer to install and a handle to change it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `directives` is not a valid `EnvFilter`.
+    pub fn new(directives: &str) -> anyhow::Result<(reload::Layer<EnvFilter, Registry>, Self)> {
+        let filter = EnvFilter::try_new(directives)
+            .with_context(|| format!("invalid log filter: {directives}"))?;
+        let (layer, handle) = reload::Layer::new(filter);
+        Ok((layer, Self { handle }))
+    }
+
+    pub fn current(&self) -> String {
+        self.handle
+            .with_current(ToString::to_string)
+            .unwrap_or_default()
+    }
+
+    /// Replaces the filter, e.g. with `debug` while chasing a field problem.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `directives` is not a valid `EnvFilter`, leaving
+    /// the current filter in place.
+    pub fn set(&self, directives: &str) -> anyhow::Result<()> {
+        let filter = EnvFilter::try_new(directives)
+            .with_context(|| format!("invalid log filter: {directives}"))?;
+        self.handle.reload(filter)?;
+        Ok(())
+    }
+}
+
+/// Installs the global subscriber. With `--otlp-endpoint`, spans are also
+/// exported over OTLP/HTTP and API requests carry a W3C `traceparent`.
+///
+/// # Errors
+///
+/// Returns an error if the log filter is invalid, or if journald, the log
+/// file or the OTLP exporter cannot be set up.
+pub fn init(config: &TracingConfig) -> anyhow::Result<Telemetry> {
+    let (filter, log_filter) = LogFilter::new(&config.log_filter)?;
+
+    let text = (config.log_format == LogFormat::Text).then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_file(true)
+            .with_line_number(true)
+    });
+    let json = (config.log_format == LogFormat::Json).then(|| {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_file(true)
+            .with_line_number(true)
+    });
+    let journald = (config.log_format == LogFormat::Journald)
+        .then(tracing_journald::layer)
+        .transpose()
+        .context("failed to connect to journald")?;
+
+    let (file, file_guard) = match &config.log_file {
+        Some(path) => {
+            let condition = RollingConditionBasic::new().max_size(config.log_file_max_bytes);
+            let appender = RollingFileAppender::new(path, condition, config.log_file_count)
+                .with_context(|| format!("failed to open log file {}", path.display()))?;
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = tracing_subscriber::fmt::layer()
+                .json()
+                .with_file(true)
+                .with_line_number(true)
+                .with_writer(writer);
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    let provider = config
+        .otlp_endpoint
+        .as_deref()
+        .map(tracer_provider)
+        .transpose()?;
+    let otel = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(text)
+        .with(json)
+        .with(journald)
+        .with(file)
+        .with(otel)
+        .init();
+
+    Ok(Telemetry {
+        log_filter,
+        provider,
+        _file_guard: file_guard,
+    })
+}
+
+fn tracer_provider(endpoint: &str) -> anyhow::Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+        .context("failed to build otlp exporter")?;
+    let resource = Resource::builder()
+        .with_service_name(SERVICE_NAME)
+        .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+        .build();
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_filter_can_be_changed() {
+        let (_layer, log_filter) = LogFilter::new("info").unwrap();
+        assert_eq!(log_filter.current(), "info");
+
+        log_filter.set("room_manager=debug").unwrap();
+        assert_eq!(log_filter.current(), "room_manager=debug");
+
+        // 不正な指定では元のフィルタのまま
+        assert!(log_filter.set("room_manager=loud").is_err());
+        assert_eq!(log_filter.current(), "room_manager=debug");
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::{Arc, Mutex};
+
+use crate::domain::{DisplayScreen, StatusDisplay};
+
+/// 表示された画面を記録するディスプレイ
+#[derive(Debug, Clone, Default)]
+pub struct RecordingDisplay {
+    screens: Arc<Mutex<Vec<DisplayScreen>>>,
+}
+
+impl RecordingDisplay {
+    pub fn screens(&self) -> Vec<DisplayScreen> {
+        self.screens.lock().unwrap().clone()
+    }
+}
+
+impl StatusDisplay for RecordingDisplay {
+    fn show(&self, screen: DisplayScreen) {
+        self.screens.lock().unwrap().push(screen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::app::LockPolicy;
+    use crate::domain::{
+        AccessLevel, ApiError, ErrorCode, LockMode, RoomEntryStatus, TouchCardResponse, TouchIntent,
+    };
+    use crate::tests::touch_card::{test_card, use_case_with};
+
+    async fn screens_for(
+        intent: TouchIntent,
+        response: Result<TouchCardResponse, ApiError>,
+        lock_policy: LockPolicy,
+    ) -> Vec<DisplayScreen> {
+        let display = RecordingDisplay::default();
+        let use_case = use_case_with(response, Ok(()))
+            .with_lock_policy(lock_policy)
+            .with_display(display.clone());
+
+        let _ = use_case.execute(&test_card(intent)).await;
+        display.screens()
+    }
+
+    #[tokio::test]
+    async fn test_success_shows_name_and_entries() {
+        let response = TouchCardResponse::Success {
+            status: RoomEntryStatus::Entry,
+            entries: 3,
+            access: AccessLevel::Unlock,
+            display_name: Some("Taro".to_string()),
+            first_entry_today: false,
+            last_entry_at: None,
+            lock_mode: None,
+        };
+        assert_eq!(
+            screens_for(TouchIntent::Toggle, Ok(response), LockPolicy::default()).await,
+            vec![
+                DisplayScreen::Reading,
+                DisplayScreen::Entered {
+                    display_name: Some("Taro".to_string()),
+                    entries: 3,
+                },
+            ]
+        );
+        assert_eq!(
+            screens_for(
+                TouchIntent::Toggle,
+                Ok(TouchCardResponse::success_exit(0)),
+                LockPolicy::default()
+            )
+            .await,
+            vec![
+                DisplayScreen::Reading,
+                DisplayScreen::Exited {
+                    display_name: None,
+                    entries: 0,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_only_screen() {
+        let response = TouchCardResponse::Success {
+            status: RoomEntryStatus::Entry,
+            entries: 2,
+            access: AccessLevel::RecordOnly,
+            display_name: Some("Taro".to_string()),
+            first_entry_today: false,
+            last_entry_at: None,
+            lock_mode: None,
+        };
+        // 解錠されないので「Welcome」とは出さない
+        assert_eq!(
+            screens_for(TouchIntent::Toggle, Ok(response), LockPolicy::default()).await,
+            vec![
+                DisplayScreen::Reading,
+                DisplayScreen::RecordOnly { entries: 2 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_screens() {
+        assert_eq!(
+            screens_for(
+                TouchIntent::Toggle,
+                Ok(TouchCardResponse::error(
+                    ErrorCode::NfcCardNotRegistered,
+                    "未登録"
+                )),
+                LockPolicy::default()
+            )
+            .await,
+            vec![
+                DisplayScreen::Reading,
+                DisplayScreen::Rejected(ErrorCode::NfcCardNotRegistered),
+            ]
+        );
+        assert_eq!(
+            screens_for(
+                TouchIntent::Toggle,
+                Ok(TouchCardResponse::Error {
+                    error: "未登録".to_string(),
+                    error_code: ErrorCode::NfcCardNotRegistered,
+                    registration_code: Some("0420".to_string()),
+                }),
+                LockPolicy::default()
+            )
+            .await,
+            vec![
+                DisplayScreen::Reading,
+                DisplayScreen::RegisterNfcCard {
+                    code: "0420".to_string()
+                },
+            ]
+        );
+        // 入室専用リーダーで退室になった
+        assert_eq!(
+            screens_for(
+                TouchIntent::Entry,
+                Ok(TouchCardResponse::success_exit(0)),
+                LockPolicy::default()
+            )
+            .await,
+            vec![
+                DisplayScreen::Reading,
+                DisplayScreen::Rejected(ErrorCode::NotEntered),
+            ]
+        );
+        assert_eq!(
+            screens_for(
+                TouchIntent::Toggle,
+                Ok(TouchCardResponse::success_entry(2)),
+                LockPolicy::new(LockMode::Lockdown, Vec::new(), HashSet::default())
+            )
+            .await,
+            vec![
+                DisplayScreen::Reading,
+                DisplayScreen::Lockdown { entries: 2 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_failure_shows_offline() {
+        assert_eq!(
+            screens_for(
+                TouchIntent::Toggle,
+                Err(ApiError::Connection(anyhow::anyhow!("connection refused"))),
+                LockPolicy::default()
+            )
+            .await,
+            vec![DisplayScreen::Reading, DisplayScreen::Offline]
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::sync::{Arc, Mutex};
+
+use crate::domain::{LockActuator, LockState, LockStateStore};
+
+// 駆動されたボルト位置を記録するだけの偽ピン
+#[derive(Clone, Default)]
+pub struct FakeLockPin {
+    moves: Arc<Mutex<Vec<LockState>>>,
+    broken: Arc<Mutex<bool>>,
+}
+
+impl FakeLockPin {
+    pub fn moves(&self) -> Vec<LockState> {
+        self.moves.lock().unwrap().clone()
+    }
+
+    // 壊れている間は動かそうとした記録だけ残して失敗する
+    pub fn set_broken(&self, broken: bool) {
+        *self.broken.lock().unwrap() = broken;
+    }
+
+    fn drive(&self, state: LockState) -> anyhow::Result<()> {
+        self.moves.lock().unwrap().push(state);
+        anyhow::ensure!(!*self.broken.lock().unwrap(), "servo stalled");
+        Ok(())
+    }
+}
+
+impl LockActuator for FakeLockPin {
+    async fn lock(&mut self) -> anyhow::Result<()> {
+        self.drive(LockState::Locked)
+    }
+
+    async fn unlock(&mut self) -> anyhow::Result<()> {
+        self.drive(LockState::Unlocked)
+    }
+}
+
+// メモリ上に状態を保存するストア
+#[derive(Clone, Default)]
+pub struct MemoryLockStateStore {
+    state: Arc<Mutex<Option<LockState>>>,
+}
+
+impl MemoryLockStateStore {
+    pub fn with_state(state: LockState) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(Some(state))),
+        }
+    }
+
+    pub fn state(&self) -> Option<LockState> {
+        *self.state.lock().unwrap()
+    }
+}
+
+impl LockStateStore for MemoryLockStateStore {
+    fn load(&self) -> anyhow::Result<Option<LockState>> {
+        Ok(self.state())
+    }
+
+    fn save(&self, state: LockState) -> anyhow::Result<()> {
+        *self.state.lock().unwrap() = Some(state);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::app::DoorLockController;
+    use crate::domain::LockError;
+
+    #[tokio::test]
+    async fn test_boot_skips_cycle_when_persisted_state_matches() {
+        let pin = FakeLockPin::default();
+        let store = MemoryLockStateStore::with_state(LockState::Locked);
+
+        let controller = DoorLockController::boot(pin.clone(), store, LockState::Locked, 10).await;
+
+        assert_eq!(controller.state(), Some(LockState::Locked));
+        assert!(pin.moves().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_boot_fail_secure_locks_unknown_state() {
+        let pin = FakeLockPin::default();
+        let store = MemoryLockStateStore::default();
+
+        let controller =
+            DoorLockController::boot(pin.clone(), store.clone(), LockState::Locked, 10).await;
+
+        assert_eq!(controller.state(), Some(LockState::Locked));
+        assert_eq!(pin.moves(), vec![LockState::Locked]);
+        assert_eq!(store.state(), Some(LockState::Locked));
+    }
+
+    #[tokio::test]
+    async fn test_boot_fail_safe_unlocks_persisted_locked_state() {
+        let pin = FakeLockPin::default();
+        let store = MemoryLockStateStore::with_state(LockState::Locked);
+
+        let controller =
+            DoorLockController::boot(pin.clone(), store.clone(), LockState::Unlocked, 10).await;
+
+        assert_eq!(controller.state(), Some(LockState::Unlocked));
+        assert_eq!(pin.moves(), vec![LockState::Unlocked]);
+        assert_eq!(store.state(), Some(LockState::Unlocked));
+    }
+
+    #[tokio::test]
+    async fn test_failed_boot_leaves_state_unknown() {
+        // 起動時に動かせなくてもエラーで終了せず、状態不明のまま動き続ける
+        let pin = FakeLockPin::default();
+        pin.set_broken(true);
+        let store = MemoryLockStateStore::with_state(LockState::Unlocked);
+
+        let mut controller =
+            DoorLockController::boot(pin.clone(), store.clone(), LockState::Locked, 10).await;
+
+        assert_eq!(controller.state(), None);
+        assert_eq!(store.state(), Some(LockState::Unlocked));
+
+        // 状態不明なので施錠を省略せずに動かす
+        pin.set_broken(false);
+        controller.lock().await.unwrap();
+        assert_eq!(controller.state(), Some(LockState::Locked));
+        assert_eq!(pin.moves(), vec![LockState::Locked, LockState::Locked]);
+    }
+
+    #[tokio::test]
+    async fn test_lock_is_noop_when_already_locked() {
+        let pin = FakeLockPin::default();
+        let store = MemoryLockStateStore::with_state(LockState::Locked);
+        let mut controller =
+            DoorLockController::boot(pin.clone(), store, LockState::Locked, 10).await;
+
+        controller.lock().await.unwrap();
+
+        assert!(pin.moves().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unlock_is_noop_when_already_unlocked() {
+        let pin = FakeLockPin::default();
+        let store = MemoryLockStateStore::with_state(LockState::Locked);
+        let mut controller =
+            DoorLockController::boot(pin.clone(), store, LockState::Locked, 1).await;
+
+        // 解錠済みなら上限に数えない
+        controller.unlock().await.unwrap();
+        controller.unlock().await.unwrap();
+
+        assert_eq!(pin.moves(), vec![LockState::Unlocked]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_lock_is_never_rate_limited() {
+        let pin = FakeLockPin::default();
+        let store = MemoryLockStateStore::with_state(LockState::Locked);
+        let mut controller =
+            DoorLockController::boot(pin.clone(), store, LockState::Locked, 1).await;
+
+        // 上限 1 回でも解錠の後の施錠は断らない
+        controller.unlock().await.unwrap();
+        controller.lock().await.unwrap();
+
+        assert_eq!(controller.state(), Some(LockState::Locked));
+        assert_eq!(pin.moves(), vec![LockState::Unlocked, LockState::Locked]);
+    }
+
+    #[tokio::test]
+    async fn test_state_is_persisted_after_each_move() {
+        let pin = FakeLockPin::default();
+        let store = MemoryLockStateStore::with_state(LockState::Locked);
+        let mut controller =
+            DoorLockController::boot(pin.clone(), store.clone(), LockState::Locked, 10).await;
+
+        controller.unlock().await.unwrap();
+        assert_eq!(store.state(), Some(LockState::Unlocked));
+
+        controller.lock().await.unwrap();
+        assert_eq!(store.state(), Some(LockState::Locked));
+        assert_eq!(pin.moves(), vec![LockState::Unlocked, LockState::Locked]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limit_refuses_extra_cycles_within_a_minute() {
+        let pin = FakeLockPin::default();
+        let store = MemoryLockStateStore::with_state(LockState::Locked);
+        let mut controller =
+            DoorLockController::boot(pin.clone(), store, LockState::Locked, 2).await;
+
+        controller.unlock().await.unwrap();
+        controller.lock().await.unwrap();
+        controller.unlock().await.unwrap();
+        controller.lock().await.unwrap();
+        let error = controller.unlock().await.unwrap_err();
+
+        assert!(matches!(error, LockError::RateLimited { max_cycles: 2 }));
+        assert_eq!(controller.state(), Some(LockState::Locked));
+        assert_eq!(pin.moves().len(), 4);
+
+        // 1分経過すれば再び動かせる
+        tokio::time::advance(Duration::from_secs(60)).await;
+        controller.unlock().await.unwrap();
+        assert_eq!(controller.state(), Some(LockState::Unlocked));
+    }
+}
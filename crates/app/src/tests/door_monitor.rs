@@ -0,0 +1,188 @@
+use mockall::predicate::*;
+use mockall::*;
+use tokio::sync::watch;
+
+use crate::domain::{ApiError, DoorEventApi, DoorEventRequest, DoorSensor, DoorState};
+
+mock! {
+    pub DoorEventApi {}
+    impl DoorEventApi for DoorEventApi {
+        async fn report_door_event(&self, req: DoorEventRequest) -> Result<(), ApiError>;
+    }
+}
+
+// watchチャネルで状態を流し込むドアセンサー
+pub struct FakeDoorSensor {
+    rx: watch::Receiver<DoorState>,
+}
+
+impl FakeDoorSensor {
+    pub fn new(initial: DoorState) -> (watch::Sender<DoorState>, Self) {
+        let (tx, rx) = watch::channel(initial);
+        (tx, Self { rx })
+    }
+}
+
+impl DoorSensor for FakeDoorSensor {
+    fn state(&self) -> DoorState {
+        *self.rx.borrow()
+    }
+
+    async fn wait_for_change(&mut self) -> anyhow::Result<DoorState> {
+        self.rx.changed().await?;
+        Ok(*self.rx.borrow_and_update())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::app::DoorMonitorUseCase;
+    use crate::domain::{DoorEvent, SoundEvent};
+    use crate::tests::touch_card::MockSoundPlayer;
+
+    const THRESHOLD: Duration = Duration::from_secs(120);
+
+    #[tokio::test(start_paused = true)]
+    async fn test_door_opened_and_closed() {
+        let (tx, sensor) = FakeDoorSensor::new(DoorState::Closed);
+
+        // 開閉イベントのみ送信される
+        let mut mock_api = MockDoorEventApi::new();
+        mock_api
+            .expect_report_door_event()
+            .with(eq(DoorEventRequest {
+                event: DoorEvent::Opened,
+                open_secs: None,
+            }))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_api
+            .expect_report_door_event()
+            .with(eq(DoorEventRequest {
+                event: DoorEvent::Closed,
+                open_secs: Some(10),
+            }))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        // 警告音は鳴らない
+        let mock_player = MockSoundPlayer::new();
+
+        let use_case = DoorMonitorUseCase::new(sensor, mock_api, mock_player, THRESHOLD, true);
+        let handle = tokio::spawn(use_case.run());
+        tokio::task::yield_now().await;
+
+        tx.send(DoorState::Open).unwrap();
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        tx.send(DoorState::Closed).unwrap();
+        tokio::task::yield_now().await;
+
+        // センサーが止まると監視も終了する
+        drop(tx);
+        handle.await.unwrap().unwrap_err();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_door_left_open_alert() {
+        let (tx, sensor) = FakeDoorSensor::new(DoorState::Closed);
+
+        let mut mock_api = MockDoorEventApi::new();
+        mock_api
+            .expect_report_door_event()
+            .with(function(|req: &DoorEventRequest| {
+                req.event == DoorEvent::Opened
+            }))
+            .times(1)
+            .returning(|_| Ok(()));
+        // しきい値ごとに警告を送る
+        mock_api
+            .expect_report_door_event()
+            .with(function(|req: &DoorEventRequest| {
+                req.event == DoorEvent::LeftOpen
+            }))
+            .times(2)
+            .returning(|_| Ok(()));
+        mock_api
+            .expect_report_door_event()
+            .with(eq(DoorEventRequest {
+                event: DoorEvent::Closed,
+                open_secs: Some(300),
+            }))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::DoorLeftOpen))
+            .times(2)
+            .returning(|_| Ok(()));
+
+        let use_case = DoorMonitorUseCase::new(sensor, mock_api, mock_player, THRESHOLD, true);
+        let handle = tokio::spawn(use_case.run());
+        tokio::task::yield_now().await;
+
+        tx.send(DoorState::Open).unwrap();
+        tokio::time::sleep(Duration::from_secs(300)).await;
+        tx.send(DoorState::Closed).unwrap();
+        tokio::task::yield_now().await;
+
+        drop(tx);
+        handle.await.unwrap().unwrap_err();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_report_failure_does_not_stop_monitor() {
+        let (tx, sensor) = FakeDoorSensor::new(DoorState::Closed);
+
+        // API送信に失敗しても監視は継続する
+        let mut mock_api = MockDoorEventApi::new();
+        mock_api
+            .expect_report_door_event()
+            .times(2)
+            .returning(|_| Err(ApiError::Status(503)));
+
+        let mock_player = MockSoundPlayer::new();
+
+        let use_case = DoorMonitorUseCase::new(sensor, mock_api, mock_player, THRESHOLD, true);
+        let handle = tokio::spawn(use_case.run());
+        tokio::task::yield_now().await;
+
+        tx.send(DoorState::Open).unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        tx.send(DoorState::Closed).unwrap();
+        tokio::task::yield_now().await;
+
+        drop(tx);
+        handle.await.unwrap().unwrap_err();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_events_are_not_reported_unless_enabled() {
+        let (tx, sensor) = FakeDoorSensor::new(DoorState::Closed);
+
+        // 報告が無効ならAPIは呼ばれないが、警告音は鳴る
+        let mock_api = MockDoorEventApi::new();
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::DoorLeftOpen))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let use_case = DoorMonitorUseCase::new(sensor, mock_api, mock_player, THRESHOLD, false);
+        let handle = tokio::spawn(use_case.run());
+        tokio::task::yield_now().await;
+
+        tx.send(DoorState::Open).unwrap();
+        tokio::time::sleep(Duration::from_secs(150)).await;
+        tx.send(DoorState::Closed).unwrap();
+        tokio::task::yield_now().await;
+
+        drop(tx);
+        handle.await.unwrap().unwrap_err();
+    }
+}
@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        AccessLevel, ApiError, ErrorCode, HeartbeatRequest, HeartbeatResponse, LockMode, LockState,
+        RoomEntryStatus, SoundEvent, TouchCardResponse,
+    };
+
+    fn parse(json: &str) -> TouchCardResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_old_server_success_grants_unlock() {
+        // access / lock_mode を返さない旧サーバー
+        let response = parse(r#"{"success":true,"status":"entry","entries":1}"#);
+
+        assert!(matches!(
+            response,
+            TouchCardResponse::Success {
+                status: RoomEntryStatus::Entry,
+                entries: 1,
+                access: AccessLevel::Unlock,
+                display_name: None,
+                first_entry_today: false,
+                last_entry_at: None,
+                lock_mode: None,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_success_with_access_and_lock_mode() {
+        let response = parse(
+            r#"{"success":true,"status":"exit","entries":0,"access":"record_only","lock_mode":"lockdown"}"#,
+        );
+
+        assert!(matches!(
+            response,
+            TouchCardResponse::Success {
+                status: RoomEntryStatus::Exit,
+                access: AccessLevel::RecordOnly,
+                lock_mode: Some(LockMode::Lockdown),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_success_with_display_name() {
+        let response = parse(r#"{"status":"entry","entries":1,"display_name":"山田"}"#);
+
+        assert!(matches!(
+            response,
+            TouchCardResponse::Success {
+                display_name: Some(ref name),
+                ..
+            } if name == "山田"
+        ));
+    }
+
+    #[test]
+    fn test_success_with_visit_history() {
+        let response = parse(
+            r#"{"status":"entry","entries":1,"first_entry_today":true,"last_entry_at":"2026-10-01T18:30:00+09:00"}"#,
+        );
+
+        let TouchCardResponse::Success {
+            first_entry_today,
+            last_entry_at,
+            ..
+        } = response
+        else {
+            panic!("expected success");
+        };
+        assert!(first_entry_today);
+        assert_eq!(
+            last_entry_at.unwrap().to_rfc3339(),
+            "2026-10-01T18:30:00+09:00"
+        );
+    }
+
+    #[test]
+    fn test_lock_mode_config_matches_api_spelling() {
+        // LOCK_MODE には API の lock_mode と同じ綴りを書く
+        for mode in [LockMode::Normal, LockMode::TouchOnly, LockMode::Lockdown] {
+            let spelling = serde_json::to_value(mode).unwrap();
+            assert_eq!(spelling.as_str().unwrap().parse::<LockMode>(), Ok(mode));
+        }
+    }
+
+    #[test]
+    fn test_sound_event_name_matches_manifest_key() {
+        // メトリクスのラベルはサウンドパックのキーと同じ綴りにする
+        for sound in SoundEvent::ALL {
+            let key = serde_json::to_value(sound).unwrap();
+            assert_eq!(key.as_str(), Some(sound.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_unknown_access_level_fails_closed() {
+        // 新しいサーバーが知らないアクセスレベルを返しても解錠しない
+        let response = parse(r#"{"status":"entry","entries":1,"access":"guest_escort"}"#);
+
+        assert!(matches!(
+            response,
+            TouchCardResponse::Success {
+                access: AccessLevel::RecordOnly,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_error_response_is_not_mistaken_for_success() {
+        let response = parse(
+            r#"{"success":false,"error":"未登録です","error_code":"STUDENT_CARD_NOT_REGISTERED"}"#,
+        );
+
+        assert!(matches!(
+            response,
+            TouchCardResponse::Error {
+                error_code: ErrorCode::StudentCardNotRegistered,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_error_response_carries_registration_code() {
+        let response = parse(
+            r#"{"success":false,"error":"未登録です","error_code":"NFC_CARD_NOT_REGISTERED","registration_code":"0420"}"#,
+        );
+
+        assert!(matches!(
+            response,
+            TouchCardResponse::Error {
+                error_code: ErrorCode::NfcCardNotRegistered,
+                registration_code: Some(code),
+                ..
+            } if code == "0420"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_error_code_is_tolerated() {
+        let response = parse(r#"{"success":false,"error":"?","error_code":"RATE_LIMITED"}"#);
+
+        assert!(matches!(
+            response,
+            TouchCardResponse::Error {
+                error_code: ErrorCode::Unknown,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_api_error_offline_classification() {
+        // 届かない・サーバーが落ちているときだけオフライン扱い
+        assert!(ApiError::Timeout.is_offline());
+        assert!(ApiError::Connection(anyhow::anyhow!("connection refused")).is_offline());
+        assert!(ApiError::Status(503).is_offline());
+        assert!(!ApiError::Status(401).is_offline());
+        assert!(!ApiError::InvalidResponse(anyhow::anyhow!("missing field")).is_offline());
+    }
+
+    #[test]
+    fn test_heartbeat_wire_format() {
+        let request = HeartbeatRequest {
+            version: "0.3.0".to_string(),
+            uptime_secs: 120,
+            readers: 2,
+            lock_state: Some(LockState::Unlocked),
+            queued_sounds: 0,
+            last_error: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"version":"0.3.0","uptime_secs":120,"readers":2,"lock_state":"unlocked","queued_sounds":0}"#
+        );
+
+        // 空のレスポンスなら端末側の間隔を使う
+        let response: HeartbeatResponse = serde_json::from_str("{}").unwrap();
+        assert_eq!(response.next_heartbeat_secs, None);
+        let response: HeartbeatResponse =
+            serde_json::from_str(r#"{"next_heartbeat_secs":30}"#).unwrap();
+        assert_eq!(response.next_heartbeat_secs, Some(30));
+    }
+}
@@ -0,0 +1,239 @@
+use crate::app::GreetingRule;
+use crate::domain::SoundEvent;
+
+pub fn rule(sound: SoundEvent) -> GreetingRule {
+    GreetingRule {
+        sound,
+        text: None,
+        time: None,
+        weekdays: Vec::new(),
+        dates: Vec::new(),
+        first_entry_of_day: false,
+        away_days: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, Local, TimeZone, Weekday};
+    use mockall::predicate::*;
+
+    use super::*;
+    use crate::app::{DateSpec, GreetingSchedule, TimeRange, TouchCardUseCase, Visit};
+    use crate::domain::{AccessLevel, RoomEntryStatus, TouchCardResponse, TouchIntent};
+    use crate::tests::lock_policy::{clock_at, monday_at};
+    use crate::tests::touch_card::{MockCardApi, MockDoorLock, MockSoundPlayer, test_card};
+
+    fn selected(
+        schedule: &GreetingSchedule,
+        at: chrono::DateTime<Local>,
+        visit: &Visit,
+    ) -> SoundEvent {
+        schedule.select(&clock_at(at), visit).sound
+    }
+
+    #[test]
+    fn test_default_schedule_keeps_time_of_day_greetings() {
+        let schedule = GreetingSchedule::default();
+        let visit = Visit::default();
+
+        for (hour, min, sound) in [
+            (5, 59, SoundEvent::GoodEvening),
+            (6, 0, SoundEvent::GoodMorning),
+            (11, 59, SoundEvent::GoodMorning),
+            (12, 0, SoundEvent::Hello),
+            (17, 59, SoundEvent::Hello),
+            (18, 0, SoundEvent::GoodEvening),
+        ] {
+            assert_eq!(selected(&schedule, monday_at(hour, min), &visit), sound);
+        }
+    }
+
+    #[test]
+    fn test_special_days() {
+        let schedule = GreetingSchedule::new(vec![
+            GreetingRule {
+                dates: vec!["12-31..01-03".parse().unwrap()],
+                ..rule(SoundEvent::NewYear)
+            },
+            GreetingRule {
+                dates: vec!["10-19".parse().unwrap()],
+                ..rule(SoundEvent::Anniversary)
+            },
+            GreetingRule {
+                dates: vec!["2026-10-19..2026-10-23".parse().unwrap()],
+                weekdays: vec![Weekday::Tue],
+                ..rule(SoundEvent::ExamWeek)
+            },
+        ]);
+        let visit = Visit::default();
+
+        // 年をまたぐ期間
+        let new_year = Local.with_ymd_and_hms(2027, 1, 2, 10, 0, 0).unwrap();
+        assert_eq!(selected(&schedule, new_year, &visit), SoundEvent::NewYear);
+        assert_eq!(
+            selected(&schedule, monday_at(10, 0), &visit),
+            SoundEvent::Anniversary
+        );
+        let tuesday = Local.with_ymd_and_hms(2026, 10, 20, 10, 0, 0).unwrap();
+        assert_eq!(selected(&schedule, tuesday, &visit), SoundEvent::ExamWeek);
+        let wednesday = Local.with_ymd_and_hms(2026, 10, 21, 10, 0, 0).unwrap();
+        assert_eq!(
+            selected(&schedule, wednesday, &visit),
+            SoundEvent::GoodMorning
+        );
+    }
+
+    #[test]
+    fn test_first_entry_and_welcome_back() {
+        let schedule = GreetingSchedule::new(vec![
+            GreetingRule {
+                first_entry_of_day: true,
+                time: Some("06:00-12:00".parse().unwrap()),
+                ..rule(SoundEvent::FirstEntry)
+            },
+            GreetingRule {
+                away_days: Some(14),
+                ..rule(SoundEvent::WelcomeBack)
+            },
+        ]);
+        let jst = FixedOffset::east_opt(9 * 3600).unwrap();
+        let first = Visit {
+            first_entry_today: true,
+            last_entry_at: None,
+        };
+        let back = |days_ago: u32| Visit {
+            first_entry_today: false,
+            last_entry_at: Some(
+                jst.with_ymd_and_hms(2026, 10, 19 - days_ago, 12, 0, 0)
+                    .unwrap(),
+            ),
+        };
+
+        assert_eq!(
+            selected(&schedule, monday_at(9, 0), &first),
+            SoundEvent::FirstEntry
+        );
+        // 規則は上から順に評価される
+        assert_eq!(
+            selected(&schedule, monday_at(13, 0), &first),
+            SoundEvent::Hello
+        );
+        assert_eq!(
+            selected(&schedule, monday_at(13, 0), &back(14)),
+            SoundEvent::WelcomeBack
+        );
+        assert_eq!(
+            selected(&schedule, monday_at(13, 0), &back(13)),
+            SoundEvent::Hello
+        );
+    }
+
+    #[test]
+    fn test_time_range_wraps_past_midnight() {
+        let night: TimeRange = "22:00-05:00".parse().unwrap();
+
+        assert!(night.contains(monday_at(23, 30).time()));
+        assert!(night.contains(monday_at(4, 59).time()));
+        assert!(!night.contains(monday_at(5, 0).time()));
+        "10:00-10:00".parse::<TimeRange>().unwrap_err();
+        "25:00-26:00".parse::<TimeRange>().unwrap_err();
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        "2026-10-23..2026-10-19".parse::<DateSpec>().unwrap_err();
+        "02-30".parse::<DateSpec>().unwrap_err();
+        "02-29".parse::<DateSpec>().unwrap();
+    }
+
+    #[test]
+    fn test_rule_deserializes() {
+        let rule: GreetingRule = serde_json::from_value(serde_json::json!({
+            "sound": "new_year",
+            "text": "あけましておめでとうございます",
+            "dates": ["01-01..01-03"],
+            "weekdays": ["sat", "sun"],
+        }))
+        .unwrap();
+
+        assert_eq!(rule.sound, SoundEvent::NewYear);
+        assert_eq!(rule.weekdays, vec![Weekday::Sat, Weekday::Sun]);
+        serde_json::from_value::<GreetingRule>(serde_json::json!({
+            "sound": "hello",
+            "hour": 9,
+        }))
+        .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_touch_speaks_rule_text() {
+        // 1月1日の朝、表示名つきで入室
+        let card = test_card(TouchIntent::Toggle);
+        let schedule = GreetingSchedule::new(vec![
+            GreetingRule {
+                text: Some("あけましておめでとうございます".to_string()),
+                dates: vec!["01-01".parse().unwrap()],
+                ..rule(SoundEvent::NewYear)
+            },
+            GreetingRule {
+                first_entry_of_day: true,
+                ..rule(SoundEvent::FirstEntry)
+            },
+        ]);
+
+        let mut mock_api = MockCardApi::new();
+        mock_api.expect_touch().times(2).returning(|_| {
+            Ok(TouchCardResponse::Success {
+                status: RoomEntryStatus::Entry,
+                entries: 1,
+                access: AccessLevel::Unlock,
+                display_name: Some("山田".to_string()),
+                first_entry_today: true,
+                last_entry_at: None,
+                lock_mode: None,
+            })
+        });
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(2)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_speak()
+            .with(
+                eq("あけましておめでとうございます、山田さん"),
+                eq(SoundEvent::NewYear),
+            )
+            .times(1)
+            .returning(|_, _| Ok(()));
+        // 読み上げる文がない規則では名前があっても音声を再生する
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::FirstEntry))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().times(2).returning(|| Ok(()));
+
+        let new_year = clock_at(Local.with_ymd_and_hms(2027, 1, 1, 9, 0, 0).unwrap());
+        TouchCardUseCase::new(&mock_api, &mock_player, new_year, &mock_door_lock)
+            .with_greetings(schedule.clone())
+            .execute(&card)
+            .await
+            .unwrap();
+        TouchCardUseCase::new(
+            &mock_api,
+            &mock_player,
+            clock_at(monday_at(9, 0)),
+            &mock_door_lock,
+        )
+        .with_greetings(schedule)
+        .execute(&card)
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+use mockall::*;
+
+use crate::domain::{ApiError, HeartbeatApi, HeartbeatRequest, HeartbeatResponse};
+
+mock! {
+    pub HeartbeatApi {}
+    impl HeartbeatApi for HeartbeatApi {
+        async fn heartbeat(&self, req: HeartbeatRequest) -> Result<HeartbeatResponse, ApiError>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use tokio::time::{self, Instant};
+
+    use super::*;
+    use crate::app::{HeartbeatUseCase, TerminalHealth};
+    use crate::domain::LockState;
+    use crate::tests::touch_card::{MockDoorLock, MockSoundPlayer};
+
+    /// `responses` を順に返し、尽きたら成功を返す API で `duration` の間動かし、
+    /// 送信した時刻 (開始からの秒) とリクエストを返す
+    async fn run_for(
+        duration: Duration,
+        responses: Vec<Result<HeartbeatResponse, ApiError>>,
+    ) -> Vec<(u64, HeartbeatRequest)> {
+        let start = Instant::now();
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut responses = responses.into_iter();
+
+        let mut mock_api = MockHeartbeatApi::new();
+        let recorded = Arc::clone(&sent);
+        mock_api.expect_heartbeat().returning(move |req| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((start.elapsed().as_secs(), req));
+            responses.next().unwrap_or(Ok(HeartbeatResponse::default()))
+        });
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock
+            .expect_state()
+            .returning(|| Some(LockState::Locked));
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player.expect_queue_len().returning(|| 2);
+
+        let use_case =
+            HeartbeatUseCase::new(mock_api, mock_door_lock, mock_player, || TerminalHealth {
+                readers: 1,
+                last_error: Some("touch-card api request timed out".to_string()),
+            })
+            .with_interval(Duration::from_secs(60));
+        time::timeout(duration, use_case.run()).await.unwrap_err();
+
+        sent.lock().unwrap().clone()
+    }
+
+    fn sent_at(sent: &[(u64, HeartbeatRequest)]) -> Vec<u64> {
+        sent.iter().map(|(at, _)| *at).collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_heartbeat_reports_terminal_state() {
+        let sent = run_for(Duration::from_secs(150), Vec::new()).await;
+
+        assert_eq!(sent_at(&sent), vec![0, 60, 120]);
+        assert_eq!(
+            sent[1].1,
+            HeartbeatRequest {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                uptime_secs: 60,
+                readers: 1,
+                lock_state: Some(LockState::Locked),
+                queued_sounds: 2,
+                last_error: Some("touch-card api request timed out".to_string()),
+            }
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failures_back_off_and_recover() {
+        let sent = run_for(
+            Duration::from_secs(100),
+            vec![
+                Err(ApiError::Timeout),
+                Err(ApiError::Status(503)),
+                Err(ApiError::Timeout),
+            ],
+        )
+        .await;
+
+        // 5秒から倍々に待ち、成功したら通常の間隔に戻る
+        assert_eq!(sent_at(&sent), vec![0, 5, 15, 35, 95]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_backoff_is_capped() {
+        let sent = run_for(
+            Duration::from_secs(1000),
+            (0..10).map(|_| Err(ApiError::Timeout)).collect(),
+        )
+        .await;
+
+        assert_eq!(sent_at(&sent), vec![0, 5, 15, 35, 75, 155, 315, 615, 915]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_server_can_change_interval() {
+        let sent = run_for(
+            Duration::from_secs(100),
+            vec![Ok(HeartbeatResponse {
+                next_heartbeat_secs: Some(30),
+            })],
+        )
+        .await;
+
+        assert_eq!(sent_at(&sent), vec![0, 30, 90]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_server_interval_is_clamped() {
+        let sent = run_for(
+            Duration::from_secs(70),
+            vec![Ok(HeartbeatResponse {
+                next_heartbeat_secs: Some(0),
+            })],
+        )
+        .await;
+
+        // 0秒を指定されても詰めて送り続けない
+        assert_eq!(sent_at(&sent), vec![0, 5, 65]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_huge_server_interval_is_capped() {
+        let sent = run_for(
+            Duration::from_secs(3700),
+            vec![Ok(HeartbeatResponse {
+                next_heartbeat_secs: Some(u64::MAX),
+            })],
+        )
+        .await;
+
+        // 不正に長い間隔でも 1 時間ごとには送る
+        assert_eq!(sent_at(&sent), vec![0, 3600, 3660]);
+    }
+}
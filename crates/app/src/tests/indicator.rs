@@ -0,0 +1,135 @@
+use std::sync::{Arc, Mutex};
+
+use crate::domain::{Indicator, IndicatorPattern};
+
+/// 表示されたパターンを記録するインジケーター
+#[derive(Debug, Clone, Default)]
+pub struct RecordingIndicator {
+    patterns: Arc<Mutex<Vec<IndicatorPattern>>>,
+}
+
+impl RecordingIndicator {
+    pub fn patterns(&self) -> Vec<IndicatorPattern> {
+        self.patterns.lock().unwrap().clone()
+    }
+}
+
+impl Indicator for RecordingIndicator {
+    fn show(&self, pattern: IndicatorPattern) {
+        self.patterns.lock().unwrap().push(pattern);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::app::LockPolicy;
+    use crate::domain::{
+        AccessLevel, ApiError, ErrorCode, LockMode, RoomEntryStatus, TouchCardResponse, TouchIntent,
+    };
+    use crate::tests::touch_card::{test_card, use_case_with};
+
+    async fn patterns_for(
+        intent: TouchIntent,
+        response: Result<TouchCardResponse, ApiError>,
+        lock_policy: LockPolicy,
+    ) -> Vec<IndicatorPattern> {
+        let indicator = RecordingIndicator::default();
+        let use_case = use_case_with(response, Ok(()))
+            .with_lock_policy(lock_policy)
+            .with_indicator(indicator.clone());
+
+        let _ = use_case.execute(&test_card(intent)).await;
+        indicator.patterns()
+    }
+
+    #[tokio::test]
+    async fn test_success_patterns() {
+        assert_eq!(
+            patterns_for(
+                TouchIntent::Toggle,
+                Ok(TouchCardResponse::success_entry(1)),
+                LockPolicy::default()
+            )
+            .await,
+            vec![IndicatorPattern::Reading, IndicatorPattern::SuccessEntry]
+        );
+        assert_eq!(
+            patterns_for(
+                TouchIntent::Toggle,
+                Ok(TouchCardResponse::success_exit(0)),
+                LockPolicy::default()
+            )
+            .await,
+            vec![IndicatorPattern::Reading, IndicatorPattern::SuccessExit]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_only_pattern() {
+        // 記録のみのカードは入室と区別して表示する
+        let response = TouchCardResponse::Success {
+            status: RoomEntryStatus::Entry,
+            entries: 1,
+            access: AccessLevel::RecordOnly,
+            display_name: None,
+            first_entry_today: false,
+            last_entry_at: None,
+            lock_mode: None,
+        };
+        assert_eq!(
+            patterns_for(TouchIntent::Toggle, Ok(response), LockPolicy::default()).await,
+            vec![IndicatorPattern::Reading, IndicatorPattern::RecordOnly]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_patterns() {
+        assert_eq!(
+            patterns_for(
+                TouchIntent::Toggle,
+                Ok(TouchCardResponse::error(
+                    ErrorCode::NfcCardNotRegistered,
+                    "未登録"
+                )),
+                LockPolicy::default()
+            )
+            .await,
+            vec![IndicatorPattern::Reading, IndicatorPattern::Error]
+        );
+        // 退出専用リーダーで入室になった
+        assert_eq!(
+            patterns_for(
+                TouchIntent::Exit,
+                Ok(TouchCardResponse::success_entry(1)),
+                LockPolicy::default()
+            )
+            .await,
+            vec![IndicatorPattern::Reading, IndicatorPattern::Error]
+        );
+        assert_eq!(
+            patterns_for(
+                TouchIntent::Toggle,
+                Ok(TouchCardResponse::success_entry(1)),
+                LockPolicy::new(LockMode::Lockdown, Vec::new(), HashSet::default())
+            )
+            .await,
+            vec![IndicatorPattern::Reading, IndicatorPattern::Error]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_failure_shows_offline() {
+        assert_eq!(
+            patterns_for(
+                TouchIntent::Toggle,
+                Err(ApiError::Connection(anyhow::anyhow!("connection refused"))),
+                LockPolicy::default()
+            )
+            .await,
+            vec![IndicatorPattern::Reading, IndicatorPattern::Offline]
+        );
+    }
+}
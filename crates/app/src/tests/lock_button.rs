@@ -0,0 +1,131 @@
+use tokio::sync::mpsc;
+
+use crate::domain::{ButtonPress, LockButton};
+
+// mpscチャネルで押下を流し込むボタン
+pub struct FakeLockButton {
+    rx: mpsc::UnboundedReceiver<ButtonPress>,
+}
+
+impl FakeLockButton {
+    pub fn new() -> (mpsc::UnboundedSender<ButtonPress>, Self) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (tx, Self { rx })
+    }
+}
+
+impl LockButton for FakeLockButton {
+    async fn next_press(&mut self) -> anyhow::Result<ButtonPress> {
+        self.rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("button closed"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::*;
+
+    use super::*;
+    use crate::app::LockButtonUseCase;
+    use crate::domain::{DoorEvent, DoorEventRequest, LockError};
+    use crate::tests::door_monitor::MockDoorEventApi;
+    use crate::tests::touch_card::MockDoorLock;
+
+    #[tokio::test]
+    async fn test_short_press_unlocks() {
+        let (tx, button) = FakeLockButton::new();
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().times(1).returning(|| Ok(()));
+
+        let mut mock_api = MockDoorEventApi::new();
+        mock_api
+            .expect_report_door_event()
+            .with(eq(DoorEventRequest {
+                event: DoorEvent::ManualUnlock,
+                open_secs: None,
+            }))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        tx.send(ButtonPress::Short).unwrap();
+        drop(tx);
+
+        let use_case = LockButtonUseCase::new(button, mock_door_lock, mock_api, true);
+        use_case.run().await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_long_press_locks() {
+        let (tx, button) = FakeLockButton::new();
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_lock().times(1).returning(|| Ok(()));
+
+        let mut mock_api = MockDoorEventApi::new();
+        mock_api
+            .expect_report_door_event()
+            .with(eq(DoorEventRequest {
+                event: DoorEvent::ManualLock,
+                open_secs: None,
+            }))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        tx.send(ButtonPress::Long).unwrap();
+        drop(tx);
+
+        let use_case = LockButtonUseCase::new(button, mock_door_lock, mock_api, true);
+        use_case.run().await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_events_not_reported_when_disabled() {
+        let (tx, button) = FakeLockButton::new();
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().times(1).returning(|| Ok(()));
+        mock_door_lock.expect_lock().times(1).returning(|| Ok(()));
+
+        // 送信しない設定ではAPIを呼ばない
+        let mock_api = MockDoorEventApi::new();
+
+        tx.send(ButtonPress::Short).unwrap();
+        tx.send(ButtonPress::Long).unwrap();
+        drop(tx);
+
+        let use_case = LockButtonUseCase::new(button, mock_door_lock, mock_api, false);
+        use_case.run().await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_failed_unlock_is_not_reported() {
+        let (tx, button) = FakeLockButton::new();
+
+        // 解錠に失敗しても次の押下を処理する
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock
+            .expect_unlock()
+            .times(1)
+            .returning(|| Err(LockError::Actuator(anyhow::anyhow!("servo error"))));
+        mock_door_lock.expect_lock().times(1).returning(|| Ok(()));
+
+        let mut mock_api = MockDoorEventApi::new();
+        mock_api
+            .expect_report_door_event()
+            .with(function(|req: &DoorEventRequest| {
+                req.event == DoorEvent::ManualLock
+            }))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        tx.send(ButtonPress::Short).unwrap();
+        tx.send(ButtonPress::Long).unwrap();
+        drop(tx);
+
+        let use_case = LockButtonUseCase::new(button, mock_door_lock, mock_api, true);
+        use_case.run().await.unwrap_err();
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+
+use crate::app::LockPolicy;
+use crate::domain::LockMode;
+use crate::tests::touch_card::MockClock;
+
+// 2026-10-19 は月曜日
+pub fn monday_at(hour: u32, min: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2026, 10, 19, hour, min, 0).unwrap()
+}
+
+pub fn club_hours_policy(mode: LockMode, holidays: &[NaiveDate]) -> LockPolicy {
+    LockPolicy::new(
+        mode,
+        vec!["mon-fri@16:00-19:00".parse().unwrap()],
+        holidays.iter().copied().collect::<HashSet<_>>(),
+    )
+}
+
+pub fn clock_at(time: DateTime<Local>) -> MockClock {
+    let mut mock_clock = MockClock::new();
+    mock_clock.expect_now().returning(move || time);
+    mock_clock
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::app::{LockDecision, LockModeOverride, LockScheduleUseCase};
+    use crate::domain::{LockError, OpenHours};
+    use crate::tests::touch_card::MockDoorLock;
+
+    #[test]
+    fn test_open_hours_hold_door_open() {
+        let policy = club_hours_policy(LockMode::Normal, &[]);
+
+        assert_eq!(
+            policy.decide(&clock_at(monday_at(16, 0)), None),
+            LockDecision::HoldOpen
+        );
+        assert_eq!(
+            policy.decide(&clock_at(monday_at(19, 0)), None),
+            LockDecision::UnlockOnTouch
+        );
+    }
+
+    #[test]
+    fn test_holiday_skips_open_hours() {
+        let policy = club_hours_policy(
+            LockMode::Normal,
+            &[NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()],
+        );
+
+        assert_eq!(
+            policy.decide(&clock_at(monday_at(17, 0)), None),
+            LockDecision::UnlockOnTouch
+        );
+    }
+
+    #[test]
+    fn test_modes_override_open_hours() {
+        let touch_only = club_hours_policy(LockMode::TouchOnly, &[]);
+        assert_eq!(
+            touch_only.decide(&clock_at(monday_at(17, 0)), None),
+            LockDecision::UnlockOnTouch
+        );
+
+        // ロックダウンでは時計を見ない
+        let lockdown = club_hours_policy(LockMode::Lockdown, &[]);
+        assert_eq!(lockdown.decide(&MockClock::new(), None), LockDecision::Deny);
+    }
+
+    #[test]
+    fn test_response_mode_overrides_config() {
+        let policy = club_hours_policy(LockMode::Normal, &[]);
+
+        assert_eq!(
+            policy.decide(&MockClock::new(), Some(LockMode::Lockdown)),
+            LockDecision::Deny
+        );
+    }
+
+    #[test]
+    fn test_open_hours_parse_ranges() {
+        let hours: OpenHours = "fri-mon@10:00-12:30".parse().unwrap();
+
+        assert_eq!(hours.weekdays.len(), 4);
+        assert!(hours.contains(monday_at(12, 29).naive_local()));
+        assert!(!hours.contains(monday_at(12, 30).naive_local()));
+        "mon@25:00-26:00".parse::<OpenHours>().unwrap_err();
+        "someday@10:00-11:00".parse::<OpenHours>().unwrap_err();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_schedule_holds_open_during_open_hours() {
+        // 仮想時間の経過に合わせて進む時計
+        let start = tokio::time::Instant::now();
+        let base = monday_at(15, 0);
+        let mut mock_clock = MockClock::new();
+        mock_clock
+            .expect_now()
+            .returning(move || base + chrono::Duration::from_std(start.elapsed()).unwrap());
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock
+            .expect_hold_open()
+            .times(1)
+            .returning(|| Ok(()));
+        mock_door_lock.expect_lock().times(1).returning(|| Ok(()));
+
+        let use_case = LockScheduleUseCase::new(
+            mock_clock,
+            mock_door_lock,
+            club_hours_policy(LockMode::Normal, &[]),
+        );
+
+        tokio::time::timeout(Duration::from_secs(5 * 60 * 60), use_case.run())
+            .await
+            .unwrap_err();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_schedule_retries_failed_hold_open() {
+        let mut mock_door_lock = MockDoorLock::new();
+        let mut attempts = 0;
+        mock_door_lock
+            .expect_hold_open()
+            .times(2)
+            .returning(move || {
+                attempts += 1;
+                if attempts == 1 {
+                    return Err(LockError::Actuator(anyhow::anyhow!("servo stalled")));
+                }
+                Ok(())
+            });
+
+        let use_case = LockScheduleUseCase::new(
+            clock_at(monday_at(17, 0)),
+            mock_door_lock,
+            club_hours_policy(LockMode::Normal, &[]),
+        );
+
+        tokio::time::timeout(Duration::from_secs(5 * 60), use_case.run())
+            .await
+            .unwrap_err();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_schedule_locks_on_lockdown_during_open_hours() {
+        // 開室時間中でも API がロックダウンを指示したら施錠する
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock
+            .expect_hold_open()
+            .times(1)
+            .returning(|| Ok(()));
+        mock_door_lock.expect_lock().times(1).returning(|| Ok(()));
+
+        let lock_mode = LockModeOverride::default();
+        let use_case = LockScheduleUseCase::new(
+            clock_at(monday_at(17, 0)),
+            mock_door_lock,
+            club_hours_policy(LockMode::Normal, &[]),
+        )
+        .with_lock_mode_override(lock_mode.clone());
+
+        let lockdown = async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            lock_mode.set(Some(LockMode::Lockdown));
+        };
+        tokio::time::timeout(Duration::from_secs(5 * 60), async {
+            tokio::join!(use_case.run(), lockdown)
+        })
+        .await
+        .unwrap_err();
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::{Arc, Mutex};
+
+use crate::domain::{CardKind, Metrics, SoundEvent, TouchOutcome};
+
+/// 記録されたメトリクスを保持する
+#[derive(Debug, Clone, Default)]
+pub struct RecordingMetrics {
+    touches: Arc<Mutex<Vec<(CardKind, TouchOutcome)>>>,
+    sound_errors: Arc<Mutex<Vec<SoundEvent>>>,
+    queue_depth: Arc<Mutex<Option<usize>>>,
+}
+
+impl RecordingMetrics {
+    pub fn touches(&self) -> Vec<(CardKind, TouchOutcome)> {
+        self.touches.lock().unwrap().clone()
+    }
+
+    pub fn sound_errors(&self) -> Vec<SoundEvent> {
+        self.sound_errors.lock().unwrap().clone()
+    }
+
+    pub fn queue_depth(&self) -> Option<usize> {
+        *self.queue_depth.lock().unwrap()
+    }
+}
+
+impl Metrics for RecordingMetrics {
+    fn touch(&self, kind: CardKind, outcome: TouchOutcome) {
+        self.touches.lock().unwrap().push((kind, outcome));
+    }
+
+    fn sound_error(&self, sound: SoundEvent) {
+        self.sound_errors.lock().unwrap().push(sound);
+    }
+
+    fn sound_queue_depth(&self, depth: usize) {
+        *self.queue_depth.lock().unwrap() = Some(depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{SoundScheduler, TouchCardUseCase};
+    use crate::domain::{
+        ApiError, Card, ErrorCode, LockError, SoundError, SoundPlayer, TouchCardResponse,
+        TouchIntent,
+    };
+    use crate::tests::sound_scheduler::FakeOutput;
+    use crate::tests::touch_card::{
+        MockCardApi, MockClock, MockDoorLock, MockSoundPlayer, test_card, use_case_with,
+    };
+
+    fn card(student_id: Option<u32>, balance: Option<u32>) -> Card {
+        Card {
+            student_id,
+            balance,
+            ..test_card(TouchIntent::Toggle)
+        }
+    }
+
+    async fn touch(
+        card: &Card,
+        response: Result<TouchCardResponse, ApiError>,
+        unlock: Result<(), LockError>,
+    ) -> RecordingMetrics {
+        let metrics = RecordingMetrics::default();
+        let use_case = use_case_with(response, unlock).with_metrics(metrics.clone());
+        let _ = use_case.execute(card).await;
+        metrics
+    }
+
+    #[test]
+    fn test_card_kind_follows_read_data() {
+        assert_eq!(card(Some(12_345_678), None).kind(), CardKind::StudentCard);
+        assert_eq!(card(None, Some(1_000)).kind(), CardKind::Transit);
+        assert_eq!(card(None, None).kind(), CardKind::Other);
+    }
+
+    #[tokio::test]
+    async fn test_touches_are_counted_by_outcome_and_kind() {
+        let student_card = card(Some(12_345_678), None);
+        let transit_card = card(None, Some(1_000));
+        let other_card = card(None, None);
+
+        let metrics = touch(
+            &transit_card,
+            Ok(TouchCardResponse::success_exit(1)),
+            Ok(()),
+        )
+        .await;
+        assert_eq!(
+            metrics.touches(),
+            vec![(CardKind::Transit, TouchOutcome::Exited)]
+        );
+
+        let metrics = touch(
+            &other_card,
+            Ok(TouchCardResponse::error(
+                ErrorCode::NfcCardNotRegistered,
+                "未登録です",
+            )),
+            Ok(()),
+        )
+        .await;
+        assert_eq!(
+            metrics.touches(),
+            vec![(CardKind::Other, TouchOutcome::Rejected)]
+        );
+
+        let metrics = touch(&student_card, Err(ApiError::Timeout), Ok(())).await;
+        assert_eq!(
+            metrics.touches(),
+            vec![(CardKind::StudentCard, TouchOutcome::Offline)]
+        );
+
+        let metrics = touch(&student_card, Err(ApiError::Status(401)), Ok(())).await;
+        assert_eq!(
+            metrics.touches(),
+            vec![(CardKind::StudentCard, TouchOutcome::ApiError)]
+        );
+
+        let metrics = touch(
+            &student_card,
+            Ok(TouchCardResponse::success_exit(1)),
+            Err(LockError::Stopped),
+        )
+        .await;
+        assert_eq!(
+            metrics.touches(),
+            vec![(CardKind::StudentCard, TouchOutcome::LockFailed)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_use_case_counts_sound_errors() {
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(1)
+            .returning(|_| Ok(TouchCardResponse::success_exit(1)));
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .returning(|_| Err(SoundError::Decode(anyhow::anyhow!("broken wav"))));
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().returning(|| Ok(()));
+
+        let metrics = RecordingMetrics::default();
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock)
+                .with_metrics(&metrics);
+        use_case.execute(&card(None, Some(1_000))).await.unwrap();
+
+        assert_eq!(
+            metrics.sound_errors(),
+            vec![SoundEvent::Touch, SoundEvent::GoodBye]
+        );
+    }
+
+    #[test]
+    fn test_scheduler_reports_queue_depth_and_failures() {
+        let output = FakeOutput::default();
+        output.break_sound(SoundEvent::GoodBye);
+        let metrics = RecordingMetrics::default();
+        let scheduler =
+            SoundScheduler::new(output.clone(), MockClock::new()).with_metrics(metrics.clone());
+
+        scheduler.play(SoundEvent::Touch).unwrap();
+        scheduler.play(SoundEvent::GoodBye).unwrap();
+        scheduler.play(SoundEvent::Last).unwrap();
+        assert_eq!(metrics.queue_depth(), Some(2));
+
+        // 壊れた音は飛ばして数える
+        output.finish();
+        scheduler.play(SoundEvent::Chime).unwrap();
+        assert_eq!(metrics.sound_errors(), vec![SoundEvent::GoodBye]);
+        assert_eq!(output.started(), vec![SoundEvent::Touch, SoundEvent::Last]);
+        assert_eq!(metrics.queue_depth(), Some(1));
+    }
+}
@@ -1 +1,13 @@
+pub mod display;
+pub mod door_lock;
+pub mod door_monitor;
+pub mod entities;
+pub mod greeting;
+pub mod heartbeat;
+pub mod indicator;
+pub mod lock_button;
+pub mod lock_policy;
+pub mod metrics;
+pub mod sound_scheduler;
 pub mod touch_card;
+pub mod volume;
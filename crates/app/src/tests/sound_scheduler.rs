@@ -0,0 +1,251 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+use crate::domain::{AudioOutput, SoundError, SoundEvent, SoundRequest};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputEvent {
+    Start(SoundRequest),
+    Stop,
+}
+
+/// 再生中かどうかをテストから切り替えられる出力
+#[derive(Debug, Clone, Default)]
+pub struct FakeOutput {
+    events: Arc<Mutex<Vec<OutputEvent>>>,
+    playing: Arc<AtomicBool>,
+    broken: Arc<Mutex<Vec<SoundEvent>>>,
+    gains: Arc<Mutex<Vec<f32>>>,
+}
+
+impl FakeOutput {
+    pub fn events(&self) -> Vec<OutputEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub fn started(&self) -> Vec<SoundEvent> {
+        self.events()
+            .into_iter()
+            .filter_map(|event| match event {
+                OutputEvent::Start(request) => Some(request.event()),
+                OutputEvent::Stop => None,
+            })
+            .collect()
+    }
+
+    pub fn gains(&self) -> Vec<f32> {
+        self.gains.lock().unwrap().clone()
+    }
+
+    pub fn finish(&self) {
+        self.playing.store(false, Ordering::SeqCst);
+    }
+
+    pub fn break_sound(&self, sound: SoundEvent) {
+        self.broken.lock().unwrap().push(sound);
+    }
+}
+
+impl AudioOutput for FakeOutput {
+    fn start(&self, request: &SoundRequest, gain: f32) -> Result<(), SoundError> {
+        if self.broken.lock().unwrap().contains(&request.event()) {
+            return Err(SoundError::Decode(anyhow::anyhow!(
+                "cannot decode {:?}",
+                request.event()
+            )));
+        }
+        self.events
+            .lock()
+            .unwrap()
+            .push(OutputEvent::Start(request.clone()));
+        self.gains.lock().unwrap().push(gain);
+        self.playing.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn stop(&self) {
+        self.events.lock().unwrap().push(OutputEvent::Stop);
+        self.playing.store(false, Ordering::SeqCst);
+    }
+
+    fn is_playing(&self) -> bool {
+        self.playing.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::app::{SoundScheduler, TouchCardUseCase};
+    use crate::domain::{SoundPlayer, TouchCardResponse, TouchIntent};
+    use crate::tests::touch_card::{MockCardApi, MockClock, MockDoorLock, test_card};
+
+    fn scheduler() -> (SoundScheduler<FakeOutput, MockClock>, FakeOutput) {
+        let output = FakeOutput::default();
+        (
+            SoundScheduler::new(output.clone(), MockClock::new()),
+            output,
+        )
+    }
+
+    #[test]
+    fn test_sounds_play_in_order() {
+        let (scheduler, output) = scheduler();
+
+        scheduler.play(SoundEvent::Hello).unwrap();
+        scheduler.play(SoundEvent::Last).unwrap();
+        assert_eq!(output.started(), vec![SoundEvent::Hello]);
+
+        output.finish();
+        scheduler.play(SoundEvent::Error).unwrap();
+
+        assert_eq!(output.started(), vec![SoundEvent::Hello, SoundEvent::Last]);
+    }
+
+    #[test]
+    fn test_touch_does_not_cut_off_greeting() {
+        let (scheduler, output) = scheduler();
+
+        scheduler.play(SoundEvent::GoodMorning).unwrap();
+        scheduler.play(SoundEvent::Last).unwrap();
+        scheduler.play(SoundEvent::Touch).unwrap();
+
+        assert!(!output.events().contains(&OutputEvent::Stop));
+        // 優先度の高いタッチ音は待機中の音声より先に鳴る
+        output.finish();
+        scheduler.play(SoundEvent::Hello).unwrap();
+        assert_eq!(
+            output.started(),
+            vec![SoundEvent::GoodMorning, SoundEvent::Touch]
+        );
+    }
+
+    #[test]
+    fn test_boot_interrupts_and_discards_lower_priority() {
+        let (scheduler, output) = scheduler();
+
+        scheduler.play(SoundEvent::Hello).unwrap();
+        scheduler.play(SoundEvent::Last).unwrap();
+        scheduler.play(SoundEvent::Boot).unwrap();
+
+        assert_eq!(
+            output.events(),
+            vec![
+                OutputEvent::Start(SoundRequest::Event(SoundEvent::Hello)),
+                OutputEvent::Stop,
+                OutputEvent::Start(SoundRequest::Event(SoundEvent::Boot)),
+            ]
+        );
+        output.finish();
+        scheduler.play(SoundEvent::Touch).unwrap();
+        assert_eq!(
+            output.started(),
+            vec![SoundEvent::Hello, SoundEvent::Boot, SoundEvent::Touch]
+        );
+    }
+
+    #[test]
+    fn test_drop_if_busy() {
+        let (scheduler, output) = scheduler();
+
+        scheduler.play(SoundEvent::Hello).unwrap();
+        scheduler.play(SoundEvent::DoorLeftOpen).unwrap();
+        output.finish();
+        scheduler.play(SoundEvent::DoorLeftOpen).unwrap();
+
+        assert_eq!(
+            output.started(),
+            vec![SoundEvent::Hello, SoundEvent::DoorLeftOpen]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_touches_coalesce() {
+        let (scheduler, output) = scheduler();
+
+        scheduler.play(SoundEvent::Hello).unwrap();
+        scheduler.play(SoundEvent::Touch).unwrap();
+        scheduler.play(SoundEvent::Touch).unwrap();
+        output.finish();
+        scheduler.play(SoundEvent::Touch).unwrap();
+
+        assert_eq!(output.started(), vec![SoundEvent::Hello, SoundEvent::Touch]);
+    }
+
+    #[test]
+    fn test_failed_sound_is_skipped() {
+        let (scheduler, output) = scheduler();
+        output.break_sound(SoundEvent::Error);
+
+        scheduler.play(SoundEvent::Error).unwrap();
+        scheduler.play(SoundEvent::NotEntered).unwrap();
+
+        assert_eq!(output.started(), vec![SoundEvent::NotEntered]);
+    }
+
+    #[test]
+    fn test_queue_len_counts_waiting_sounds() {
+        let (scheduler, output) = scheduler();
+        assert_eq!(scheduler.queue_len(), 0);
+
+        scheduler.play(SoundEvent::Hello).unwrap();
+        scheduler.play(SoundEvent::Last).unwrap();
+        scheduler.play(SoundEvent::Touch).unwrap();
+        // 再生中の音は数えない
+        assert_eq!(scheduler.queue_len(), 2);
+
+        output.finish();
+        scheduler.play(SoundEvent::Touch).unwrap();
+        assert_eq!(scheduler.queue_len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_starts_queued_sound_after_current_finishes() {
+        let (scheduler, output) = scheduler();
+
+        scheduler.play(SoundEvent::Hello).unwrap();
+        scheduler.play(SoundEvent::Last).unwrap();
+        output.finish();
+        tokio::time::timeout(Duration::from_secs(1), scheduler.run())
+            .await
+            .unwrap_err();
+
+        assert_eq!(output.started(), vec![SoundEvent::Hello, SoundEvent::Last]);
+    }
+
+    #[tokio::test]
+    async fn test_second_touch_keeps_first_greeting() {
+        // 1人目の挨拶中に2人目がタッチしても挨拶は途切れない
+        let (scheduler, output) = scheduler();
+        let card = test_card(TouchIntent::Toggle);
+
+        let mut mock_clock = MockClock::new();
+        let mock_time = Local.with_ymd_and_hms(2026, 10, 19, 13, 0, 0).unwrap();
+        mock_clock.expect_now().returning(move || mock_time);
+
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(2)
+            .returning(|_| Ok(TouchCardResponse::success_entry(1)));
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().times(2).returning(|| Ok(()));
+
+        let use_case = TouchCardUseCase::new(mock_api, &scheduler, mock_clock, mock_door_lock);
+
+        use_case.execute(&card).await.unwrap();
+        // タッチ音が鳴り終わり、1人目の挨拶が始まったところで2人目がタッチする
+        output.finish();
+        use_case.execute(&card).await.unwrap();
+
+        assert!(!output.events().contains(&OutputEvent::Stop));
+        assert_eq!(output.started(), vec![SoundEvent::Touch, SoundEvent::Hello]);
+    }
+}
@@ -1,23 +1,29 @@
+use std::time::Instant;
+
+use chrono::{Local, TimeZone};
 use mockall::predicate::*;
 use mockall::*;
 
+use crate::app::TouchCardUseCase;
 use crate::domain::{
-    Card, CardApi, Clock, DoorLock, ErrorCode, SoundEvent, SoundPlayer, TouchCardResponse,
+    ApiError, Card, CardApi, Clock, DoorLock, ErrorCode, LockError, ReaderId, SoundError,
+    SoundEvent, SoundPlayer, TouchCardResponse, TouchIntent,
 };
 
 // モッククラスの自動生成
 mock! {
     pub CardApi {}
     impl CardApi for CardApi {
-        async fn touch(&self, req: crate::domain::TouchCardRequest) -> anyhow::Result<TouchCardResponse>;
+        async fn touch(&self, req: crate::domain::TouchCardRequest) -> Result<TouchCardResponse, ApiError>;
     }
 }
 
 mock! {
     pub SoundPlayer {}
     impl SoundPlayer for SoundPlayer {
-        fn play(&self, sound: SoundEvent) -> anyhow::Result<()>;
-        fn reset(&self);
+        fn play(&self, sound: SoundEvent) -> Result<(), SoundError>;
+        fn speak(&self, text: &str, fallback: SoundEvent) -> Result<(), SoundError>;
+        fn queue_len(&self) -> usize;
     }
 }
 
@@ -31,25 +37,77 @@ mock! {
 mock! {
     pub DoorLock {}
     impl DoorLock for DoorLock {
-        async fn unlock(&self) -> anyhow::Result<()>;
+        async fn unlock(&self) -> Result<(), LockError>;
+        async fn lock(&self) -> Result<(), LockError>;
+        async fn hold_open(&self) -> Result<(), LockError>;
+        async fn state(&self) -> Option<crate::domain::LockState>;
+    }
+}
+
+pub fn test_reader() -> ReaderId {
+    ReaderId {
+        usb_path: "1-1.2".to_string(),
+        label: Some("front-door".to_string()),
+    }
+}
+
+/// 学生証を`test_reader()`で読んだカード
+pub fn test_card(intent: TouchIntent) -> Card {
+    Card {
+        idm: "0123456789abcdef".to_string(),
+        student_id: Some(12_345_678),
+        balance: None,
+        intent,
+        reader: test_reader(),
+        detected_at: Instant::now(),
     }
 }
 
+/// APIが`response`を1回返し、解錠が`unlock`になるユースケース
+/// 音はすべて成功し、時計は平日の午後で止まっている
+pub fn use_case_with(
+    response: Result<TouchCardResponse, ApiError>,
+    unlock: Result<(), LockError>,
+) -> TouchCardUseCase<MockCardApi, MockSoundPlayer, MockClock, MockDoorLock> {
+    let mut mock_api = MockCardApi::new();
+    let mut response = Some(response);
+    mock_api
+        .expect_touch()
+        .times(1)
+        .returning(move |_| response.take().unwrap());
+
+    let mut mock_player = MockSoundPlayer::new();
+    mock_player.expect_play().returning(|_| Ok(()));
+    mock_player.expect_speak().returning(|_, _| Ok(()));
+
+    let mut mock_clock = MockClock::new();
+    let mock_time = Local.with_ymd_and_hms(2026, 10, 19, 15, 0, 0).unwrap();
+    mock_clock.expect_now().returning(move || mock_time);
+
+    let mut mock_door_lock = MockDoorLock::new();
+    let mut unlock = Some(unlock);
+    mock_door_lock
+        .expect_unlock()
+        .returning(move || unlock.take().unwrap());
+
+    TouchCardUseCase::new(mock_api, mock_player, mock_clock, mock_door_lock)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::app::TouchCardUseCase;
-    use crate::domain::TouchCardRequest;
-    use chrono::{Local, TimeZone};
+    use std::collections::HashSet;
+
+    use crate::app::{LockModeOverride, LockPolicy, TouchCardError};
+    use crate::domain::{
+        AccessLevel, DisplayScreen, IndicatorPattern, LockMode, RoomEntryStatus, TouchCardRequest,
+    };
+    use crate::tests::{display::RecordingDisplay, indicator::RecordingIndicator};
 
     #[tokio::test]
     async fn test_entry_morning() {
         // 学生証のモックデータ
-        let card_id = Card {
-            idm: "0123456789abcdef".to_string(),
-            student_id: Some(12_345_678),
-            balance: None,
-        };
+        let card_id = test_card(TouchIntent::Toggle);
 
         // 時計のモック設定（午前9時に固定）
         let mock_time = Local.with_ymd_and_hms(2025, 4, 21, 9, 0, 0).unwrap();
@@ -71,7 +129,6 @@ mod tests {
 
         // サウンドプレイヤーのモック設定
         let mut mock_player = MockSoundPlayer::new();
-        mock_player.expect_reset().times(1).return_const(());
         mock_player
             .expect_play()
             .with(eq(SoundEvent::Touch))
@@ -97,9 +154,9 @@ mod tests {
     async fn test_exit_last_person() {
         // Suicaカードのモックデータ
         let card_id = Card {
-            idm: "0123456789abcdef".to_string(),
             student_id: None,
             balance: Some(1234),
+            ..test_card(TouchIntent::Toggle)
         };
 
         // 時計のモック設定（夕方18時に固定だが、退出時には使用されない）
@@ -119,7 +176,6 @@ mod tests {
 
         // サウンドプレイヤーのモック設定
         let mut mock_player = MockSoundPlayer::new();
-        mock_player.expect_reset().times(1).return_const(());
         mock_player
             .expect_play()
             .with(eq(SoundEvent::Touch))
@@ -151,9 +207,8 @@ mod tests {
     async fn test_unregistered_card() {
         // 未登録の学生証
         let card_id = Card {
-            idm: "0123456789abcdef".to_string(),
             student_id: Some(99_999_999),
-            balance: None,
+            ..test_card(TouchIntent::Toggle)
         };
 
         // API通信のモック設定
@@ -167,7 +222,6 @@ mod tests {
 
         // サウンドプレイヤーのモック設定
         let mut mock_player = MockSoundPlayer::new();
-        mock_player.expect_reset().times(1).return_const(());
         mock_player
             .expect_play()
             .with(eq(SoundEvent::Touch))
@@ -191,4 +245,717 @@ mod tests {
         // executeを非同期で直接呼び出す
         use_case.execute(&card_id).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_unregistered_nfc_card_reads_out_code() {
+        // 登録コードを 1 桁ずつ読み上げる
+        let card_id = Card {
+            student_id: None,
+            ..test_card(TouchIntent::Toggle)
+        };
+
+        let mut mock_api = MockCardApi::new();
+        mock_api.expect_touch().times(1).returning(|_| {
+            Ok(TouchCardResponse::Error {
+                error: "NFCカードが登録されていません".to_string(),
+                error_code: ErrorCode::NfcCardNotRegistered,
+                registration_code: Some("0420".to_string()),
+            })
+        });
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_speak()
+            .with(
+                eq("NFCカードの登録コードは、ゼロ、ヨン、ニー、ゼロ、です"),
+                eq(SoundEvent::RegisterNfcCard),
+            )
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), MockDoorLock::new());
+
+        use_case.execute(&card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exit_reader_sends_intent() {
+        // 退出専用リーダーでのタッチ
+        let card_id = test_card(TouchIntent::Exit);
+
+        // API通信のモック設定（退出intentとリーダーIDが送られること）
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .with(function(|req: &TouchCardRequest| {
+                req.intent == TouchIntent::Exit && req.reader == test_reader()
+            }))
+            .times(1)
+            .returning(|_| Ok(TouchCardResponse::success_exit(3)));
+
+        // サウンドプレイヤーのモック設定
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::GoodBye))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().times(1).returning(|| Ok(()));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock);
+
+        use_case.execute(&card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exit_reader_mismatched_entry_response() {
+        // 退出専用リーダーに対して入室が返ってきた場合（旧サーバー）
+        let card_id = test_card(TouchIntent::Exit);
+
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(1)
+            .returning(|_| Ok(TouchCardResponse::success_entry(1)));
+
+        // 在室していない旨の案内のみ再生する
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::NotEntered))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        // 解錠はしない
+        let mock_door_lock = MockDoorLock::new();
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock);
+
+        use_case.execute(&card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_entry_reader_already_entered() {
+        // 入室専用リーダーで既に在室中のユーザー
+        let card_id = test_card(TouchIntent::Entry);
+
+        let mut mock_api = MockCardApi::new();
+        mock_api.expect_touch().times(1).returning(|_| {
+            Ok(TouchCardResponse::error(
+                ErrorCode::AlreadyEntered,
+                "既に入室しています",
+            ))
+        });
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::AlreadyEntered))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mock_door_lock = MockDoorLock::new();
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock);
+
+        use_case.execute(&card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_lockdown_from_response_does_not_unlock() {
+        // APIがロックダウンを指示した場合は記録だけして解錠しない
+        let card_id = test_card(TouchIntent::Toggle);
+
+        let mut mock_api = MockCardApi::new();
+        mock_api.expect_touch().times(1).returning(|_| {
+            Ok(TouchCardResponse::Success {
+                status: RoomEntryStatus::Exit,
+                entries: 2,
+                access: AccessLevel::Unlock,
+                display_name: None,
+                first_entry_today: false,
+                last_entry_at: None,
+                lock_mode: Some(LockMode::Lockdown),
+            })
+        });
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::GoodBye))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Lockdown))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mock_door_lock = MockDoorLock::new();
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock);
+
+        use_case.execute(&card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_response_lock_mode_is_shared() {
+        // 応答の lock_mode はスケジュールにも引き継ぐ
+        let lock_mode = LockModeOverride::default();
+        let use_case = use_case_with(
+            Ok(TouchCardResponse::Success {
+                status: RoomEntryStatus::Exit,
+                entries: 2,
+                access: AccessLevel::Unlock,
+                display_name: None,
+                first_entry_today: false,
+                last_entry_at: None,
+                lock_mode: Some(LockMode::Lockdown),
+            }),
+            Ok(()),
+        )
+        .with_lock_mode_override(lock_mode.clone());
+
+        use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap();
+        assert_eq!(lock_mode.get(), Some(LockMode::Lockdown));
+
+        // lock_mode を返さない応答で設定どおりに戻る
+        let use_case = use_case_with(Ok(TouchCardResponse::success_exit(1)), Ok(()))
+            .with_lock_mode_override(lock_mode.clone());
+
+        use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap();
+        assert_eq!(lock_mode.get(), None);
+    }
+
+    #[tokio::test]
+    async fn test_open_hours_leave_door_alone() {
+        // 開室時間中はスケジュールで開けているので解錠操作をしない
+        let card_id = test_card(TouchIntent::Toggle);
+
+        let mut mock_clock = MockClock::new();
+        // 2026-10-19 は月曜日
+        let mock_time = Local.with_ymd_and_hms(2026, 10, 19, 17, 0, 0).unwrap();
+        mock_clock.expect_now().returning(move || mock_time);
+
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(1)
+            .returning(|_| Ok(TouchCardResponse::success_exit(2)));
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::GoodBye))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mock_door_lock = MockDoorLock::new();
+
+        let policy = LockPolicy::new(
+            LockMode::Normal,
+            vec!["mon-fri@16:00-19:00".parse().unwrap()],
+            HashSet::new(),
+        );
+        let use_case = TouchCardUseCase::new(mock_api, mock_player, mock_clock, mock_door_lock)
+            .with_lock_policy(policy);
+
+        use_case.execute(&card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_record_only_access_does_not_unlock() {
+        // 在室記録のみ許可されたユーザー（卒業生など）
+        let card_id = test_card(TouchIntent::Toggle);
+
+        let mut mock_clock = MockClock::new();
+        let mock_time = Local.with_ymd_and_hms(2026, 10, 19, 21, 0, 0).unwrap();
+        mock_clock.expect_now().returning(move || mock_time);
+
+        let mut mock_api = MockCardApi::new();
+        mock_api.expect_touch().times(1).returning(|_| {
+            Ok(TouchCardResponse::Success {
+                status: RoomEntryStatus::Entry,
+                entries: 1,
+                access: AccessLevel::RecordOnly,
+                display_name: None,
+                first_entry_today: false,
+                last_entry_at: None,
+                lock_mode: None,
+            })
+        });
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::GoodEvening))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::RecordOnly))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mock_door_lock = MockDoorLock::new();
+
+        let use_case = TouchCardUseCase::new(mock_api, mock_player, mock_clock, mock_door_lock);
+
+        use_case.execute(&card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_entry_greets_by_display_name() {
+        // 表示名があれば名前入りの挨拶を読み上げる
+        let card_id = test_card(TouchIntent::Toggle);
+
+        let mut mock_clock = MockClock::new();
+        let mock_time = Local.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        mock_clock.expect_now().returning(move || mock_time);
+
+        let mut mock_api = MockCardApi::new();
+        mock_api.expect_touch().times(1).returning(|_| {
+            Ok(TouchCardResponse::Success {
+                status: RoomEntryStatus::Entry,
+                entries: 1,
+                access: AccessLevel::Unlock,
+                display_name: Some("山田".to_string()),
+                first_entry_today: false,
+                last_entry_at: None,
+                lock_mode: None,
+            })
+        });
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_speak()
+            .with(
+                eq("おはようございます、山田さん"),
+                eq(SoundEvent::GoodMorning),
+            )
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().times(1).returning(|| Ok(()));
+
+        let use_case = TouchCardUseCase::new(mock_api, mock_player, mock_clock, mock_door_lock);
+
+        use_case.execute(&card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_blank_display_name_plays_stock_sound() {
+        let card_id = test_card(TouchIntent::Toggle);
+
+        let mut mock_api = MockCardApi::new();
+        mock_api.expect_touch().times(1).returning(|_| {
+            Ok(TouchCardResponse::Success {
+                status: RoomEntryStatus::Exit,
+                entries: 3,
+                access: AccessLevel::Unlock,
+                display_name: Some("  ".to_string()),
+                first_entry_today: false,
+                last_entry_at: None,
+                lock_mode: None,
+            })
+        });
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::GoodBye))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().times(1).returning(|| Ok(()));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock);
+
+        use_case.execute(&card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sound_failure_still_unlocks() {
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(1)
+            .returning(|_| Ok(TouchCardResponse::success_exit(1)));
+
+        // スピーカーが壊れていても解錠は止めない
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .times(2)
+            .returning(|_| Err(SoundError::Decode(anyhow::anyhow!("broken wav"))));
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().times(1).returning(|| Ok(()));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock);
+
+        use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unlock_failure_plays_error() {
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(1)
+            .returning(|_| Ok(TouchCardResponse::success_exit(1)));
+
+        // 開かなかったのに挨拶はしない
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Error))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock
+            .expect_unlock()
+            .times(1)
+            .returning(|| Err(LockError::Actuator(anyhow::anyhow!("servo stalled"))));
+
+        let indicator = RecordingIndicator::default();
+        let display = RecordingDisplay::default();
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock)
+                .with_indicator(indicator.clone())
+                .with_display(display.clone());
+
+        let error = use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            TouchCardError::DoorLock(LockError::Actuator(_))
+        ));
+        // 成功表示を挟まずにエラーを出す
+        assert_eq!(
+            indicator.patterns(),
+            vec![IndicatorPattern::Reading, IndicatorPattern::Error]
+        );
+        assert_eq!(
+            display.screens(),
+            vec![
+                DisplayScreen::Reading,
+                DisplayScreen::LockFailed { entries: 1 }
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unlock_happens_before_greeting() {
+        let mut seq = Sequence::new();
+
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(1)
+            .returning(|_| Ok(TouchCardResponse::success_exit(0)));
+
+        let mut mock_player = MockSoundPlayer::new();
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+        mock_door_lock
+            .expect_unlock()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::GoodBye))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Last))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock);
+
+        use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_every_sound_and_unlock_failure_combination() {
+        for (sound_fails, unlock_fails) in
+            [(false, false), (true, false), (false, true), (true, true)]
+        {
+            let mut mock_api = MockCardApi::new();
+            mock_api
+                .expect_touch()
+                .times(1)
+                .returning(|_| Ok(TouchCardResponse::success_exit(1)));
+
+            let mut mock_player = MockSoundPlayer::new();
+            mock_player.expect_play().returning(move |_| {
+                if sound_fails {
+                    Err(SoundError::Decode(anyhow::anyhow!("broken wav")))
+                } else {
+                    Ok(())
+                }
+            });
+
+            // 音の成否にかかわらず解錠は1回だけ試みる
+            let mut mock_door_lock = MockDoorLock::new();
+            mock_door_lock.expect_unlock().times(1).returning(move || {
+                if unlock_fails {
+                    Err(LockError::Stopped)
+                } else {
+                    Ok(())
+                }
+            });
+
+            let use_case =
+                TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock);
+
+            let result = use_case.execute(&test_card(TouchIntent::Toggle)).await;
+            assert_eq!(
+                result.is_err(),
+                unlock_fails,
+                "sound_fails={sound_fails}, unlock_fails={unlock_fails}"
+            );
+            if unlock_fails {
+                assert!(matches!(
+                    result,
+                    Err(TouchCardError::DoorLock(LockError::Stopped))
+                ));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_speech_failure_still_unlocks() {
+        let mut mock_api = MockCardApi::new();
+        mock_api.expect_touch().times(1).returning(|_| {
+            Ok(TouchCardResponse::Success {
+                status: RoomEntryStatus::Exit,
+                entries: 3,
+                access: AccessLevel::Unlock,
+                display_name: Some("たろう".to_string()),
+                first_entry_today: false,
+                last_entry_at: None,
+                lock_mode: None,
+            })
+        });
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_speak()
+            .times(1)
+            .returning(|_, _| Err(SoundError::Decode(anyhow::anyhow!("broken wav"))));
+
+        let mut mock_door_lock = MockDoorLock::new();
+        mock_door_lock.expect_unlock().times(1).returning(|| Ok(()));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), mock_door_lock);
+
+        use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_record_only_with_broken_speaker_does_not_unlock() {
+        let mut mock_api = MockCardApi::new();
+        mock_api.expect_touch().times(1).returning(|_| {
+            Ok(TouchCardResponse::Success {
+                status: RoomEntryStatus::Exit,
+                entries: 1,
+                access: AccessLevel::RecordOnly,
+                display_name: None,
+                first_entry_today: false,
+                last_entry_at: None,
+                lock_mode: None,
+            })
+        });
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .times(3)
+            .returning(|_| Err(SoundError::Decode(anyhow::anyhow!("broken wav"))));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), MockDoorLock::new());
+
+        use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_api_failure_with_broken_speaker_reports_api_error() {
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(1)
+            .returning(|_| Err(ApiError::Status(400)));
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .times(2)
+            .returning(|_| Err(SoundError::Decode(anyhow::anyhow!("broken wav"))));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), MockDoorLock::new());
+
+        let error = use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, TouchCardError::Api(ApiError::Status(400))));
+    }
+
+    #[tokio::test]
+    async fn test_api_timeout_is_offline() {
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(1)
+            .returning(|_| Err(ApiError::Timeout));
+
+        // オフライン時はエラー音を鳴らさない
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), MockDoorLock::new());
+
+        let error = use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, TouchCardError::Api(ApiError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_api_rejection_plays_error() {
+        let mut mock_api = MockCardApi::new();
+        mock_api
+            .expect_touch()
+            .times(1)
+            .returning(|_| Err(ApiError::Status(401)));
+
+        let mut mock_player = MockSoundPlayer::new();
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Touch))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_player
+            .expect_play()
+            .with(eq(SoundEvent::Error))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let use_case =
+            TouchCardUseCase::new(mock_api, mock_player, MockClock::new(), MockDoorLock::new());
+
+        let error = use_case
+            .execute(&test_card(TouchIntent::Toggle))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, TouchCardError::Api(ApiError::Status(401))));
+    }
 }
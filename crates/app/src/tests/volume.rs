@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::app::{SoundScheduler, VolumePolicy};
+    use crate::domain::{SoundEvent, SoundPlayer, SoundRequest};
+    use crate::tests::lock_policy::{clock_at, monday_at};
+    use crate::tests::sound_scheduler::FakeOutput;
+    use crate::tests::touch_card::MockClock;
+
+    fn quiet_policy(chime: bool) -> VolumePolicy {
+        VolumePolicy::new(0.8, HashMap::from([(SoundEvent::Touch, 0.5)])).with_quiet_hours(
+            vec!["22:00-07:00".parse().unwrap()],
+            0.25,
+            chime,
+        )
+    }
+
+    fn assert_gain(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_default_policy_keeps_volume_without_clock() {
+        let request = SoundRequest::Event(SoundEvent::Hello);
+
+        let (played, gain) = VolumePolicy::default().apply(&MockClock::new(), &request);
+
+        assert_eq!(played, request);
+        assert_gain(gain, 1.0);
+    }
+
+    #[test]
+    fn test_master_and_event_gain_multiply() {
+        let policy = quiet_policy(false);
+        let clock = clock_at(monday_at(12, 0));
+
+        let (_, touch) = policy.apply(&clock, &SoundRequest::Event(SoundEvent::Touch));
+        let (_, hello) = policy.apply(&clock, &SoundRequest::Event(SoundEvent::Hello));
+
+        assert_gain(touch, 0.4);
+        assert_gain(hello, 0.8);
+    }
+
+    #[test]
+    fn test_quiet_hours_lower_volume_across_midnight() {
+        let policy = quiet_policy(false);
+        let hello = SoundRequest::Event(SoundEvent::Hello);
+
+        for (hour, min, expected) in [
+            (21, 59, 0.8),
+            (22, 0, 0.2),
+            (3, 0, 0.2),
+            (6, 59, 0.2),
+            (7, 0, 0.8),
+        ] {
+            let (played, gain) = policy.apply(&clock_at(monday_at(hour, min)), &hello);
+            assert_eq!(played, hello);
+            assert_gain(gain, expected);
+        }
+    }
+
+    #[test]
+    fn test_quiet_chime_replaces_greetings_only() {
+        let policy = quiet_policy(true);
+        let clock = clock_at(monday_at(23, 0));
+
+        let speech = SoundRequest::Speech {
+            text: "こんばんは、山田さん".to_string(),
+            fallback: SoundEvent::GoodEvening,
+        };
+        for greeting in [SoundRequest::Event(SoundEvent::GoodEvening), speech] {
+            let (played, _) = policy.apply(&clock, &greeting);
+            assert_eq!(played, SoundRequest::Event(SoundEvent::Chime));
+        }
+        // 案内は静音時間帯でも聞こえるよう差し替えない
+        let guidance = SoundRequest::Event(SoundEvent::RegisterStudentCard);
+        let (played, gain) = policy.apply(&clock, &guidance);
+        assert_eq!(played, guidance);
+        assert_gain(gain, 0.2);
+    }
+
+    #[test]
+    fn test_quiet_chime_keeps_last_person_guidance() {
+        // 最後の退出者への案内は挨拶ではないのでチャイムにしない
+        let policy = quiet_policy(true);
+        let clock = clock_at(monday_at(23, 0));
+
+        let last = SoundRequest::Event(SoundEvent::Last);
+        let (played, gain) = policy.apply(&clock, &last);
+        assert_eq!(played, last);
+        assert_gain(gain, 0.2);
+    }
+
+    #[test]
+    fn test_scheduler_applies_policy_when_sound_starts() {
+        let output = FakeOutput::default();
+        let scheduler = SoundScheduler::new(output.clone(), clock_at(monday_at(23, 30)))
+            .with_volume(quiet_policy(true));
+
+        scheduler.play(SoundEvent::Touch).unwrap();
+        scheduler.play(SoundEvent::GoodEvening).unwrap();
+        output.finish();
+        scheduler.play(SoundEvent::Last).unwrap();
+
+        assert_eq!(output.started(), vec![SoundEvent::Touch, SoundEvent::Chime]);
+        let gains = output.gains();
+        assert_gain(gains[0], 0.1);
+        assert_gain(gains[1], 0.2);
+    }
+}
@@ -0,0 +1 @@
+ALTER TABLE `users` ADD `access` text DEFAULT 'unlock' NOT NULL;
\ No newline at end of file
@@ -0,0 +1,19 @@
+import { describe, expect, it } from "vitest";
+
+import { traceIdFromTraceparent } from "../logger";
+
+describe("traceIdFromTraceparent", () => {
+  it("traceparent からトレース ID を取り出すこと", () => {
+    const traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    expect(traceIdFromTraceparent(traceparent)).toBe("4bf92f3577b34da6a3ce929d0e0e4736");
+  });
+
+  it("ヘッダーがないか不正な場合は undefined を返すこと", () => {
+    const invalidTraceId = "00-00000000000000000000000000000000-00f067aa0ba902b7-01";
+
+    expect(traceIdFromTraceparent(undefined)).toBeUndefined();
+    expect(traceIdFromTraceparent("not-a-traceparent")).toBeUndefined();
+    expect(traceIdFromTraceparent(invalidTraceId)).toBeUndefined();
+  });
+});
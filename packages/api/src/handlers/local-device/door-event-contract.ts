@@ -0,0 +1,14 @@
+import { z } from "zod";
+
+export const DoorEventRequestSchema = z.object({
+  event: z.union([
+    z.literal("opened"),
+    z.literal("closed"),
+    z.literal("left_open"),
+    z.literal("manual_unlock"),
+    z.literal("manual_lock"),
+  ]),
+  open_secs: z.number().int().nonnegative().optional(),
+});
+
+export type DoorEventRequest = z.infer<typeof DoorEventRequestSchema>;
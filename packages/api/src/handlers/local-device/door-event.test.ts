@@ -0,0 +1,68 @@
+import { Hono } from "hono";
+import { describe, expect, it, vi } from "vitest";
+
+import type { AppEnv } from "@/env";
+
+import { DoorEventHandler } from "./door-event";
+
+const createApp = (discordService: { sendMessage: ReturnType<typeof vi.fn> }) => {
+  const handler = new DoorEventHandler(discordService as never);
+  const app = new Hono<AppEnv>();
+  app.post("/local-device/door-event", (c) => handler.handle(c));
+  return app;
+};
+
+const postDoorEvent = (app: Hono<AppEnv>, body: unknown) =>
+  app.request("/local-device/door-event", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify(body),
+  });
+
+describe("DoorEventHandler", () => {
+  it("開けっ放しを Discord に通知すること", async () => {
+    const discordService = { sendMessage: vi.fn().mockResolvedValue(undefined) };
+    const app = createApp(discordService);
+
+    const res = await postDoorEvent(app, { event: "left_open", open_secs: 300 });
+
+    expect(res.status).toBe(204);
+    expect(discordService.sendMessage).toHaveBeenCalledTimes(1);
+    const [message] = discordService.sendMessage.mock.calls[0];
+    expect(message.embeds[0].title).toContain("開けっ放し");
+    expect(message.embeds[0].description).toContain("5分以上");
+  });
+
+  it("開閉や手動操作は通知せずに受け付けること", async () => {
+    const discordService = { sendMessage: vi.fn() };
+    const app = createApp(discordService);
+
+    for (const body of [
+      { event: "opened" },
+      { event: "closed", open_secs: 12 },
+      { event: "manual_unlock" },
+      { event: "manual_lock" },
+    ]) {
+      const res = await postDoorEvent(app, body);
+      expect(res.status).toBe(204);
+    }
+    expect(discordService.sendMessage).not.toHaveBeenCalled();
+  });
+
+  it("不正なリクエストは 400 を返すこと", async () => {
+    const discordService = { sendMessage: vi.fn() };
+    const app = createApp(discordService);
+
+    const unknownEvent = await postDoorEvent(app, { event: "exploded" });
+    const negativeSecs = await postDoorEvent(app, { event: "closed", open_secs: -1 });
+    const notJson = await app.request("/local-device/door-event", {
+      method: "POST",
+      body: "not json",
+    });
+
+    expect(unknownEvent.status).toBe(400);
+    expect(negativeSecs.status).toBe(400);
+    expect(notJson.status).toBe(400);
+    expect(discordService.sendMessage).not.toHaveBeenCalled();
+  });
+});
@@ -0,0 +1,66 @@
+import type { RESTPostAPIChannelMessageJSONBody } from "discord-api-types/v10";
+import type { Context } from "hono";
+
+import { colorToHex } from "@/discord";
+import type { AppEnv } from "@/env";
+import type { AppLogger } from "@/logger";
+import { noopLogger, serializeError } from "@/logger";
+import type { DiscordService } from "@/services/DiscordService";
+
+import type { DoorEventRequest } from "./door-event-contract";
+import { DoorEventRequestSchema } from "./door-event-contract";
+
+import type { LocalDeviceHandler } from ".";
+
+export class DoorEventHandler implements LocalDeviceHandler {
+  constructor(
+    private readonly discordService: DiscordService,
+    private readonly logger: AppLogger = noopLogger,
+  ) {}
+
+  async handle(c: Context<AppEnv>): Promise<Response> {
+    let body: unknown;
+    try {
+      body = await c.req.json();
+    } catch (error) {
+      this.logger.warn("Failed to parse door event request body", serializeError(error));
+      return c.text("Invalid request", 400);
+    }
+
+    const request = DoorEventRequestSchema.safeParse(body);
+    if (!request.success) {
+      this.logger.warn("Door event request validation failed", {
+        issues: request.error.issues,
+      });
+      return c.text("Invalid request", 400);
+    }
+
+    const { event, open_secs: openSecs } = request.data;
+    this.logger.info("Handling door event request", { event, openSecs });
+
+    const message = leftOpenMessage(request.data);
+    if (message) {
+      await this.discordService.sendMessage(message);
+    }
+
+    return c.body(null, 204);
+  }
+}
+
+// 開けっ放しだけを Discord に通知し、それ以外のイベントはログに残すだけにする
+function leftOpenMessage(request: DoorEventRequest): RESTPostAPIChannelMessageJSONBody | null {
+  if (request.event !== "left_open") {
+    return null;
+  }
+
+  const minutes = Math.floor((request.open_secs ?? 0) / 60);
+  return {
+    embeds: [
+      {
+        title: "ドアが開けっ放しになっています",
+        description: minutes > 0 ? `${minutes}分以上ドアが開いたままです` : "ドアが開いたままです",
+        color: colorToHex("red"),
+      },
+    ],
+  };
+}
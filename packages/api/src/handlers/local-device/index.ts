@@ -5,6 +5,7 @@ import type { AppLogger } from "@/logger";
 import type { Services } from "@/services";
 import type { UseCases } from "@/usecase";
 
+import { DoorEventHandler } from "./door-event";
 import { TouchCardHandler } from "./touch-card";
 import { TouchCardPresenter } from "./touch-card-presenter";
 
@@ -14,6 +15,7 @@ export interface LocalDeviceHandler {
 
 export interface LocalDeviceHandlers {
   touchCard: TouchCardHandler;
+  doorEvent: DoorEventHandler;
 }
 
 export function createLocalDeviceHandlers(
@@ -31,5 +33,6 @@ export function createLocalDeviceHandlers(
       services.discord,
       logger.child({ tag: "touch-card" }),
     ),
+    doorEvent: new DoorEventHandler(services.discord, logger.child({ tag: "door-event" })),
   };
 }
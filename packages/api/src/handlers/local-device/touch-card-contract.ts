@@ -3,6 +3,13 @@ import { z } from "zod";
 export const TouchCardRequestSchema = z.object({
   idm: z.string(),
   student_id: z.number().optional(),
+  intent: z.union([z.literal("entry"), z.literal("exit"), z.literal("toggle")]).optional(),
+  reader: z
+    .object({
+      usb_path: z.string(),
+      label: z.string().optional(),
+    })
+    .optional(),
 });
 
 export const TouchCardResponseSchema = z.union([
@@ -10,11 +17,16 @@ export const TouchCardResponseSchema = z.union([
     success: z.literal(true),
     status: z.union([z.literal("entry"), z.literal("exit")]),
     entries: z.number(),
+    access: z.union([z.literal("unlock"), z.literal("record_only")]).optional(),
+    display_name: z.string().optional(),
+    first_entry_today: z.boolean().optional(),
+    last_entry_at: z.string().optional(),
   }),
   z.object({
     success: z.literal(false),
     error: z.string(),
     error_code: z.string(),
+    registration_code: z.string().optional(),
   }),
 ]);
 
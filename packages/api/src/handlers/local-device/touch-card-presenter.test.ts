@@ -1,3 +1,4 @@
+import { Temporal } from "@js-temporal/polyfill";
 import { err, ok } from "neverthrow";
 import { describe, expect, it, vi } from "vitest";
 
@@ -36,6 +37,8 @@ describe("TouchCardPresenter", () => {
         status: "entry",
         entries: 3,
         user: new User(1, "discord-user"),
+        firstEntryToday: true,
+        lastEntryAt: Temporal.Instant.from("2026-10-01T09:00:00Z"),
       }),
     );
 
@@ -44,11 +47,44 @@ describe("TouchCardPresenter", () => {
       success: true,
       status: "entry",
       entries: 3,
+      access: "unlock",
+      display_name: "Alice",
+      first_entry_today: true,
+      last_entry_at: "2026-10-01T09:00:00Z",
     });
     expect(presentation.embed.title).toContain("Aliceさんが入室しました");
     expect(presentation.embed.description).toContain("3人が入室中です");
   });
 
+  it("解錠しない利用者には record_only を返すこと", async () => {
+    const presenter = new TouchCardPresenter(
+      {
+        fetchUserInfo: vi.fn().mockResolvedValue({
+          name: "Bob",
+          iconUrl: "https://example.com/icon.png",
+        }),
+      } as never,
+      createEnv(),
+    );
+
+    const presentation = await presenter.present(
+      ok({
+        status: "entry",
+        entries: 1,
+        user: new User(2, "discord-user", "record_only"),
+        firstEntryToday: false,
+        lastEntryAt: null,
+      }),
+    );
+
+    expect(presentation.response).toMatchObject({
+      success: true,
+      access: "record_only",
+      first_entry_today: false,
+      last_entry_at: undefined,
+    });
+  });
+
   it("未登録 NFC カード時に登録案内を返すこと", async () => {
     const presenter = new TouchCardPresenter(
       {
@@ -69,7 +105,30 @@ describe("TouchCardPresenter", () => {
       success: false,
       error: "NFC card not registered.",
       error_code: "NFC_CARD_NOT_REGISTERED",
+      registration_code: "0420",
     });
     expect(presentation.embed.description).toContain("0420");
   });
+
+  it("リーダーの役割と在室状態が矛盾する場合にエラーコードを返すこと", async () => {
+    const presenter = new TouchCardPresenter(
+      {
+        fetchUserInfo: vi.fn(),
+      } as never,
+      createEnv(),
+    );
+    const error = new TouchCardError("User already entered.", {
+      meta: {
+        code: "ALREADY_ENTERED",
+      },
+    });
+
+    const presentation = await presenter.present(err(error));
+
+    expect(presentation.response).toEqual({
+      success: false,
+      error: "User already entered.",
+      error_code: "ALREADY_ENTERED",
+    });
+  });
 });
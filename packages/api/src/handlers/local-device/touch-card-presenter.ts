@@ -52,6 +52,10 @@ export class TouchCardPresenter {
         success: true,
         status: result.status,
         entries: result.entries,
+        access: result.user.access,
+        display_name: userInfo.name,
+        first_entry_today: result.firstEntryToday,
+        last_entry_at: result.lastEntryAt?.toString(),
       },
     };
   }
@@ -71,6 +75,18 @@ export class TouchCardPresenter {
             description: `</room register nfc-card:${this.env.DISCORD_ROOM_COMMAND_ID}>で\`${error.meta.unknownNfcCard.code}\`を使用してNFCカードを登録してください。`,
             color: colorToHex("red"),
           };
+        case "ALREADY_ENTERED":
+          return {
+            title: "入室済みです",
+            description: "入室専用のリーダーにタッチしましたが、既に入室しています。",
+            color: colorToHex("red"),
+          };
+        case "NOT_ENTERED":
+          return {
+            title: "入室していません",
+            description: "退出専用のリーダーにタッチしましたが、入室していません。",
+            color: colorToHex("red"),
+          };
         case "UNKNOWN":
           return {
             title: "エラーが発生しました",
@@ -86,6 +102,9 @@ export class TouchCardPresenter {
         success: false,
         error: error.message,
         error_code: error.meta.code,
+        ...(error.meta.code === "NFC_CARD_NOT_REGISTERED"
+          ? { registration_code: error.meta.unknownNfcCard.code }
+          : {}),
       },
     };
   }
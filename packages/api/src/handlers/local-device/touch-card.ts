@@ -37,12 +37,14 @@ export class TouchCardHandler implements LocalDeviceHandler {
       return c.text("Invalid request", 400);
     }
 
-    const { idm, student_id: studentId } = request.data;
+    const { idm, student_id: studentId, intent, reader } = request.data;
     this.logger.info("Handling touch card request", {
       idm,
+      intent,
+      reader,
       studentId,
     });
-    const result = await this.usecase.execute({ idm, studentId });
+    const result = await this.usecase.execute({ idm, studentId, intent });
     const presentation = await this.presenter.present(result);
 
     const message: RESTPostAPIChannelMessageJSONBody = {
@@ -18,7 +18,7 @@ import { createLocalDeviceHandlers } from "./handlers/local-device";
 import { createScheduledHandlers } from "./handlers/scheduled";
 import { createSlashCommandHandlers, handleSlashCommand } from "./handlers/slash-command";
 import type { AppLogger } from "./logger";
-import { createLogger, serializeError } from "./logger";
+import { createLogger, serializeError, traceIdFromTraceparent } from "./logger";
 import { createRepositories } from "./repositories";
 import { createServices } from "./services";
 import { createUseCases } from "./usecase";
@@ -47,6 +47,7 @@ function getRouteKind(path: string): string {
 const app = new Hono<AppEnv>()
   .use(async (c, next) => {
     const startedAt = Date.now();
+    const traceId = traceIdFromTraceparent(c.req.header("traceparent"));
     const logger = createLogger({
       tag: "api",
       context: {
@@ -54,6 +55,7 @@ const app = new Hono<AppEnv>()
         method: c.req.method,
         path: c.req.path,
         routeKind: getRouteKind(c.req.path),
+        ...(traceId ? { traceId } : {}),
       },
     });
     logger.info("Request started");
@@ -113,6 +115,16 @@ const app = new Hono<AppEnv>()
     const res = await localDeviceHandlers.touchCard.handle(c);
     return res;
   })
+  .post("/local-device/door-event", async (c) => {
+    const usecases = c.get("usecases");
+    const services = c.get("services");
+    const env = c.get("env");
+    const logger = c.get("logger").child({ tag: "local-device" });
+    const localDeviceHandlers = createLocalDeviceHandlers(usecases, services, env, logger);
+
+    const res = await localDeviceHandlers.doorEvent.handle(c);
+    return res;
+  })
   .post("/interaction", interactionVerifier, async (c) => {
     const usecases = c.get("usecases");
     const services = c.get("services");
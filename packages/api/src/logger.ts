@@ -93,6 +93,21 @@ export function serializeError(error: unknown): LogContext {
   return serialized;
 }
 
+// W3C Trace Context: version-traceid-parentid-flags
+const TRACEPARENT_PATTERN = /^[\da-f]{2}-([\da-f]{32})-[\da-f]{16}-[\da-f]{2}$/;
+
+/**
+ * `traceparent` ヘッダーからトレース ID を取り出す。端末のトレースとログを突き合わせるのに使う
+ */
+export function traceIdFromTraceparent(header: string | undefined): string | undefined {
+  const traceId = header?.trim().match(TRACEPARENT_PATTERN)?.[1];
+  if (!traceId || /^0+$/.test(traceId)) {
+    return undefined;
+  }
+
+  return traceId;
+}
+
 function joinTags(parentTag?: string, childTag?: string): string | undefined {
   if (parentTag && childTag) {
     return `${parentTag}:${childTag}`;
@@ -1,6 +1,12 @@
+/**
+ * タッチ時に解錠するか (`unlock`)、在室を記録するだけか (`record_only`)
+ */
+export type UserAccess = "unlock" | "record_only";
+
 export class User {
   constructor(
     public readonly id: number,
     public readonly discordId: string,
+    public readonly access: UserAccess = "unlock",
   ) {}
 }
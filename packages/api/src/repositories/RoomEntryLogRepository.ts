@@ -1,5 +1,5 @@
 import { Temporal } from "@js-temporal/polyfill";
-import { and, eq, inArray, isNull } from "drizzle-orm";
+import { and, eq, gte, inArray, isNull } from "drizzle-orm";
 
 import type { Database } from "@/database";
 import type { AppLogger } from "@/logger";
@@ -11,9 +11,26 @@ const TOGGLE_RETRY_LIMIT = 5;
 
 export type RoomEntryToggleStatus = "entry" | "exit";
 
+export type RoomEntryIntent = RoomEntryToggleStatus | "toggle";
+
 export interface RoomEntryLogRepository {
-  toggle(userId: number, at: Temporal.Instant): Promise<RoomEntryToggleStatus>;
+  /**
+   * 入退室を切り替える。`intent` が今の在室状態と矛盾する場合は何も変更せず `null` を返す。
+   */
+  toggle(
+    userId: number,
+    at: Temporal.Instant,
+    intent?: RoomEntryIntent,
+  ): Promise<RoomEntryToggleStatus | null>;
   findAllEntry(): Promise<RoomEntryLog[]>;
+  /**
+   * 利用者の最後の入室日時を返す。入室したことがなければ `null`。
+   */
+  findLatestEntryAt(userId: number): Promise<Temporal.Instant | null>;
+  /**
+   * `at` 以降に誰かが入室していれば `true` を返す。
+   */
+  existsEntrySince(at: Temporal.Instant): Promise<boolean>;
   setManyExitAt(entryLogIds: number[], exitAt: Temporal.Instant): Promise<void>;
 }
 
@@ -23,7 +40,11 @@ export class DBRoomEntryLogRepository implements RoomEntryLogRepository {
     private readonly logger: AppLogger = noopLogger,
   ) {}
 
-  async toggle(userId: number, at: Temporal.Instant): Promise<RoomEntryToggleStatus> {
+  async toggle(
+    userId: number,
+    at: Temporal.Instant,
+    intent: RoomEntryIntent = "toggle",
+  ): Promise<RoomEntryToggleStatus | null> {
     for (let attempt = 0; attempt < TOGGLE_RETRY_LIMIT; attempt += 1) {
       const openEntryLog = await this.db.query.roomEntryLogs.findFirst({
         where: (roomEntryLogs, { and, eq, isNull }) =>
@@ -31,6 +52,18 @@ export class DBRoomEntryLogRepository implements RoomEntryLogRepository {
         orderBy: (roomEntryLogs, { desc }) => desc(roomEntryLogs.entryAt),
       });
 
+      if (openEntryLog && intent === "entry") {
+        this.logger.info("rejected entry of user already in the room", {
+          entryLogId: openEntryLog.id,
+          userId,
+        });
+        return null;
+      }
+      if (!openEntryLog && intent === "exit") {
+        this.logger.info("rejected exit of user not in the room", { userId });
+        return null;
+      }
+
       if (openEntryLog) {
         const closedEntryLogs = await this.db
           .update(schema.roomEntryLogs)
@@ -117,6 +150,26 @@ export class DBRoomEntryLogRepository implements RoomEntryLogRepository {
     );
   }
 
+  async findLatestEntryAt(userId: number): Promise<Temporal.Instant | null> {
+    const result = await this.db.query.roomEntryLogs.findFirst({
+      where: (roomEntryLogs, { eq }) => eq(roomEntryLogs.userId, userId),
+      orderBy: (roomEntryLogs, { desc }) => desc(roomEntryLogs.entryAt),
+    });
+
+    return result ? Temporal.Instant.fromEpochMilliseconds(result.entryAt) : null;
+  }
+
+  async existsEntrySince(at: Temporal.Instant): Promise<boolean> {
+    const result = await this.db
+      .select({ id: schema.roomEntryLogs.id })
+      .from(schema.roomEntryLogs)
+      .where(gte(schema.roomEntryLogs.entryAt, at.epochMilliseconds))
+      .limit(1)
+      .get();
+
+    return result != null;
+  }
+
   async setManyExitAt(entryLogIds: number[], exitAt: Temporal.Instant): Promise<void> {
     try {
       await this.db
@@ -37,7 +37,7 @@ export class DBUserRepository implements UserRepository {
         userId: result.id,
       });
 
-      return new User(result.id, result.discordId);
+      return new User(result.id, result.discordId, result.access);
     } catch (error) {
       this.logger.error("failed to create user", {
         discordId,
@@ -77,7 +77,7 @@ export class DBUserRepository implements UserRepository {
       where: (users, { inArray }) => inArray(users.id, ids),
     });
 
-    return result.map((user) => new User(user.id, user.discordId));
+    return result.map((user) => new User(user.id, user.discordId, user.access));
   }
 
   async findByDiscordId(discordId: string): Promise<User | null> {
@@ -86,7 +86,7 @@ export class DBUserRepository implements UserRepository {
     });
     if (!result) return null;
 
-    return new User(result.id, result.discordId);
+    return new User(result.id, result.discordId, result.access);
   }
 
   async findByStudentId(studentId: number): Promise<User | null> {
@@ -98,7 +98,7 @@ export class DBUserRepository implements UserRepository {
     });
     if (!result) return null;
 
-    return new User(result.user.id, result.user.discordId);
+    return new User(result.user.id, result.user.discordId, result.user.access);
   }
 
   async findByNfcIdm(idm: string): Promise<User | null> {
@@ -110,7 +110,7 @@ export class DBUserRepository implements UserRepository {
     });
     if (!result) return null;
 
-    return new User(result.user.id, result.user.discordId);
+    return new User(result.user.id, result.user.discordId, result.user.access);
   }
 
   async findAllEntryUsers(): Promise<User[]> {
@@ -121,6 +121,8 @@ export class DBUserRepository implements UserRepository {
       },
     });
 
-    return result.map((entryLog) => new User(entryLog.user.id, entryLog.user.discordId));
+    return result.map(
+      (entryLog) => new User(entryLog.user.id, entryLog.user.discordId, entryLog.user.access),
+    );
   }
 }
@@ -8,6 +8,7 @@ export const users = sqliteTable("users", {
 
   // columns
   discordId: text("discord_id").notNull().unique(),
+  access: text("access", { enum: ["unlock", "record_only"] }).notNull().default("unlock"),
 
   // timestamps
   createdAt: integer("created_at")
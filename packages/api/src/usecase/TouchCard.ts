@@ -7,16 +7,26 @@ import type { AppLogger } from "@/logger";
 import { noopLogger, serializeError } from "@/logger";
 import type { UnknownNfcCard } from "@/models/UnknownNfcCard";
 import type { User } from "@/models/User";
-import type { RoomEntryLogRepository } from "@/repositories/RoomEntryLogRepository";
+import type {
+  RoomEntryIntent,
+  RoomEntryLogRepository,
+} from "@/repositories/RoomEntryLogRepository";
 import type { UnknownNfcCardRepository } from "@/repositories/UnknownNfcCardRepository";
 import type { UserRepository } from "@/repositories/UserRepository";
 
 export type TouchCardStatus = "entry" | "exit";
 
+// 「今日」の区切りに使うタイムゾーン
+const TIME_ZONE = "Asia/Tokyo";
+
 export interface TouchCardResult {
   status: TouchCardStatus;
   entries: number;
   user: User;
+  /** その日最初の入室なら `true`。退室時は常に `false` */
+  firstEntryToday: boolean;
+  /** 今回より前の最後の入室日時。退室時や初めての入室では `null` */
+  lastEntryAt: Temporal.Instant | null;
 }
 
 export class TouchCardUseCase {
@@ -30,12 +40,15 @@ export class TouchCardUseCase {
   async execute({
     idm,
     studentId,
+    intent = "toggle",
   }: {
     idm: string;
     studentId?: number;
+    intent?: RoomEntryIntent;
   }): Promise<Result<TouchCardResult, TouchCardError>> {
     this.logger.info("touch card started", {
       idm,
+      intent,
       studentId,
     });
     try {
@@ -50,7 +63,11 @@ export class TouchCardUseCase {
       const user = userResult.value;
 
       // 入退室処理を実行
-      const result = await this.toggleUserRoomPresence(user);
+      const toggleResult = await this.toggleUserRoomPresence(user, intent);
+      if (toggleResult.isErr()) {
+        return err(toggleResult.error);
+      }
+      const result = toggleResult.value;
       this.logger.info("touch card completed", {
         discordId: user.discordId,
         entries: result.entries,
@@ -128,9 +145,31 @@ export class TouchCardUseCase {
     return ok(user);
   }
 
-  private async toggleUserRoomPresence(user: User): Promise<TouchCardResult> {
+  private async toggleUserRoomPresence(
+    user: User,
+    intent: RoomEntryIntent,
+  ): Promise<Result<TouchCardResult, TouchCardError>> {
     const now = Temporal.Now.instant();
-    const status = await this.roomEntryLogRepository.toggle(user.id, now);
+    // 切り替えると今回の入室が記録されるので、その前に調べておく
+    const startOfToday = now.toZonedDateTimeISO(TIME_ZONE).startOfDay().toInstant();
+    const [lastEntryAt, enteredToday] = await Promise.all([
+      this.roomEntryLogRepository.findLatestEntryAt(user.id),
+      this.roomEntryLogRepository.existsEntrySince(startOfToday),
+    ]);
+
+    const status = await this.roomEntryLogRepository.toggle(user.id, now, intent);
+    if (status === null) {
+      // 入室専用 / 退出専用リーダーで在室状態と矛盾するタッチ。記録も通知も変えない
+      return err(
+        intent === "entry"
+          ? new TouchCardError("User already entered.", {
+              meta: { code: "ALREADY_ENTERED" },
+            })
+          : new TouchCardError("User not entered.", {
+              meta: { code: "NOT_ENTERED" },
+            }),
+      );
+    }
 
     // 入室中のユーザーを取得
     const entryUsers = await this.userRepository.findAllEntryUsers();
@@ -141,11 +180,13 @@ export class TouchCardUseCase {
       userId: user.id,
     });
 
-    return {
+    return ok({
       status,
       entries: entryUsers.length,
       user,
-    };
+      firstEntryToday: status === "entry" && !enteredToday,
+      lastEntryAt: status === "entry" ? lastEntryAt : null,
+    });
   }
 }
 
@@ -157,6 +198,12 @@ type ErrorMeta =
       code: "NFC_CARD_NOT_REGISTERED";
       unknownNfcCard: UnknownNfcCard;
     }
+  | {
+      code: "ALREADY_ENTERED";
+    }
+  | {
+      code: "NOT_ENTERED";
+    }
   | {
       code: "UNKNOWN";
     };
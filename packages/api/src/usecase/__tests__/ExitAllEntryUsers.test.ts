@@ -22,6 +22,8 @@ const createMockUserRepository = () => {
 const createMockRoomEntryLogRepository = () => {
   return {
     findAllEntry: vi.fn(),
+    findLatestEntryAt: vi.fn(),
+    existsEntrySince: vi.fn(),
     setManyExitAt: vi.fn(),
     toggle: vi.fn(),
   } satisfies RoomEntryLogRepository;
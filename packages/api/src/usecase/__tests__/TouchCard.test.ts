@@ -23,6 +23,8 @@ const createMockUserRepository = () => {
 const createMockRoomEntryLogRepository = () => {
   return {
     findAllEntry: vi.fn(),
+    findLatestEntryAt: vi.fn(),
+    existsEntrySince: vi.fn(),
     setManyExitAt: vi.fn(),
     toggle: vi.fn(),
   } satisfies RoomEntryLogRepository;
@@ -114,7 +116,10 @@ describe("TouchCardUseCase", () => {
     const idm = "registered-idm";
     const userId = 1;
     const user = new User(userId, "discord-user-1");
+    const lastEntryAt = Temporal.Instant.from("2026-10-01T09:00:00Z");
     userRepository.findByNfcIdm.mockResolvedValue(user);
+    roomEntryLogRepository.findLatestEntryAt.mockResolvedValue(lastEntryAt);
+    roomEntryLogRepository.existsEntrySince.mockResolvedValue(true);
     roomEntryLogRepository.toggle.mockResolvedValue("entry");
     userRepository.findAllEntryUsers.mockResolvedValue([user]);
 
@@ -127,11 +132,14 @@ describe("TouchCardUseCase", () => {
       expect(result.value.status).toBe("entry");
       expect(result.value.entries).toBe(1);
       expect(result.value.user).toEqual(user);
+      expect(result.value.firstEntryToday).toBe(false);
+      expect(result.value.lastEntryAt).toEqual(lastEntryAt);
     }
     expect(userRepository.findByNfcIdm).toHaveBeenCalledWith(idm);
     expect(roomEntryLogRepository.toggle).toHaveBeenCalledWith(
       userId,
       expect.any(Temporal.Instant),
+      "toggle",
     );
     expect(userRepository.findAllEntryUsers).toHaveBeenCalled();
   });
@@ -145,6 +153,10 @@ describe("TouchCardUseCase", () => {
     const userId = 1;
     const user = new User(userId, "discord-user-1");
     userRepository.findByNfcIdm.mockResolvedValue(user);
+    roomEntryLogRepository.findLatestEntryAt.mockResolvedValue(
+      Temporal.Instant.from("2026-10-19T09:00:00Z"),
+    );
+    roomEntryLogRepository.existsEntrySince.mockResolvedValue(false);
     roomEntryLogRepository.toggle.mockResolvedValue("exit");
     userRepository.findAllEntryUsers.mockResolvedValue([]);
 
@@ -157,15 +169,90 @@ describe("TouchCardUseCase", () => {
       expect(result.value.status).toBe("exit");
       expect(result.value.entries).toBe(0);
       expect(result.value.user).toEqual(user);
+      expect(result.value.firstEntryToday).toBe(false);
+      expect(result.value.lastEntryAt).toBeNull();
     }
     expect(userRepository.findByNfcIdm).toHaveBeenCalledWith(idm);
     expect(roomEntryLogRepository.toggle).toHaveBeenCalledWith(
       userId,
       expect.any(Temporal.Instant),
+      "toggle",
     );
     expect(userRepository.findAllEntryUsers).toHaveBeenCalled();
   });
 
+  it("その日まだ誰も入室していなければ最初の入室として返すこと", async () => {
+    // セットアップ
+    const { useCase, userRepository, roomEntryLogRepository } = setup();
+
+    // モックの設定
+    const user = new User(1, "discord-user-1");
+    userRepository.findByNfcIdm.mockResolvedValue(user);
+    roomEntryLogRepository.findLatestEntryAt.mockResolvedValue(null);
+    roomEntryLogRepository.existsEntrySince.mockResolvedValue(false);
+    roomEntryLogRepository.toggle.mockResolvedValue("entry");
+    userRepository.findAllEntryUsers.mockResolvedValue([user]);
+
+    // 実行
+    const result = await useCase.execute({ idm: "registered-idm" });
+
+    // 検証
+    expect(result.isOk()).toBe(true);
+    if (result.isOk()) {
+      expect(result.value.firstEntryToday).toBe(true);
+      expect(result.value.lastEntryAt).toBeNull();
+    }
+    // 日本時間の 0 時以降の入室を調べること
+    const [since] = roomEntryLogRepository.existsEntrySince.mock.calls[0];
+    const startOfDay = since.toZonedDateTimeISO("Asia/Tokyo");
+    expect([startOfDay.hour, startOfDay.minute, startOfDay.second]).toEqual([0, 0, 0]);
+    expect(roomEntryLogRepository.findLatestEntryAt).toHaveBeenCalledWith(1);
+  });
+
+  it("入室専用リーダーで入室中のユーザーがタッチすると何も変えずにエラーを返すこと", async () => {
+    // セットアップ
+    const { useCase, userRepository, roomEntryLogRepository } = setup();
+
+    // モックの設定
+    const idm = "registered-idm";
+    const userId = 1;
+    userRepository.findByNfcIdm.mockResolvedValue(new User(userId, "discord-user-1"));
+    roomEntryLogRepository.toggle.mockResolvedValue(null);
+
+    // 実行
+    const result = await useCase.execute({ idm, intent: "entry" });
+
+    // 検証
+    expect(result.isErr()).toBe(true);
+    if (result.isErr()) {
+      expect(result.error.meta.code).toBe("ALREADY_ENTERED");
+    }
+    expect(roomEntryLogRepository.toggle).toHaveBeenCalledWith(
+      userId,
+      expect.any(Temporal.Instant),
+      "entry",
+    );
+    expect(userRepository.findAllEntryUsers).not.toHaveBeenCalled();
+  });
+
+  it("退出専用リーダーで入室していないユーザーがタッチするとエラーを返すこと", async () => {
+    // セットアップ
+    const { useCase, userRepository, roomEntryLogRepository } = setup();
+
+    // モックの設定
+    userRepository.findByNfcIdm.mockResolvedValue(new User(1, "discord-user-1"));
+    roomEntryLogRepository.toggle.mockResolvedValue(null);
+
+    // 実行
+    const result = await useCase.execute({ idm: "registered-idm", intent: "exit" });
+
+    // 検証
+    expect(result.isErr()).toBe(true);
+    if (result.isErr()) {
+      expect(result.error.meta.code).toBe("NOT_ENTERED");
+    }
+  });
+
   it("例外が発生した場合にエラーを返すこと", async () => {
     // セットアップ
     const { useCase, userRepository } = setup();